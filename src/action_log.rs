@@ -0,0 +1,128 @@
+use crate::config::ButtonAction;
+use crate::error::ControllerResult;
+use log::warn;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 动作日志，将每次执行的动作及结果追加写入文件
+///
+/// 文件超过 `max_bytes` 时重命名为 `<文件名>.old`（覆盖上一份）后重新开始，
+/// 最多占用约两倍上限的磁盘空间。
+pub struct ActionLog {
+    path: PathBuf,
+    max_bytes: u64,
+}
+
+impl ActionLog {
+    /// 创建动作日志写入器
+    pub fn new(path: impl Into<PathBuf>, max_bytes: u64) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes,
+        }
+    }
+
+    /// 记录一次动作执行，写入失败只打印错误，不影响动作本身
+    pub fn record(&self, action: &ButtonAction, result: &ControllerResult<()>) {
+        let outcome = match result {
+            Ok(()) => "成功".to_string(),
+            Err(e) => format!("失败: {}", e),
+        };
+        let line = format!("{} {:?} {}\n", Self::timestamp(), action, outcome);
+
+        if let Err(e) = self.append(&line) {
+            warn!("写入动作日志失败: {}", e);
+        }
+    }
+
+    fn append(&self, line: &str) -> std::io::Result<()> {
+        self.rotate_if_needed()?;
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(line.as_bytes())
+    }
+
+    /// 超过大小上限时轮转日志文件
+    fn rotate_if_needed(&self) -> std::io::Result<()> {
+        let size = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata.len(),
+            Err(_) => return Ok(()), // 文件尚不存在
+        };
+        if self.max_bytes == 0 || size < self.max_bytes {
+            return Ok(());
+        }
+
+        let mut rotated = self.path.clone().into_os_string();
+        rotated.push(".old");
+        fs::rename(&self.path, rotated)
+    }
+
+    /// Unix 时间戳（秒，精确到毫秒）
+    fn timestamp() -> String {
+        let elapsed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        format!("{}.{:03}", elapsed.as_secs(), elapsed.subsec_millis())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ControllerError;
+
+    fn read_lines(path: &std::path::Path) -> Vec<String> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn records_action_and_result() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs").join("actions.log");
+        let log = ActionLog::new(&path, 0);
+
+        log.record(&ButtonAction::Refresh, &Ok(()));
+        log.record(
+            &ButtonAction::LaunchApp("Safari".to_string()),
+            &Err(ControllerError::InputSimulation("无法启动".to_string())),
+        );
+
+        let lines = read_lines(&path);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(" Refresh 成功"), "{}", lines[0]);
+        assert!(
+            lines[1].ends_with(" LaunchApp(\"Safari\") 失败: 输入模拟错误: 无法启动"),
+            "{}",
+            lines[1]
+        );
+    }
+
+    #[test]
+    fn rotates_when_over_size_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("actions.log");
+        let log = ActionLog::new(&path, 10);
+
+        log.record(&ButtonAction::Refresh, &Ok(()));
+        log.record(&ButtonAction::NewTab, &Ok(()));
+
+        let rotated = read_lines(&dir.path().join("actions.log.old"));
+        assert_eq!(rotated.len(), 1);
+        assert!(rotated[0].contains("Refresh"));
+        let current = read_lines(&path);
+        assert_eq!(current.len(), 1);
+        assert!(current[0].contains("NewTab"));
+    }
+}
@@ -0,0 +1,279 @@
+use crate::dualsense::SONY_VENDOR_ID;
+use crate::hid::{Report, TARGET_VENDOR_ID, parser_for};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// 录制文件首行的格式标识
+const CAPTURE_HEADER: &str = "# controller-capture v1";
+
+/// 原始 HID 报告录制器
+///
+/// 文件为纯文本：首行为 `# controller-capture v1 vid=0x045E pid=0x02E0`，
+/// 之后每行一份报告，格式为 `<自开始录制经过的微秒数>,<报告字节的十六进制>`。
+/// 每份报告写入后立即落盘，Ctrl+C 结束录制也不会丢失数据。
+pub struct CaptureWriter {
+    file: File,
+    started_at: Instant,
+}
+
+impl CaptureWriter {
+    /// 创建录制文件并写入文件头（已存在的文件会被覆盖）
+    pub fn create<P: AsRef<Path>>(path: P, vendor_id: u16, product_id: u16) -> io::Result<Self> {
+        let mut file = File::create(path)?;
+        writeln!(
+            file,
+            "{} vid={:#06X} pid={:#06X}",
+            CAPTURE_HEADER, vendor_id, product_id
+        )?;
+        Ok(Self {
+            file,
+            started_at: Instant::now(),
+        })
+    }
+
+    /// 追加一份原始报告
+    pub fn record(&mut self, report: &[u8]) -> io::Result<()> {
+        let hex: String = report.iter().map(|byte| format!("{:02x}", byte)).collect();
+        writeln!(
+            self.file,
+            "{},{}",
+            self.started_at.elapsed().as_micros(),
+            hex
+        )
+    }
+}
+
+/// 录制的一份原始报告
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapturedReport {
+    /// 自开始录制经过的时间
+    pub elapsed: Duration,
+    /// 报告原始字节
+    pub data: Vec<u8>,
+}
+
+/// 读取后的录制文件
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capture {
+    /// 录制时手柄的厂商ID
+    pub vendor_id: u16,
+    /// 录制时手柄的产品ID
+    pub product_id: u16,
+    pub reports: Vec<CapturedReport>,
+}
+
+impl Capture {
+    /// 从文件读取录制内容
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let content =
+            fs::read_to_string(path.as_ref()).map_err(|e| format!("读取录制文件失败: {}", e))?;
+        Self::parse(&content)
+    }
+
+    /// 解析录制文件内容
+    ///
+    /// 早期录制文件的文件头只有 `pid=`，厂商ID按 Xbox 手柄处理。
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut lines = content.lines();
+        let header = lines.next().unwrap_or_default();
+        let (vendor_id, product_id) = header
+            .strip_prefix(CAPTURE_HEADER)
+            .and_then(Self::parse_ids)
+            .ok_or_else(|| format!("无法识别的录制文件头: {}", header))?;
+
+        let reports = lines
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                Self::parse_report(line)
+                    .ok_or_else(|| format!("第 {} 行格式错误: {}", index + 2, line))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            vendor_id,
+            product_id,
+            reports,
+        })
+    }
+
+    /// 解析文件头中的 `vid=0x.... pid=0x....` 字段
+    fn parse_ids(fields: &str) -> Option<(u16, u16)> {
+        let mut vendor_id = TARGET_VENDOR_ID;
+        let mut product_id = None;
+        for field in fields.split_whitespace() {
+            let (name, value) = field.split_once("=0x")?;
+            let value = u16::from_str_radix(value, 16).ok()?;
+            match name {
+                "vid" => vendor_id = value,
+                "pid" => product_id = Some(value),
+                _ => return None,
+            }
+        }
+        Some((vendor_id, product_id?))
+    }
+
+    fn parse_report(line: &str) -> Option<CapturedReport> {
+        let (micros, hex) = line.trim().split_once(',')?;
+        if hex.len() % 2 != 0 {
+            return None;
+        }
+        let data = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+        Some(CapturedReport {
+            elapsed: Duration::from_micros(micros.parse().ok()?),
+            data,
+        })
+    }
+
+    /// 逐份解析报告并打印各字段的值
+    pub fn print_analysis(&self, analog_trigger_threshold: u8) {
+        let parser = parser_for(self.vendor_id, self.product_id);
+        let device = if self.vendor_id == SONY_VENDOR_ID {
+            "DualSense"
+        } else {
+            "Xbox"
+        };
+        println!(
+            "厂商ID: {:#06X}，产品ID: {:#06X}（{} 报告格式），共 {} 份报告",
+            self.vendor_id,
+            self.product_id,
+            device,
+            self.reports.len()
+        );
+
+        for report in &self.reports {
+            let time = report.elapsed.as_secs_f64() * 1000.0;
+            match Report::parse(&report.data, analog_trigger_threshold, parser.as_ref()) {
+                Report::Input(state) => {
+                    let mut buttons: Vec<u8> = state.pressed_buttons.iter().copied().collect();
+                    buttons.sort_unstable();
+                    let buttons: Vec<String> =
+                        buttons.iter().map(|b| format!("{:#04X}", b)).collect();
+                    println!(
+                        "[{:>10.3}ms] 输入 L=({}, {}) R=({}, {}) LT={} RT={} 陀螺仪=({}, {}) 按键=[{}]",
+                        time,
+                        state.lx,
+                        state.ly,
+                        state.rx,
+                        state.ry,
+                        state.lt,
+                        state.rt,
+                        state.gyro_yaw,
+                        state.gyro_pitch,
+                        buttons.join(", ")
+                    );
+                }
+                Report::Battery(status) => println!(
+                    "[{:>10.3}ms] 电量 {}%{}",
+                    time,
+                    status.level,
+                    if status.charging {
+                        "（充电中）"
+                    } else {
+                        ""
+                    }
+                ),
+                Report::Unknown => {
+                    println!("[{:>10.3}ms] 未知报告 ({} 字节)", time, report.data.len())
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hid::{BUTTON_A, BUTTON_START, BatteryStatus, DPAD_UP};
+    use std::collections::HashSet;
+
+    const XBOX_WIRELESS_CAPTURE: &str = include_str!("../testdata/xbox_wireless.capture");
+
+    #[test]
+    fn writer_output_parses_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reports.capture");
+        let reports: [&[u8]; 2] = [&[0x01, 0x00, 0xFF], &[0x04, 0x12]];
+
+        let mut writer = CaptureWriter::create(&path, SONY_VENDOR_ID, 0x0CE6).unwrap();
+        for report in reports {
+            writer.record(report).unwrap();
+        }
+        drop(writer);
+
+        let capture = Capture::load(&path).unwrap();
+        assert_eq!(capture.vendor_id, SONY_VENDOR_ID);
+        assert_eq!(capture.product_id, 0x0CE6);
+        let data: Vec<&[u8]> = capture.reports.iter().map(|r| r.data.as_slice()).collect();
+        assert_eq!(data, reports);
+        assert!(capture.reports[0].elapsed <= capture.reports[1].elapsed);
+    }
+
+    #[test]
+    fn legacy_header_defaults_to_xbox_vendor() {
+        let capture = Capture::parse("# controller-capture v1 pid=0x0B13\n5,0412\n").unwrap();
+        assert_eq!(capture.vendor_id, TARGET_VENDOR_ID);
+        assert_eq!(capture.product_id, 0x0B13);
+        assert_eq!(capture.reports[0].elapsed, Duration::from_micros(5));
+    }
+
+    #[test]
+    fn malformed_lines_are_rejected() {
+        assert!(Capture::parse("not a capture\n").is_err());
+        assert!(Capture::parse("# controller-capture v1 pid=0x02E0\n0,abc\n").is_err());
+        assert!(Capture::parse("# controller-capture v1 pid=0x02E0\n0,zz\n").is_err());
+    }
+
+    #[test]
+    fn xbox_fixture_decodes_through_the_report_parser() {
+        let capture = Capture::parse(XBOX_WIRELESS_CAPTURE).unwrap();
+        let parser = parser_for(capture.vendor_id, capture.product_id);
+        let reports: Vec<Report> = capture
+            .reports
+            .iter()
+            .map(|report| Report::parse(&report.data, 20, parser.as_ref()))
+            .collect();
+
+        let [idle, stick, gyro, battery, released] = reports.as_slice() else {
+            panic!("fixture should contain five reports");
+        };
+        for report in [idle, released] {
+            match report {
+                Report::Input(state) => {
+                    assert!(state.pressed_buttons.is_empty());
+                    assert_eq!((state.lx, state.ly, state.rx, state.ry), (0, 0, 0, 0));
+                }
+                _ => panic!("idle report should decode as input"),
+            }
+        }
+
+        let Report::Input(stick) = stick else {
+            panic!("stick report should decode as input");
+        };
+        assert_eq!(stick.pressed_buttons, HashSet::from([BUTTON_A]));
+        assert_eq!(stick.lx, i16::MAX);
+        assert_eq!(stick.ry, i16::MAX);
+        assert_eq!((stick.gyro_yaw, stick.gyro_pitch), (0, 0));
+
+        // 按住 LT 时才解析陀螺仪，12 位有符号数
+        let Report::Input(gyro) = gyro else {
+            panic!("gyro report should decode as input");
+        };
+        assert_eq!(gyro.lt, 255);
+        assert_eq!(gyro.pressed_buttons, HashSet::from([DPAD_UP, BUTTON_START]));
+        assert_eq!((gyro.gyro_yaw, gyro.gyro_pitch), (16, -224));
+
+        assert!(matches!(
+            battery,
+            Report::Battery(BatteryStatus {
+                level: 66,
+                charging: true
+            })
+        ));
+    }
+}
@@ -0,0 +1,95 @@
+use std::path::PathBuf;
+
+/// 命令行用法说明
+pub const USAGE: &str = "用法: controller [选项]
+
+选项:
+  --config <文件>    使用指定的配置文件（默认 ~/.config/controller/config.toml）
+  --profile <名称>   使用指定的配置档案，覆盖配置文件中的 profile
+  --capture <文件>   录制手柄的原始 HID 报告到文件（按 Ctrl+C 结束）
+  --analyze <文件>   解析录制文件并打印每份报告的字段值
+  --calibrate        测量摇杆静止漂移，并建议写入配置文件的死区
+  --tune             运行时从标准输入读取调参命令（如 sens +1、save，输入 help 查看）
+  -h, --help         显示此帮助信息";
+
+/// 程序运行模式
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mode {
+    /// 正常运行，用手柄控制电脑
+    Run,
+    /// 录制原始报告
+    Capture(PathBuf),
+    /// 解析录制文件
+    Analyze(PathBuf),
+    /// 测量摇杆漂移并校准死区
+    Calibrate,
+    /// 显示帮助信息
+    Help,
+}
+
+/// 解析后的命令行参数
+#[derive(Debug, Clone, PartialEq)]
+pub struct Args {
+    pub mode: Mode,
+    /// 配置文件路径，未指定时使用默认路径
+    pub config_path: Option<PathBuf>,
+    /// 配置档案名称，覆盖配置文件中的 profile
+    pub profile: Option<String>,
+    /// 正常运行时是否从标准输入读取调参命令
+    pub tune: bool,
+}
+
+/// 解析命令行参数（不含程序名）
+pub fn parse_args<I>(args: I) -> Result<Args, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+    let mut parsed = Args {
+        mode: Mode::Run,
+        config_path: None,
+        profile: None,
+        tune: false,
+    };
+
+    while let Some(arg) = args.next() {
+        let next_mode = match arg.as_str() {
+            "--config" => {
+                parsed.config_path = Some(next_path(&mut args, &arg)?);
+                continue;
+            }
+            "--profile" => {
+                let name = args
+                    .next()
+                    .ok_or_else(|| format!("{} 需要指定档案名称", arg))?;
+                parsed.profile = Some(name);
+                continue;
+            }
+            "--capture" => Mode::Capture(next_path(&mut args, &arg)?),
+            "--analyze" => Mode::Analyze(next_path(&mut args, &arg)?),
+            "--calibrate" => Mode::Calibrate,
+            "--tune" => {
+                parsed.tune = true;
+                continue;
+            }
+            "-h" | "--help" => {
+                parsed.mode = Mode::Help;
+                return Ok(parsed);
+            }
+            _ => return Err(format!("未知参数: {}", arg)),
+        };
+        if parsed.mode != Mode::Run {
+            return Err("--capture、--analyze 和 --calibrate 只能指定一个".to_string());
+        }
+        parsed.mode = next_mode;
+    }
+
+    Ok(parsed)
+}
+
+/// 读取选项后面的文件路径参数
+fn next_path(args: &mut impl Iterator<Item = String>, option: &str) -> Result<PathBuf, String> {
+    args.next()
+        .map(PathBuf::from)
+        .ok_or_else(|| format!("{} 需要指定文件路径", option))
+}
@@ -1,33 +1,689 @@
+use crate::error::{ControllerError, ControllerResult};
+use crate::input_handler::InputHandler;
+use log::info;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+
+/// 热重载时检查配置文件修改时间的间隔
+const CONFIG_WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// S 形加速曲线陡度的下限，过小时曲线两端几乎重合，归一化会因精度损失而失真
+const MIN_SIGMOID_STEEPNESS: f64 = 1.0;
+
+/// 双击两次点击之间间隔的上限（毫秒），超过系统的双击判定时间会被识别为两次单击
+const MAX_DOUBLE_CLICK_INTERVAL_MS: u64 = 500;
+
+/// 多键同按组合的时间窗口上限（毫秒），过长会让参与组合的按键单独按下时明显延迟
+const MAX_CHORD_WINDOW_MS: u64 = 500;
+
+/// 默认配置模板中的字段说明：(所在表, 字段名, 说明)，字段名为空表示表本身
+const FIELD_DOCS: &[(&str, &str, &str)] = &[
+    (
+        "",
+        "analog_trigger_threshold",
+        "模拟扳机阈值 (0-255)，超过该值视为按下",
+    ),
+    (
+        "",
+        "hid_report_size",
+        "读取 HID 报告的缓冲区大小 (字节)，小于手柄报告格式所需长度时按所需长度读取，重新连接后生效",
+    ),
+    ("", "joystick_deadzone", "左摇杆死区 (0-32767)"),
+    ("", "right_joystick_deadzone", "右摇杆死区 (0-32767)"),
+    (
+        "",
+        "deadzone_shape",
+        "左摇杆死区形状: Axial（各轴分别计算）/ Radial（按推动幅度计算，斜向更平滑）",
+    ),
+    (
+        "",
+        "deadzone_hysteresis",
+        "死区回差，摇杆静止后死区扩大该值以减少抖动 (0=关闭)",
+    ),
+    (
+        "",
+        "stick_click_guard_threshold",
+        "摇杆按下(L3/R3)防误触阈值，摇杆偏移超过该值时忽略按下 (0=关闭)",
+    ),
+    ("", "gyro_deadzone", "陀螺仪死区 (0-32767)"),
+    (
+        "",
+        "nav_trigger_threshold",
+        "右摇杆左右导航触发阈值 (0-32767)",
+    ),
+    (
+        "",
+        "dominant_axis_factor",
+        "主导轴系数 (>1.0)，一个轴需超过另一轴该倍数才判定方向",
+    ),
+    (
+        "",
+        "joystick_sensitivity",
+        "左摇杆灵敏度 (5.0-30.0)，推到底时光标每秒约移动 灵敏度×100 像素",
+    ),
+    (
+        "",
+        "joystick_sensitivity_x",
+        "左摇杆水平方向灵敏度，未设置时使用 joystick_sensitivity",
+    ),
+    (
+        "",
+        "joystick_sensitivity_y",
+        "左摇杆垂直方向灵敏度，未设置时使用 joystick_sensitivity",
+    ),
+    ("", "gyro_sensitivity", "陀螺仪灵敏度 (0.01-0.2)"),
+    (
+        "",
+        "gyro_active_stick_scale",
+        "按住LT使用陀螺仪时左摇杆灵敏度的缩放系数 (1.0=不变)",
+    ),
+    (
+        "",
+        "rt_speed_multiplier",
+        "RT 加速倍率 (0=关闭)，按住RT时左摇杆速度最多提高到 1+倍率 倍",
+    ),
+    (
+        "",
+        "speed_modifier_multiplier",
+        "光标加速倍率 (>0)，按住 speed_modifier_button 设置的按键时光标位移乘以该倍率",
+    ),
+    (
+        "",
+        "speed_modifier_button",
+        "光标加速修饰键（按键名称，如 \"RB\"），未设置时不启用",
+    ),
+    ("", "direct_scroll_sensitivity", "滚动灵敏度 (5.0-50.0)"),
+    (
+        "",
+        "scroll_mode",
+        "滚动方式 (smooth=平滑滚动, line=按行即时滚动)",
+    ),
+    (
+        "",
+        "invert_scroll",
+        "反转滚动方向，与系统自然滚动设置冲突时开启",
+    ),
+    ("", "max_scroll_power", "滚动力度上限 (0=不限制)"),
+    (
+        "",
+        "min_output_pixels",
+        "光标每帧最小位移 (像素)，不足时累积到后续帧，用于抑制抖动；0 与 1 相同，只跳过不足一像素的帧",
+    ),
+    ("", "pacer_loop_hz", "滚动步调器频率 (30-120 Hz)"),
+    (
+        "",
+        "main_loop_max_hz",
+        "主循环最高频率 (Hz, 0=不限制)，每帧取出全部积压的报告并响应最新的一份",
+    ),
+    (
+        "",
+        "scroll_ramp_up",
+        "滚动加速系数 (0.0-1.0]，每次步调器循环实际滚动力度向目标靠近的比例，1.0=立即达到",
+    ),
+    (
+        "",
+        "scroll_ramp_down",
+        "滚动减速系数 (0.0-1.0]，松开摇杆后滚动力度每次循环回落的比例，1.0=立即停止",
+    ),
+    (
+        "",
+        "min_click_hold_ms",
+        "鼠标点击的最短按住时间 (毫秒, 0=不补足)",
+    ),
+    (
+        "",
+        "double_click_interval_ms",
+        "DoubleClick 动作两次点击之间的间隔 (1-500 毫秒)",
+    ),
+    (
+        "",
+        "max_actions_per_second",
+        "每秒最多执行的动作数 (0=不限制)",
+    ),
+    (
+        "",
+        "lone_trigger_max_ms",
+        "单独轻按 LT/RT 的最长按住时间 (毫秒)",
+    ),
+    (
+        "",
+        "chord_window_ms",
+        "多键同按组合（如 \"A+B\"）中各键先后按下的最大间隔 (0-500 毫秒, 0=只识别同一帧按下)",
+    ),
+    (
+        "",
+        "stick_hold_ms",
+        "右摇杆方向从轻拨 (_Flick) 切换为按住 (_Hold) 的时间 (毫秒)",
+    ),
+    (
+        "",
+        "drift_check_ms",
+        "连接时采样摇杆静止偏移的时长 (毫秒, 0=不检查)",
+    ),
+    ("", "rumble_on_action", "执行绑定的动作时手柄短促震动"),
+    (
+        "",
+        "zoom_scroll_button",
+        "缩放滚动修饰键（按键名称），按住时右摇杆滚动改为缩放",
+    ),
+    (
+        "",
+        "preset",
+        "调校预设: precise / balanced / fast，未单独设置的灵敏度、死区和加速曲线取预设值",
+    ),
+    (
+        "",
+        "action_log_path",
+        "动作日志文件路径，设置后每次执行的动作及结果都会追加写入该文件",
+    ),
+    (
+        "",
+        "default_action",
+        "未绑定按键的默认动作（未设置则不执行任何操作）",
+    ),
+    (
+        "",
+        "action_log_max_bytes",
+        "动作日志大小上限 (字节, 0=不限制)，需设置 action_log_path 启用日志",
+    ),
+    (
+        "",
+        "gyro_yaw_target",
+        "陀螺仪左右转动的输出: CursorX / CursorY / ScrollV / ScrollH / None",
+    ),
+    (
+        "",
+        "gyro_pitch_target",
+        "陀螺仪上下转动的输出: CursorX / CursorY / ScrollV / ScrollH / None",
+    ),
+    (
+        "",
+        "gyro_smoothing_factor",
+        "陀螺仪平滑系数 (0.0-1.0)，越大抖动越小但响应越慢，0=不平滑",
+    ),
+    (
+        "",
+        "cursor_source",
+        "移动光标的输入: LeftStick / Gyro / Both / None (None=只用于滚动和按键)",
+    ),
+    ("", "gyro_invert_yaw", "反转陀螺仪左右转动的方向"),
+    ("", "gyro_invert_pitch", "反转陀螺仪上下转动的方向"),
+    (
+        "",
+        "gyro_swap_axes",
+        "交换陀螺仪两个轴：左右转动按 gyro_pitch_target 输出，上下转动按 gyro_yaw_target 输出",
+    ),
+    (
+        "",
+        "output_backend",
+        "输出后端: Enigo / CoreGraphics (仅macOS)",
+    ),
+    (
+        "",
+        "platform",
+        "内置动作按哪个系统的快捷键发送: MacOS (主修饰键 Cmd) / Linux / Windows (主修饰键 Ctrl)",
+    ),
+    (
+        "",
+        "primary_modifier",
+        "覆盖主修饰键（如 \"cmd\"、\"ctrl\"），未设置时按 platform 选择",
+    ),
+    (
+        "",
+        "dry_run",
+        "试运行：只打印将要执行的操作，不实际移动光标或发送按键",
+    ),
+    ("", "nav_method", "右摇杆左右导航方式: Shortcut / Swipe"),
+    (
+        "",
+        "right_stick_horizontal_mode",
+        "右摇杆左右推动: Navigate (前进/后退) / HorizontalScroll (水平滚动)",
+    ),
+    (
+        "",
+        "scroll_source",
+        "滚动来源: RightStick / Triggers (RT向上、LT向下，扳机不再作为修饰键)",
+    ),
+    (
+        "",
+        "dual_stick_policy",
+        "两个摇杆同时推动时: Independent / LeftPriority / RightPriority",
+    ),
+    (
+        "",
+        "input_panic_policy",
+        "输入处理发生panic时: Continue / Exit",
+    ),
+    (
+        "",
+        "recenter_cursor_on_connect",
+        "每次连接成功后将光标移动到屏幕中央",
+    ),
+    ("", "on_connect_actions", "连接成功后依次执行的动作"),
+    ("", "profile", "启动时使用的配置档案名称"),
+    (
+        "acceleration_curve",
+        "",
+        "左摇杆加速曲线，摇杆偏移到光标速度的映射",
+    ),
+    (
+        "acceleration_curve",
+        "type",
+        "曲线类型: Linear（线性）/ Exponential（指数，需设置 power）/ Sigmoid（S 形，需设置 steepness）",
+    ),
+    (
+        "acceleration_curve",
+        "power",
+        "指数曲线的指数 (>0)，越大中心附近越精细",
+    ),
+    (
+        "acceleration_curve",
+        "steepness",
+        "S 形曲线的陡度 (>=1.0)，建议 5.0-15.0",
+    ),
+    (
+        "trigger_stages",
+        "",
+        "两段式扳机：轻按执行 LT_Light/RT_Light，按到底执行 LT_Full/RT_Full",
+    ),
+    ("trigger_stages", "enabled", "是否启用两段式扳机"),
+    ("trigger_stages", "light_threshold", "轻按阈值 (0-255)"),
+    (
+        "trigger_stages",
+        "full_threshold",
+        "按到底阈值 (0-255)，需大于轻按阈值",
+    ),
+    (
+        "trigger_stages",
+        "hysteresis",
+        "回差，回落时需低于阈值减去该值才退回上一段",
+    ),
+    (
+        "grid_snap",
+        "",
+        "光标网格吸附：光标只停在每隔 size 像素的网格点上",
+    ),
+    ("grid_snap", "enabled", "是否启用网格吸附"),
+    ("grid_snap", "size", "网格间距 (像素, >0)"),
+    (
+        "cursor_momentum",
+        "",
+        "光标惯性：摇杆回中后光标以逐帧衰减的速度继续滑行",
+    ),
+    ("cursor_momentum", "enabled", "是否启用光标惯性"),
+    (
+        "cursor_momentum",
+        "decay",
+        "速度每 10 毫秒的衰减系数 (0.0-1.0)，越大滑行越远",
+    ),
+    (
+        "gyro_stick_suppression",
+        "",
+        "左摇杆推出死区时削弱陀螺仪输出，避免两种输入叠加",
+    ),
+    ("gyro_stick_suppression", "enabled", "是否启用陀螺仪抑制"),
+    (
+        "gyro_stick_suppression",
+        "gyro_scale",
+        "摇杆推动期间陀螺仪输出的缩放系数 (0.0-1.0, 0=完全停用)",
+    ),
+    (
+        "arming",
+        "",
+        "解锁组合键：启用后需先同时按下 arm_chord 中的按键，手柄才开始控制电脑",
+    ),
+    ("arming", "require_arm", "是否需要先解锁"),
+    ("arming", "arm_chord", "解锁组合键（按键名称，需同时按下）"),
+    (
+        "arming",
+        "disarm_chord",
+        "重新锁定的组合键（为空则解锁后一直保持到程序退出）",
+    ),
+    ("reconnection", "", "自动重连配置"),
+    (
+        "reconnection",
+        "enable_auto_reconnect",
+        "手柄断开后是否等待重连 (false=直接退出)",
+    ),
+    (
+        "reconnection",
+        "reconnect_interval_ms",
+        "重连尝试间隔 (毫秒)，启用退避时为首次等待的间隔",
+    ),
+    (
+        "reconnection",
+        "reconnect_backoff_multiplier",
+        "重连退避倍率 (>=1.0)，每次失败后间隔乘以该值 (1.0=固定间隔)",
+    ),
+    (
+        "reconnection",
+        "reconnect_max_interval_ms",
+        "退避后重连间隔的上限 (毫秒)",
+    ),
+    (
+        "reconnection",
+        "max_reconnect_attempts",
+        "最大重连次数 (0=无限)",
+    ),
+    (
+        "reconnection",
+        "show_reconnect_messages",
+        "是否显示重连消息",
+    ),
+    (
+        "reconnection",
+        "max_silent_failures",
+        "连续失败超过该次数后才显示重连消息",
+    ),
+    (
+        "reconnection",
+        "disconnect_grace_ms",
+        "断线宽限期 (毫秒, 0=立即释放按住的按键)",
+    ),
+    (
+        "reconnection",
+        "on_initial_failure",
+        "初始连接失败时: Wait / Silent / Beep",
+    ),
+    (
+        "reconnection",
+        "reprint_instructions",
+        "重连后是否重新打印操作说明 (false=只在首次连接时打印)",
+    ),
+    (
+        "reconnection",
+        "initial_connect_timeout_ms",
+        "从未连接时的等待超时 (毫秒, 0=一直等待)",
+    ),
+    ("multi_controller", "", "多手柄配置"),
+    (
+        "multi_controller",
+        "max_devices",
+        "同时连接的手柄数量上限 (1=只连接第一个找到的手柄)",
+    ),
+    (
+        "multi_controller",
+        "device_profiles",
+        "按连接顺序为手柄指定配置档案，空字符串表示共用当前配置（同组手柄的输入合并）",
+    ),
+    ("shortcuts", "", "内置动作使用的快捷键"),
+    ("shortcuts.scroll_to_top", "", "ScrollToTop 使用的快捷键"),
+    (
+        "shortcuts.scroll_to_bottom",
+        "",
+        "ScrollToBottom 使用的快捷键",
+    ),
+    ("shortcuts.lock_screen", "", "LockScreen 使用的快捷键"),
+    (
+        "shortcuts.paste_plain",
+        "",
+        "PastePlain 使用的快捷键，部分应用使用 Cmd+Shift+V",
+    ),
+    (
+        "shortcuts.toggle_fullscreen",
+        "",
+        "ToggleFullscreen 使用的快捷键",
+    ),
+    (
+        "shortcuts.minimize_window",
+        "",
+        "MinimizeWindow 使用的快捷键",
+    ),
+    ("shortcuts.hide_app", "", "HideApp 使用的快捷键"),
+    (
+        "shortcuts.start_dictation",
+        "",
+        "StartDictation 使用的快捷键，需与系统设置 > 键盘 > 听写中的快捷键一致",
+    ),
+    ("profiles", "", "配置档案：按名称覆盖部分灵敏度和按键绑定"),
+    (
+        "binding_enabled",
+        "",
+        "按键绑定启用状态，设为 false 可暂时停用绑定而不删除（未列出的绑定默认启用）",
+    ),
+    (
+        "repeat_actions",
+        "",
+        "按住后重复执行动作的按键（按键名称 -> 重复参数）",
+    ),
+    (
+        "combo_mappings",
+        "",
+        "组合键绑定（\"修饰键+按键\"），与 button_mappings 中的同名绑定同时存在时以此处为准",
+    ),
+    (
+        "button_mappings",
+        "",
+        "按键绑定：支持单独按键、LT组合键和右摇杆方向；媒体遥控示例: \"DPad_Up\" = \"VolumeUp\"（另有 VolumeDown / Mute / BrightnessUp / BrightnessDown）",
+    ),
+];
 
 /// 控制器配置结构体
+///
+/// 基本参数和按键绑定之外的字段都有默认值，旧版本的配置文件可以直接加载
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ControllerConfig {
     /// 模拟扳机阈值
     pub analog_trigger_threshold: u8,
+    /// 读取 HID 报告的缓冲区大小（字节），不会小于手柄报告格式所需的长度
+    #[serde(default = "default_hid_report_size")]
+    pub hid_report_size: usize,
     /// 左摇杆死区
+    #[serde(default = "default_joystick_deadzone")]
     pub joystick_deadzone: i16,
     /// 右摇杆死区
+    #[serde(default = "default_right_joystick_deadzone")]
     pub right_joystick_deadzone: i16,
+    /// 左摇杆死区形状
+    #[serde(default)]
+    pub deadzone_shape: DeadzoneShape,
+    /// 摇杆死区回差：摇杆静止在死区内时，需超出死区加回差才视为离开（0表示关闭）
+    #[serde(default)]
+    pub deadzone_hysteresis: i16,
+    /// 摇杆按下（L3/R3）防误触阈值：按下时对应摇杆偏移超过该值则忽略本次按下（0表示关闭）
+    #[serde(default)]
+    pub stick_click_guard_threshold: i16,
     /// 陀螺仪死区
+    #[serde(default = "default_gyro_deadzone")]
     pub gyro_deadzone: i16,
     /// 页面导航触发阈值
     pub nav_trigger_threshold: i16,
     /// 主导轴系数
     pub dominant_axis_factor: f64,
-    /// 左摇杆灵敏度
+    /// 左摇杆灵敏度：推到底时光标每 10 毫秒移动的像素数（按实际经过时间换算）
+    #[serde(default = "default_joystick_sensitivity")]
     pub joystick_sensitivity: f64,
+    /// 左摇杆水平方向灵敏度，未设置时使用 `joystick_sensitivity`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub joystick_sensitivity_x: Option<f64>,
+    /// 左摇杆垂直方向灵敏度，未设置时使用 `joystick_sensitivity`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub joystick_sensitivity_y: Option<f64>,
+    /// 左摇杆移动光标的加速曲线
+    #[serde(default)]
+    pub acceleration_curve: AccelerationCurve,
     /// 陀螺仪灵敏度
+    #[serde(default = "default_gyro_sensitivity")]
     pub gyro_sensitivity: f64,
+    /// 陀螺仪启用（按住LT）时左摇杆灵敏度的缩放系数
+    #[serde(default = "default_gyro_active_stick_scale")]
+    pub gyro_active_stick_scale: f64,
+    /// RT 加速倍率：左摇杆移动量乘以 1 + RT按下程度 × 倍率（0表示关闭）
+    #[serde(default)]
+    pub rt_speed_multiplier: f64,
+    /// 光标加速修饰键：按住该键时光标位移乘以 `speed_modifier_multiplier`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed_modifier_button: Option<String>,
+    /// 按住光标加速修饰键时的位移倍率
+    #[serde(default = "default_speed_modifier_multiplier")]
+    pub speed_modifier_multiplier: f64,
     /// 直接滚动灵敏度
+    #[serde(default = "default_direct_scroll_sensitivity")]
     pub direct_scroll_sensitivity: f64,
+    /// 滚动方式：平滑滚动或按行即时滚动，兼容旧版本的 `scroll_smooth = true/false`
+    #[serde(
+        default,
+        alias = "scroll_smooth",
+        deserialize_with = "deserialize_scroll_mode"
+    )]
+    pub scroll_mode: ScrollMode,
+    /// 是否反转滚动方向（垂直和水平同时反转）
+    #[serde(default)]
+    pub invert_scroll: bool,
+    /// 滚动力度上限（绝对值，0表示不限制）
+    #[serde(default)]
+    pub max_scroll_power: f64,
+    /// 光标每帧最小输出位移（像素），不足时累积到后续帧再移动；至少为一像素，0 与 1 相同
+    #[serde(default, alias = "min_cursor_delta")]
+    pub min_output_pixels: u32,
     /// 步调器循环频率 (Hz)
     pub pacer_loop_hz: u64,
+    /// 主循环最高频率 (Hz)，每帧提前完成时休眠剩余时间（0表示不限制）
+    #[serde(default = "default_main_loop_max_hz")]
+    pub main_loop_max_hz: u64,
+    /// 滚动加速系数：滚动力度增大时，每次步调器循环向目标力度靠近的比例（1.0 表示立即达到）
+    #[serde(default = "default_scroll_ramp_up")]
+    pub scroll_ramp_up: f64,
+    /// 滚动减速系数：滚动力度减小或停止时，每次步调器循环向目标力度靠近的比例（1.0 表示立即停止）
+    #[serde(default = "default_scroll_ramp_down")]
+    pub scroll_ramp_down: f64,
+    /// 鼠标点击的最短按住时间（毫秒），0 表示不补足
+    #[serde(default)]
+    pub min_click_hold_ms: u64,
+    /// `DoubleClick` 动作两次点击之间的间隔（毫秒）
+    #[serde(default = "default_double_click_interval_ms")]
+    pub double_click_interval_ms: u64,
+    /// 每秒最多执行的动作数，防止故障手柄疯狂触发（0 表示不限制）
+    #[serde(default)]
+    pub max_actions_per_second: u32,
+    /// 单独轻按扳机的最长按住时间（毫秒），超过则不触发 "LT"/"RT" 绑定
+    #[serde(default = "default_lone_trigger_max_ms")]
+    pub lone_trigger_max_ms: u64,
+    /// 多键同按组合的时间窗口（毫秒）：参与组合的按键按下后等待该时长，期间按齐组合即执行组合绑定
+    #[serde(default = "default_chord_window_ms")]
+    pub chord_window_ms: u64,
+    /// 右摇杆推入方向超过该时长（毫秒）视为按住，执行 "_Hold" 绑定而非 "_Flick" 绑定
+    #[serde(default = "default_stick_hold_ms")]
+    pub stick_hold_ms: u64,
+    /// 连接时采样摇杆静止位置的时长（毫秒），偏移超出死区时提示摇杆漂移（0表示不检查）
+    #[serde(default)]
+    pub drift_check_ms: u64,
+    /// 执行绑定的动作时手柄是否短促震动
+    #[serde(default)]
+    pub rumble_on_action: bool,
+    /// 缩放滚动修饰键：按住该键时右摇杆滚动改为 Cmd+滚动（缩放）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zoom_scroll_button: Option<String>,
+    /// 调校预设名称（precise / balanced / fast），未单独设置的灵敏度、死区和加速曲线取预设值
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    /// 动作日志文件路径，设置后每次执行的动作及结果都会追加写入该文件
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action_log_path: Option<String>,
+    /// 未绑定按键的默认动作，按下任何没有绑定的按键时执行（未设置则不执行任何操作）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_action: Option<ButtonAction>,
+    /// 动作日志文件大小上限（字节），超过后轮转（0表示不限制）
+    #[serde(default = "default_action_log_max_bytes")]
+    pub action_log_max_bytes: u64,
+    /// 陀螺仪偏航轴（左右转动）的输出目标
+    #[serde(default = "default_gyro_yaw_target")]
+    pub gyro_yaw_target: GyroTarget,
+    /// 陀螺仪俯仰轴（上下转动）的输出目标
+    #[serde(default = "default_gyro_pitch_target")]
+    pub gyro_pitch_target: GyroTarget,
+    /// 控制光标移动的输入来源
+    #[serde(default)]
+    pub cursor_source: CursorSource,
+    /// 陀螺仪平滑系数：平滑值 = 系数 × 上一帧平滑值 + (1 - 系数) × 当前值（0表示不平滑）
+    #[serde(default)]
+    pub gyro_smoothing_factor: f64,
+    /// 反转陀螺仪偏航轴的方向
+    #[serde(default)]
+    pub gyro_invert_yaw: bool,
+    /// 反转陀螺仪俯仰轴的方向
+    #[serde(default)]
+    pub gyro_invert_pitch: bool,
+    /// 交换陀螺仪两个轴的输出目标（在反转之后应用）
+    #[serde(default)]
+    pub gyro_swap_axes: bool,
+    /// 输出后端
+    #[serde(default)]
+    pub output_backend: OutputBackend,
+    /// 内置动作使用哪个操作系统的快捷键（决定主修饰键是 Cmd 还是 Ctrl）
+    #[serde(default = "Platform::current")]
+    pub platform: Platform,
+    /// 覆盖主修饰键（键名格式与 `CustomShortcut` 相同，如 "cmd"、"ctrl"），未设置时按 `platform` 选择
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary_modifier: Option<String>,
+    /// 试运行：只打印将要执行的操作，不实际移动光标或发送按键
+    #[serde(default)]
+    pub dry_run: bool,
+    /// 每次连接（包括重连）成功后将光标移动到屏幕中央
+    #[serde(default)]
+    pub recenter_cursor_on_connect: bool,
+    /// 右摇杆左右导航（前进/后退）的方式
+    #[serde(default)]
+    pub nav_method: NavMethod,
+    /// 右摇杆左右推动的用途（导航或水平滚动）
+    #[serde(default)]
+    pub right_stick_horizontal_mode: RightStickHorizontalMode,
+    /// 滚动来源（右摇杆或扳机）
+    #[serde(default)]
+    pub scroll_source: ScrollSource,
+    /// 两个摇杆同时推动时的仲裁策略
+    #[serde(default)]
+    pub dual_stick_policy: DualStickPolicy,
+    /// 输入处理发生 panic 时的处理策略
+    #[serde(default)]
+    pub input_panic_policy: InputPanicPolicy,
+    /// 两段式扳机配置
+    #[serde(default)]
+    pub trigger_stages: TriggerStageConfig,
+    /// 光标网格吸附配置
+    #[serde(default)]
+    pub grid_snap: GridSnapConfig,
+    /// 光标惯性配置
+    #[serde(default)]
+    pub cursor_momentum: CursorMomentumConfig,
+    /// 摇杆推动时的陀螺仪抑制配置
+    #[serde(default)]
+    pub gyro_stick_suppression: GyroStickSuppressionConfig,
+    /// 解锁组合键配置
+    #[serde(default)]
+    pub arming: ArmingConfig,
+    /// 连接成功后依次执行的动作序列
+    #[serde(default)]
+    pub on_connect_actions: Vec<ButtonAction>,
     /// 重连配置
     pub reconnection: ReconnectionConfig,
+    /// 多手柄配置
+    #[serde(default)]
+    pub multi_controller: MultiControllerConfig,
+    /// 内置动作使用的快捷键
+    #[serde(default)]
+    pub shortcuts: ActionShortcuts,
+    /// 启动时使用的配置档案名称
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// 配置档案：按名称覆盖部分灵敏度和按键绑定
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub profiles: HashMap<String, ProfileOverride>,
+    /// 按键绑定启用状态，设为 false 可暂时停用绑定而不删除（未列出的绑定默认启用）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub binding_enabled: HashMap<String, bool>,
+    /// 按住后重复执行动作的按键（按键名称 -> 重复参数），未列出的按键按下时只执行一次
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub repeat_actions: HashMap<String, RepeatConfig>,
+    /// 组合键绑定（"修饰键+按键"，修饰键可以是 LT/RT 或任意按键名称），
+    /// 与 `button_mappings` 中的同名绑定同时存在时以此处为准
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub combo_mappings: HashMap<String, ButtonAction>,
     /// 按键绑定配置
     pub button_mappings: HashMap<String, ButtonAction>,
 }
@@ -36,21 +692,178 @@ impl Default for ControllerConfig {
     fn default() -> Self {
         Self {
             analog_trigger_threshold: 20,
-            joystick_deadzone: 1000,
-            right_joystick_deadzone: 5000,
-            gyro_deadzone: 10,
+            hid_report_size: default_hid_report_size(),
+            joystick_deadzone: default_joystick_deadzone(),
+            right_joystick_deadzone: default_right_joystick_deadzone(),
+            deadzone_shape: DeadzoneShape::Axial,
+            deadzone_hysteresis: 0,
+            stick_click_guard_threshold: 0,
+            gyro_deadzone: default_gyro_deadzone(),
             nav_trigger_threshold: 32001,
             dominant_axis_factor: 1.5,
-            joystick_sensitivity: 15.0,
-            gyro_sensitivity: 0.08,
-            direct_scroll_sensitivity: 20.0,
+            joystick_sensitivity: default_joystick_sensitivity(),
+            joystick_sensitivity_x: None,
+            joystick_sensitivity_y: None,
+            acceleration_curve: AccelerationCurve::default(),
+            gyro_sensitivity: default_gyro_sensitivity(),
+            gyro_active_stick_scale: default_gyro_active_stick_scale(),
+            rt_speed_multiplier: 0.0,
+            speed_modifier_button: None,
+            speed_modifier_multiplier: default_speed_modifier_multiplier(),
+            direct_scroll_sensitivity: default_direct_scroll_sensitivity(),
+            scroll_mode: ScrollMode::default(),
+            invert_scroll: false,
+            max_scroll_power: 0.0,
+            min_output_pixels: 0,
             pacer_loop_hz: 75,
+            main_loop_max_hz: default_main_loop_max_hz(),
+            scroll_ramp_up: default_scroll_ramp_up(),
+            scroll_ramp_down: default_scroll_ramp_down(),
+            min_click_hold_ms: 0,
+            double_click_interval_ms: default_double_click_interval_ms(),
+            max_actions_per_second: 0,
+            lone_trigger_max_ms: default_lone_trigger_max_ms(),
+            chord_window_ms: default_chord_window_ms(),
+            stick_hold_ms: default_stick_hold_ms(),
+            drift_check_ms: 0,
+            rumble_on_action: false,
+            zoom_scroll_button: None,
+            preset: None,
+            action_log_path: None,
+            default_action: None,
+            action_log_max_bytes: default_action_log_max_bytes(),
+            gyro_yaw_target: default_gyro_yaw_target(),
+            gyro_pitch_target: default_gyro_pitch_target(),
+            cursor_source: CursorSource::Both,
+            gyro_smoothing_factor: 0.0,
+            gyro_invert_yaw: false,
+            gyro_invert_pitch: false,
+            gyro_swap_axes: false,
+            output_backend: OutputBackend::Enigo,
+            platform: Platform::current(),
+            primary_modifier: None,
+            dry_run: false,
+            recenter_cursor_on_connect: false,
+            nav_method: NavMethod::Shortcut,
+            right_stick_horizontal_mode: RightStickHorizontalMode::Navigate,
+            scroll_source: ScrollSource::RightStick,
+            dual_stick_policy: DualStickPolicy::Independent,
+            input_panic_policy: InputPanicPolicy::Continue,
+            trigger_stages: TriggerStageConfig::default(),
+            grid_snap: GridSnapConfig::default(),
+            cursor_momentum: CursorMomentumConfig::default(),
+            gyro_stick_suppression: GyroStickSuppressionConfig::default(),
+            arming: ArmingConfig::default(),
+            on_connect_actions: Vec::new(),
             reconnection: ReconnectionConfig::default(),
+            multi_controller: MultiControllerConfig::default(),
+            shortcuts: ActionShortcuts::default(),
+            profile: None,
+            profiles: HashMap::new(),
+            binding_enabled: HashMap::new(),
+            repeat_actions: HashMap::new(),
+            combo_mappings: HashMap::new(),
             button_mappings: Self::default_button_mappings(),
         }
     }
 }
 
+// serde 默认值：配置文件省略这些字段时使用，与 `Default` 实现保持一致
+
+fn default_hid_report_size() -> usize {
+    64
+}
+
+fn default_joystick_deadzone() -> i16 {
+    1000
+}
+
+fn default_right_joystick_deadzone() -> i16 {
+    5000
+}
+
+fn default_gyro_deadzone() -> i16 {
+    10
+}
+
+fn default_joystick_sensitivity() -> f64 {
+    15.0
+}
+
+fn default_gyro_sensitivity() -> f64 {
+    0.08
+}
+
+fn default_gyro_active_stick_scale() -> f64 {
+    1.0
+}
+
+fn default_speed_modifier_multiplier() -> f64 {
+    2.0
+}
+
+fn default_direct_scroll_sensitivity() -> f64 {
+    20.0
+}
+
+/// 读取 `scroll_mode`，同时接受旧版本 `scroll_smooth` 的布尔值
+fn deserialize_scroll_mode<'de, D>(deserializer: D) -> Result<ScrollMode, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Smooth(bool),
+        Mode(ScrollMode),
+    }
+    Ok(match Repr::deserialize(deserializer)? {
+        Repr::Smooth(true) => ScrollMode::Smooth,
+        Repr::Smooth(false) => ScrollMode::Line,
+        Repr::Mode(mode) => mode,
+    })
+}
+
+fn default_main_loop_max_hz() -> u64 {
+    250
+}
+
+fn default_scroll_ramp_up() -> f64 {
+    1.0
+}
+
+fn default_scroll_ramp_down() -> f64 {
+    1.0
+}
+
+fn default_double_click_interval_ms() -> u64 {
+    120
+}
+
+fn default_lone_trigger_max_ms() -> u64 {
+    250
+}
+
+fn default_chord_window_ms() -> u64 {
+    50
+}
+
+fn default_stick_hold_ms() -> u64 {
+    300
+}
+
+fn default_action_log_max_bytes() -> u64 {
+    1024 * 1024
+}
+
+fn default_gyro_yaw_target() -> GyroTarget {
+    GyroTarget::CursorX
+}
+
+fn default_gyro_pitch_target() -> GyroTarget {
+    GyroTarget::CursorY
+}
+
 impl ControllerConfig {
     /// 创建默认按键绑定配置
     fn default_button_mappings() -> HashMap<String, ButtonAction> {
@@ -67,9 +880,17 @@ impl ControllerConfig {
         mappings.insert("DPad_Down".to_string(), ButtonAction::None);
         mappings.insert("DPad_Left".to_string(), ButtonAction::None);
         mappings.insert("DPad_Right".to_string(), ButtonAction::NewTab);
+        mappings.insert("Start".to_string(), ButtonAction::ToggleFullscreen);
+        mappings.insert("Back".to_string(), ButtonAction::MinimizeWindow);
+        // Xbox 徽标键常被系统或手柄驱动占用（如唤出游戏栏），默认不绑定以免同时触发两种操作
+        mappings.insert("Guide".to_string(), ButtonAction::None);
+
+        // 分享键默认不绑定：截图宏等用法见 example_config.toml
+        mappings.insert("Share".to_string(), ButtonAction::None);
 
         // 组合键
         mappings.insert("LT+X".to_string(), ButtonAction::QuitApp);
+        mappings.insert("LT+DPad_Right".to_string(), ButtonAction::ToggleFullscreen);
 
         mappings
     }
@@ -81,10 +902,10 @@ impl ControllerConfig {
         if path.exists() {
             Self::load_from_file(path)
         } else {
-            let default_config = Self::default();
-            default_config.save_to_file(path)?;
-            println!("已创建默认配置文件: {}", path.display());
-            Ok(default_config)
+            let template = Self::default_template_toml().map_err(|e| e.to_string())?;
+            Self::write_config_file(path, &template)?;
+            info!("已创建默认配置文件: {}", path.display());
+            Self::from_toml_str(&template)
         }
     }
 
@@ -92,14 +913,151 @@ impl ControllerConfig {
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
         let content =
             fs::read_to_string(path.as_ref()).map_err(|e| format!("读取配置文件失败: {}", e))?;
+        Self::from_toml_str(&content)
+    }
+
+    /// 解析配置内容并按预设填充未显式设置的字段
+    ///
+    /// 启动、新建默认配置和热重载都经过这里，预设在各个加载途径中的效果一致。
+    pub fn from_toml_str(content: &str) -> Result<Self, String> {
+        let mut config: Self =
+            toml::from_str(content).map_err(|e| format!("解析配置文件失败: {}", e))?;
+
+        // 记录内容中显式设置的字段，预设不会覆盖这些字段
+        let explicit: toml::Table =
+            toml::from_str(content).map_err(|e| format!("解析配置文件失败: {}", e))?;
+        config.apply_preset(&explicit)?;
+
+        Ok(config)
+    }
+
+    /// 在后台线程中监视配置文件，文件修改后重新加载并发送到通道
+    ///
+    /// 通过轮询文件修改时间检测变化。发送的配置已校验并应用了选择的配置档案
+    /// （`profile` 不为空时覆盖文件中的选择），加载或校验失败时发送错误信息，
+    /// 由接收方决定如何处理。接收方断开后线程退出。
+    pub fn watch_for_changes(
+        path: PathBuf,
+        profile: Option<String>,
+        tx: Sender<Result<ControllerConfig, String>>,
+    ) -> thread::JoinHandle<()> {
+        thread::spawn(move || {
+            let modified_at = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+            let mut last_modified = modified_at(&path);
+
+            loop {
+                thread::sleep(CONFIG_WATCH_INTERVAL);
+                let modified = modified_at(&path);
+                if modified.is_none() || modified == last_modified {
+                    continue;
+                }
+                last_modified = modified;
 
-        toml::from_str(&content).map_err(|e| format!("解析配置文件失败: {}", e))
+                let reloaded = Self::load_from_file(&path)
+                    .and_then(|config| config.activate(profile.as_deref()));
+                if tx.send(reloaded).is_err() {
+                    break;
+                }
+            }
+        })
     }
 
-    /// 保存配置到文件
+    /// 校验配置并应用选择的配置档案，`profile` 不为空时覆盖配置文件中的选择
+    pub fn activate(mut self, profile: Option<&str>) -> Result<Self, String> {
+        if let Some(name) = profile {
+            self.profile = Some(name.to_string());
+        }
+        self.validate()?;
+        match &self.profile {
+            Some(name) => self.with_profile(name),
+            None => Ok(self),
+        }
+    }
+
+    /// 按预设填充未显式设置的灵敏度、死区和加速曲线字段
+    fn apply_preset(&mut self, explicit: &toml::Table) -> Result<(), String> {
+        let Some(name) = &self.preset else {
+            return Ok(());
+        };
+        let preset = TuningPreset::find(name).ok_or_else(|| format!("未知的调校预设: {}", name))?;
+
+        if !explicit.contains_key("joystick_sensitivity") {
+            self.joystick_sensitivity = preset.joystick_sensitivity;
+        }
+        if !explicit.contains_key("joystick_deadzone") {
+            self.joystick_deadzone = preset.joystick_deadzone;
+        }
+        if !explicit.contains_key("right_joystick_deadzone") {
+            self.right_joystick_deadzone = preset.right_joystick_deadzone;
+        }
+        if !explicit.contains_key("gyro_sensitivity") {
+            self.gyro_sensitivity = preset.gyro_sensitivity;
+        }
+        if !explicit.contains_key("gyro_deadzone") {
+            self.gyro_deadzone = preset.gyro_deadzone;
+        }
+        if !explicit.contains_key("direct_scroll_sensitivity") {
+            self.direct_scroll_sensitivity = preset.direct_scroll_sensitivity;
+        }
+        if !explicit.contains_key("acceleration_curve") {
+            self.acceleration_curve = preset.acceleration_curve;
+        }
+
+        Ok(())
+    }
+
+    /// 生成带注释的默认配置模板，注释说明各字段的含义和取值范围
+    pub fn default_template_toml() -> ControllerResult<String> {
+        let content = toml::to_string_pretty(&Self::default())?;
+        Ok(Self::annotate_toml(&content))
+    }
+
+    /// 将配置保存到文件，字段注释与默认模板相同
+    ///
+    /// 文件会被整体重写，手写的注释不会保留。
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
         let content = toml::to_string_pretty(self).map_err(|e| format!("序列化配置失败: {}", e))?;
+        Self::write_config_file(path, &Self::annotate_toml(&content))
+    }
+
+    /// 为序列化后的配置逐字段加上 `FIELD_DOCS` 中的说明注释
+    fn annotate_toml(content: &str) -> String {
+        let mut template = String::from(
+            "# 手柄控制器配置文件\n# 除基本参数和按键绑定外，删除某个字段即使用其默认值\n\n",
+        );
+        let mut section = "";
+        for line in content.lines() {
+            let trimmed = line.trim();
+            let key =
+                if let Some(name) = trimmed.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+                    section = name;
+                    ""
+                } else if let Some((key, _)) = trimmed.split_once(" = ") {
+                    key
+                } else {
+                    // 多行数组等续行不加注释
+                    template.push_str(line);
+                    template.push('\n');
+                    continue;
+                };
 
+            if let Some((_, _, doc)) = FIELD_DOCS
+                .iter()
+                .find(|(doc_section, doc_key, _)| *doc_section == section && *doc_key == key)
+            {
+                template.push_str("# ");
+                template.push_str(doc);
+                template.push('\n');
+            }
+            template.push_str(line);
+            template.push('\n');
+        }
+
+        template
+    }
+
+    /// 将配置内容写入文件
+    fn write_config_file<P: AsRef<Path>>(path: P, content: &str) -> Result<(), String> {
         // 确保父目录存在
         if let Some(parent) = path.as_ref().parent() {
             fs::create_dir_all(parent).map_err(|e| format!("创建配置目录失败: {}", e))?;
@@ -110,20 +1068,135 @@ impl ControllerConfig {
         Ok(())
     }
 
-    /// 验证配置参数的合理性
+    /// 验证配置参数的合理性（包括每个配置档案合并后的配置）
     pub fn validate(&self) -> Result<(), String> {
+        self.validate_settings()?;
+
+        if let Some(name) = &self.profile
+            && !self.profiles.contains_key(name)
+        {
+            return Err(format!("未知的配置档案: {}", name));
+        }
+        for name in &self.multi_controller.device_profiles {
+            if !name.is_empty() && !self.profiles.contains_key(name) {
+                return Err(format!("手柄指定了未知的配置档案: {}", name));
+            }
+        }
+        for name in self.profiles.keys() {
+            self.with_profile(name)?
+                .validate_settings()
+                .map_err(|e| format!("配置档案 {} 无效: {}", name, e))?;
+        }
+
+        Ok(())
+    }
+
+    /// 应用指定的配置档案，返回合并后的配置
+    ///
+    /// 档案中设置的灵敏度覆盖基础配置，按键绑定与基础绑定合并（同名绑定以档案为准）。
+    pub fn with_profile(&self, name: &str) -> Result<ControllerConfig, String> {
+        let profile = self
+            .profiles
+            .get(name)
+            .ok_or_else(|| format!("未知的配置档案: {}", name))?;
+
+        let mut config = self.clone();
+        if let Some(sensitivity) = profile.joystick_sensitivity {
+            config.set_joystick_sensitivity(sensitivity);
+        }
+        if let Some(sensitivity) = profile.gyro_sensitivity {
+            config.gyro_sensitivity = sensitivity;
+        }
+        if let Some(mappings) = &profile.button_mappings {
+            config.button_mappings.extend(mappings.clone());
+        }
+        if let Some(mappings) = &profile.combo_mappings {
+            config.combo_mappings.extend(mappings.clone());
+        }
+        config.profile = Some(name.to_string());
+        Ok(config)
+    }
+
+    /// 验证单个配置（不含配置档案）的参数
+    fn validate_settings(&self) -> Result<(), String> {
         if self.joystick_sensitivity <= 0.0 {
             return Err("摇杆灵敏度必须大于0".to_string());
         }
+        let (sensitivity_x, sensitivity_y) = self.stick_sensitivity();
+        if sensitivity_x <= 0.0 || sensitivity_y <= 0.0 {
+            return Err("摇杆水平和垂直灵敏度必须大于0".to_string());
+        }
+
+        match self.acceleration_curve {
+            AccelerationCurve::Exponential { power } if power <= 0.0 => {
+                return Err("加速曲线指数必须大于0".to_string());
+            }
+            AccelerationCurve::Sigmoid { steepness }
+                if steepness.is_nan() || steepness < MIN_SIGMOID_STEEPNESS =>
+            {
+                return Err(format!(
+                    "加速曲线陡度不能小于{}: {}",
+                    MIN_SIGMOID_STEEPNESS, steepness
+                ));
+            }
+            _ => {}
+        }
 
         if self.gyro_sensitivity <= 0.0 {
             return Err("陀螺仪灵敏度必须大于0".to_string());
         }
 
+        if self.gyro_active_stick_scale < 0.0 {
+            return Err("陀螺仪启用时的摇杆缩放系数不能为负数".to_string());
+        }
+
+        if self.rt_speed_multiplier < 0.0 {
+            return Err("RT 加速倍率不能为负数".to_string());
+        }
+
+        if self.speed_modifier_multiplier <= 0.0 {
+            return Err("光标加速倍率必须大于0".to_string());
+        }
+
+        if self.max_scroll_power < 0.0 {
+            return Err("滚动力度上限不能为负数".to_string());
+        }
+
         if self.pacer_loop_hz == 0 {
             return Err("步调器频率必须大于0".to_string());
         }
 
+        for (name, factor) in [
+            ("滚动加速系数", self.scroll_ramp_up),
+            ("滚动减速系数", self.scroll_ramp_down),
+        ] {
+            if factor <= 0.0 || factor > 1.0 {
+                return Err(format!("{}必须大于0且不超过1.0: {}", name, factor));
+            }
+        }
+
+        if !(0.0..=1.0).contains(&self.gyro_smoothing_factor) {
+            return Err("陀螺仪平滑系数必须在0.0到1.0之间".to_string());
+        }
+
+        if !(1..=MAX_DOUBLE_CLICK_INTERVAL_MS).contains(&self.double_click_interval_ms) {
+            return Err(format!(
+                "双击间隔必须在1到{}毫秒之间: {}",
+                MAX_DOUBLE_CLICK_INTERVAL_MS, self.double_click_interval_ms
+            ));
+        }
+
+        if self.chord_window_ms > MAX_CHORD_WINDOW_MS {
+            return Err(format!(
+                "组合键同按时间窗口不能超过{}毫秒: {}",
+                MAX_CHORD_WINDOW_MS, self.chord_window_ms
+            ));
+        }
+
+        if self.hid_report_size == 0 {
+            return Err("HID 报告缓冲区大小必须大于0".to_string());
+        }
+
         if self.joystick_deadzone < 0 {
             return Err("摇杆死区不能为负数".to_string());
         }
@@ -132,6 +1205,14 @@ impl ControllerConfig {
             return Err("右摇杆死区不能为负数".to_string());
         }
 
+        if self.deadzone_hysteresis < 0 {
+            return Err("摇杆死区回差不能为负数".to_string());
+        }
+
+        if self.stick_click_guard_threshold < 0 {
+            return Err("摇杆按下防误触阈值不能为负数".to_string());
+        }
+
         if self.gyro_deadzone < 0 {
             return Err("陀螺仪死区不能为负数".to_string());
         }
@@ -140,6 +1221,80 @@ impl ControllerConfig {
             return Err("主导轴系数必须大于1.0".to_string());
         }
 
+        if self.grid_snap.enabled && self.grid_snap.size == 0 {
+            return Err("网格吸附间距必须大于0".to_string());
+        }
+
+        if self.cursor_momentum.enabled && !(0.0..1.0).contains(&self.cursor_momentum.decay) {
+            return Err("光标惯性衰减系数必须在0.0到1.0之间（不含1.0）".to_string());
+        }
+
+        for (button, repeat) in &self.repeat_actions {
+            if repeat.interval_ms == 0 {
+                return Err(format!("按键 {} 的重复间隔必须大于0", button));
+            }
+        }
+
+        if self.multi_controller.max_devices == 0 {
+            return Err("手柄数量上限必须大于0".to_string());
+        }
+
+        if self.reconnection.reconnect_backoff_multiplier < 1.0 {
+            return Err("重连退避倍率不能小于1.0".to_string());
+        }
+
+        let suppression = &self.gyro_stick_suppression;
+        if suppression.enabled && !(0.0..=1.0).contains(&suppression.gyro_scale) {
+            return Err("陀螺仪抑制缩放系数必须在0.0到1.0之间".to_string());
+        }
+
+        if self.arming.require_arm && self.arming.arm_chord.is_empty() {
+            return Err("启用解锁后解锁组合键不能为空".to_string());
+        }
+
+        if self.trigger_stages.enabled {
+            let stages = &self.trigger_stages;
+            if stages.light_threshold >= stages.full_threshold {
+                return Err("两段式扳机的轻按阈值必须小于按到底阈值".to_string());
+            }
+            if stages.hysteresis >= stages.light_threshold {
+                return Err("两段式扳机的回差必须小于轻按阈值".to_string());
+            }
+        }
+
+        if let Some(name) = &self.preset {
+            TuningPreset::find(name).ok_or_else(|| format!("未知的调校预设: {}", name))?;
+        }
+
+        for (name, shortcut) in self.shortcuts.entries() {
+            shortcut
+                .validate()
+                .map_err(|e| format!("快捷键 shortcuts.{} 无效: {}", name, e))?;
+        }
+
+        for (combo, action) in &self.button_mappings {
+            action
+                .validate()
+                .map_err(|e| format!("按键绑定 {} 无效: {}", combo, e))?;
+        }
+        for (combo, action) in &self.combo_mappings {
+            let valid_name = combo
+                .split_once('+')
+                .is_some_and(|(modifier, button)| !modifier.is_empty() && !button.is_empty());
+            if !valid_name {
+                return Err(format!(
+                    "组合键名称必须为 \"修饰键+按键\" 的形式: {}",
+                    combo
+                ));
+            }
+            action
+                .validate()
+                .map_err(|e| format!("组合键绑定 {} 无效: {}", combo, e))?;
+        }
+        for action in self.on_connect_actions.iter().chain(&self.default_action) {
+            action.validate()?;
+        }
+
         Ok(())
     }
 
@@ -153,9 +1308,130 @@ impl ControllerConfig {
             .join("config.toml"))
     }
 
-    /// 获取按键绑定
+    /// 左摇杆 (水平, 垂直) 灵敏度，未单独设置的轴使用 `joystick_sensitivity`
+    pub fn stick_sensitivity(&self) -> (f64, f64) {
+        (
+            self.joystick_sensitivity_x
+                .unwrap_or(self.joystick_sensitivity),
+            self.joystick_sensitivity_y
+                .unwrap_or(self.joystick_sensitivity),
+        )
+    }
+
+    /// 调整摇杆灵敏度，单独设置的水平/垂直灵敏度按相同比例缩放，两轴的比例保持不变
+    pub fn set_joystick_sensitivity(&mut self, sensitivity: f64) {
+        if self.joystick_sensitivity > 0.0 {
+            let scale = sensitivity / self.joystick_sensitivity;
+            for value in [
+                &mut self.joystick_sensitivity_x,
+                &mut self.joystick_sensitivity_y,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                *value *= scale;
+            }
+        }
+        self.joystick_sensitivity = sensitivity;
+    }
+
+    /// 获取按键绑定（已停用的绑定视为未绑定），组合键优先查找 `combo_mappings`
     pub fn get_button_action(&self, button_combo: &str) -> Option<&ButtonAction> {
-        self.button_mappings.get(button_combo)
+        if !self.is_binding_enabled(button_combo) {
+            return None;
+        }
+        self.combo_mappings
+            .get(button_combo)
+            .or_else(|| self.button_mappings.get(button_combo))
+    }
+
+    /// 多键同按组合的绑定：名称由 "+" 连接的按键名称组成且不含 LT/RT（扳机组合按修饰键处理）
+    fn chord_bindings(&self) -> impl Iterator<Item = (Vec<&str>, &ButtonAction)> {
+        self.combo_mappings
+            .iter()
+            .chain(&self.button_mappings)
+            .filter(|(combo, _)| combo.contains('+') && self.is_binding_enabled(combo))
+            .map(|(combo, action)| (combo.split('+').collect::<Vec<_>>(), action))
+            .filter(|(buttons, _)| !buttons.iter().any(|b| matches!(*b, "LT" | "RT")))
+    }
+
+    /// 按键是否参与了某个多键同按组合
+    pub fn is_chord_button(&self, button: &str) -> bool {
+        self.chord_bindings()
+            .any(|(buttons, _)| buttons.contains(&button))
+    }
+
+    /// 查找恰好由这些按键组成的同按组合绑定（与书写顺序无关，`combo_mappings` 优先）
+    pub fn get_chord_action(&self, pressed: &[String]) -> Option<&ButtonAction> {
+        self.chord_bindings()
+            .find(|(buttons, _)| {
+                buttons.len() == pressed.len()
+                    && buttons.iter().all(|b| pressed.iter().any(|p| p == b))
+            })
+            .map(|(_, action)| action)
+    }
+
+    /// 绑定是否启用
+    pub fn is_binding_enabled(&self, button_combo: &str) -> bool {
+        self.binding_enabled
+            .get(button_combo)
+            .copied()
+            .unwrap_or(true)
+    }
+}
+
+/// 内置调校预设，一组相互协调的灵敏度、死区和加速曲线参数
+pub struct TuningPreset {
+    pub name: &'static str,
+    pub joystick_sensitivity: f64,
+    pub joystick_deadzone: i16,
+    pub right_joystick_deadzone: i16,
+    pub gyro_sensitivity: f64,
+    pub gyro_deadzone: i16,
+    pub direct_scroll_sensitivity: f64,
+    pub acceleration_curve: AccelerationCurve,
+}
+
+impl TuningPreset {
+    /// 内置预设表，balanced 与默认配置一致
+    pub const ALL: &'static [TuningPreset] = &[
+        TuningPreset {
+            name: "precise",
+            joystick_sensitivity: 8.0,
+            joystick_deadzone: 1500,
+            right_joystick_deadzone: 6000,
+            gyro_sensitivity: 0.05,
+            gyro_deadzone: 15,
+            direct_scroll_sensitivity: 12.0,
+            acceleration_curve: AccelerationCurve::Exponential { power: 2.5 },
+        },
+        TuningPreset {
+            name: "balanced",
+            joystick_sensitivity: 15.0,
+            joystick_deadzone: 1000,
+            right_joystick_deadzone: 5000,
+            gyro_sensitivity: 0.08,
+            gyro_deadzone: 10,
+            direct_scroll_sensitivity: 20.0,
+            acceleration_curve: AccelerationCurve::Exponential { power: 2.0 },
+        },
+        TuningPreset {
+            name: "fast",
+            joystick_sensitivity: 25.0,
+            joystick_deadzone: 800,
+            right_joystick_deadzone: 4000,
+            gyro_sensitivity: 0.12,
+            gyro_deadzone: 8,
+            direct_scroll_sensitivity: 30.0,
+            acceleration_curve: AccelerationCurve::Exponential { power: 1.5 },
+        },
+    ];
+
+    /// 按名称查找预设（不区分大小写）
+    pub fn find(name: &str) -> Option<&'static TuningPreset> {
+        Self::ALL
+            .iter()
+            .find(|preset| preset.name.eq_ignore_ascii_case(name))
     }
 }
 
@@ -166,6 +1442,12 @@ pub enum ButtonAction {
     LeftClick,
     /// 鼠标右键
     RightClick,
+    /// 鼠标左键单击：按下时立即完成一次点击，松开时不做任何操作
+    LeftTap,
+    /// 鼠标右键单击：按下时立即完成一次点击，松开时不做任何操作
+    RightTap,
+    /// 双击鼠标按键：按下时连续点击两次，间隔为 `double_click_interval_ms`
+    DoubleClick(MouseButton),
     /// 关闭窗口
     CloseWindow,
     /// 调度中心
@@ -176,29 +1458,635 @@ pub enum ButtonAction {
     NextTab,
     /// 退出应用程序 (Cmd+Q)
     QuitApp,
+    /// 退出本程序：不向系统发送按键，释放按住的输出后正常退出
+    QuitController,
     /// 新建标签页 (Cmd+T)
     NewTab,
     /// 刷新页面 (Cmd+R)
     Refresh,
+    /// 滚动到页面顶部（快捷键见 `shortcuts.scroll_to_top`）
+    ScrollToTop,
+    /// 滚动到页面底部（快捷键见 `shortcuts.scroll_to_bottom`）
+    ScrollToBottom,
+    /// 锁定屏幕（快捷键见 `shortcuts.lock_screen`）
+    LockScreen,
+    /// 粘贴 (Cmd+V / Ctrl+V)
+    Paste,
+    /// 粘贴为纯文本（快捷键见 `shortcuts.paste_plain`）
+    PastePlain,
+    /// 切换全屏（快捷键见 `shortcuts.toggle_fullscreen`）
+    ToggleFullscreen,
+    /// 最小化当前窗口（快捷键见 `shortcuts.minimize_window`）
+    MinimizeWindow,
+    /// 隐藏当前应用（快捷键见 `shortcuts.hide_app`）
+    HideApp,
+    /// 启动听写（快捷键见 `shortcuts.start_dictation`）
+    StartDictation,
+    /// 调高音量（媒体键）
+    VolumeUp,
+    /// 调低音量（媒体键）
+    VolumeDown,
+    /// 静音/取消静音（媒体键）
+    Mute,
+    /// 调高屏幕亮度（macOS 和 Linux）
+    BrightnessUp,
+    /// 调低屏幕亮度（macOS 和 Linux）
+    BrightnessDown,
+    /// 切换到第 index 个标签页 (Cmd+1..Cmd+9，9 表示最后一个标签页)
+    SwitchTab { index: u8 },
+    /// 重复上一次执行的动作
+    RepeatLast,
+    /// 模拟双指左右滑动手势翻页
+    Swipe(SwipeDirection),
+    /// 按住按键：按钮按下时按下该键，松开时释放（键名格式与 `CustomShortcut` 相同）
+    HoldKey(String),
+    /// 切换按住鼠标按键：第一次按下时按住，再次按下时松开（用于拖放）
+    ToggleHold(MouseButton),
+    /// 将光标移动到屏幕的角落或中央（用于窗口吸附等）
+    MoveCursorTo(ScreenAnchor),
     /// 自定义快捷键
     CustomShortcut { modifiers: Vec<String>, key: String },
+    /// 输入一段固定文本（按下时输入一次，支持中文等任意 Unicode 字符）
+    TypeText(String),
+    /// 启动应用程序：macOS 上按应用名称通过 `open -a` 打开，其他系统直接执行该程序名
+    LaunchApp(String),
+    /// 宏：按顺序执行一组步骤
+    Macro(Vec<MacroStep>),
     /// 无操作
     None,
 }
 
+impl ButtonAction {
+    /// 是否为按下/释放对应鼠标按键的点击动作
+    pub fn is_mouse_click(&self) -> bool {
+        matches!(self, ButtonAction::LeftClick | ButtonAction::RightClick)
+    }
+
+    /// 验证动作参数（包括宏内的动作）
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            ButtonAction::SwitchTab { index } if !(1..=9).contains(index) => {
+                Err(format!("标签页序号必须在1到9之间: {}", index))
+            }
+            ButtonAction::TypeText(text) if text.is_empty() => {
+                Err("TypeText 的文本不能为空".to_string())
+            }
+            ButtonAction::HoldKey(key) => validate_key_names([key]),
+            ButtonAction::CustomShortcut { modifiers, key } => {
+                validate_key_names(modifiers.iter().chain([key]))
+            }
+            ButtonAction::Macro(steps) if steps.is_empty() => Err("宏不能为空".to_string()),
+            ButtonAction::Macro(steps) => steps.iter().try_for_each(|step| match step {
+                MacroStep::Action(action) => action.validate(),
+                MacroStep::KeyShortcut { modifiers, key } => {
+                    validate_key_names(modifiers.iter().chain([key]))
+                }
+                MacroStep::TypeText(text) if text.is_empty() => {
+                    Err("宏中 TypeText 的文本不能为空".to_string())
+                }
+                MacroStep::TypeText(_) | MacroStep::Delay(_) => Ok(()),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 检查键名在当前平台上可以模拟（例如 Fn 键只有 macOS 上可用），避免执行到动作时才报错
+fn validate_key_names<'a>(names: impl IntoIterator<Item = &'a String>) -> Result<(), String> {
+    for name in names {
+        InputHandler::parse_key_string_static(name).map_err(|e| match e {
+            ControllerError::Config(msg) => msg,
+            other => other.to_string(),
+        })?;
+    }
+    Ok(())
+}
+
+/// 快捷键定义，键名格式与 `CustomShortcut` 相同
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Shortcut {
+    pub modifiers: Vec<String>,
+    pub key: String,
+    /// 连续按下的次数（默认 1），例如连按两次 Fn 启动听写
+    #[serde(
+        default = "Shortcut::single_press",
+        skip_serializing_if = "Shortcut::is_single_press"
+    )]
+    pub presses: u32,
+}
+
+impl Shortcut {
+    fn new(modifiers: &[&str], key: &str) -> Self {
+        Self {
+            modifiers: modifiers.iter().map(|m| m.to_string()).collect(),
+            key: key.to_string(),
+            presses: 1,
+        }
+    }
+
+    fn single_press() -> u32 {
+        1
+    }
+
+    fn is_single_press(presses: &u32) -> bool {
+        *presses == 1
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.presses == 0 {
+            return Err("连按次数必须大于0".to_string());
+        }
+        validate_key_names(self.modifiers.iter().chain([&self.key]))
+    }
+}
+
+/// 内置动作使用的快捷键，不同应用习惯不同时可在配置中修改
+///
+/// 默认值按编译目标系统选择，下面括号中为 macOS 的默认值。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActionShortcuts {
+    /// 滚动到顶部（默认 Cmd+上箭头）
+    pub scroll_to_top: Shortcut,
+    /// 滚动到底部（默认 Cmd+下箭头）
+    pub scroll_to_bottom: Shortcut,
+    /// 锁定屏幕（默认 Ctrl+Cmd+Q）
+    pub lock_screen: Shortcut,
+    /// 粘贴为纯文本/粘贴并匹配样式（默认 Option+Shift+Cmd+V）
+    pub paste_plain: Shortcut,
+    /// 切换全屏（默认 Ctrl+Cmd+F）
+    pub toggle_fullscreen: Shortcut,
+    /// 最小化窗口（默认 Cmd+M）
+    pub minimize_window: Shortcut,
+    /// 隐藏应用（默认 Cmd+H）
+    pub hide_app: Shortcut,
+    /// 启动听写（默认连按两次 Fn，实际快捷键取决于系统设置）
+    pub start_dictation: Shortcut,
+}
+
+impl ActionShortcuts {
+    /// 按配置键名列出所有快捷键
+    fn entries(&self) -> [(&'static str, &Shortcut); 8] {
+        [
+            ("scroll_to_top", &self.scroll_to_top),
+            ("scroll_to_bottom", &self.scroll_to_bottom),
+            ("lock_screen", &self.lock_screen),
+            ("paste_plain", &self.paste_plain),
+            ("toggle_fullscreen", &self.toggle_fullscreen),
+            ("minimize_window", &self.minimize_window),
+            ("hide_app", &self.hide_app),
+            ("start_dictation", &self.start_dictation),
+        ]
+    }
+}
+
+impl Default for ActionShortcuts {
+    fn default() -> Self {
+        match Platform::current() {
+            Platform::MacOS => Self {
+                scroll_to_top: Shortcut::new(&["cmd"], "up"),
+                scroll_to_bottom: Shortcut::new(&["cmd"], "down"),
+                lock_screen: Shortcut::new(&["ctrl", "cmd"], "q"),
+                paste_plain: Shortcut::new(&["alt", "shift", "cmd"], "v"),
+                toggle_fullscreen: Shortcut::new(&["ctrl", "cmd"], "f"),
+                minimize_window: Shortcut::new(&["cmd"], "m"),
+                hide_app: Shortcut::new(&["cmd"], "h"),
+                start_dictation: Shortcut {
+                    presses: 2,
+                    ..Shortcut::new(&[], "fn")
+                },
+            },
+            // GNOME / KDE：Super+H 最小化窗口；系统没有听写快捷键，
+            // 默认连按两次 Ctrl，需在所用的听写工具中设置相同的快捷键
+            Platform::Linux => Self {
+                minimize_window: Shortcut::new(&["meta"], "h"),
+                hide_app: Shortcut::new(&["meta"], "h"),
+                start_dictation: Shortcut {
+                    presses: 2,
+                    ..Shortcut::new(&[], "ctrl")
+                },
+                ..Self::ctrl_based()
+            },
+            Platform::Windows => Self::ctrl_based(),
+        }
+    }
+}
+
+impl ActionShortcuts {
+    /// 以 Ctrl 为主修饰键的快捷键（Windows 的默认值，Linux 在此基础上修改）
+    fn ctrl_based() -> Self {
+        Self {
+            scroll_to_top: Shortcut::new(&["ctrl"], "home"),
+            scroll_to_bottom: Shortcut::new(&["ctrl"], "end"),
+            lock_screen: Shortcut::new(&["meta"], "l"),
+            paste_plain: Shortcut::new(&["ctrl", "shift"], "v"),
+            toggle_fullscreen: Shortcut::new(&[], "f11"),
+            minimize_window: Shortcut::new(&["meta"], "down"),
+            hide_app: Shortcut::new(&["meta"], "down"),
+            start_dictation: Shortcut::new(&["meta"], "h"),
+        }
+    }
+}
+
+/// 宏步骤枚举
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MacroStep {
+    /// 执行一个按钮动作（按下后立即释放）
+    Action(ButtonAction),
+    /// 按下快捷键（键名格式与 `CustomShortcut` 相同）
+    KeyShortcut { modifiers: Vec<String>, key: String },
+    /// 输入一段文本
+    TypeText(String),
+    /// 等待指定毫秒数（等待期间不阻塞其他输入）
+    Delay(u64),
+}
+
+/// 摇杆死区形状
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum DeadzoneShape {
+    /// 两个轴分别扣除死区，斜向推动时光标容易贴着坐标轴移动
+    #[default]
+    Axial,
+    /// 按两轴合成的推动幅度扣除死区，再按比例缩放两个分量，保持推动方向
+    Radial,
+}
+
+/// 摇杆偏移（去除死区后规范化到 0-1）到光标速度的加速曲线
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum AccelerationCurve {
+    /// 速度与偏移成正比
+    Linear,
+    /// 速度为偏移的 power 次方，power 越大中心附近越精细
+    Exponential { power: f64 },
+    /// S 形曲线：中心附近精细、中段快速加速、推满时趋于最大速度，steepness 越大加速越集中
+    Sigmoid { steepness: f64 },
+}
+
+impl Default for AccelerationCurve {
+    fn default() -> Self {
+        AccelerationCurve::Exponential { power: 2.0 }
+    }
+}
+
+/// 陀螺仪轴的输出目标
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum GyroTarget {
+    /// 水平移动光标
+    CursorX,
+    /// 垂直移动光标
+    CursorY,
+    /// 垂直滚动
+    ScrollV,
+    /// 水平滚动
+    ScrollH,
+    /// 忽略该轴
+    None,
+}
+
+/// 滚动方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScrollMode {
+    /// 按像素平滑滚动
+    #[default]
+    Smooth,
+    /// 按行即时滚动，响应更直接，适合平滑滚动过细或不支持平滑滚动的应用
+    Line,
+}
+
+/// 控制光标移动的输入来源
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum CursorSource {
+    /// 只用左摇杆移动光标
+    LeftStick,
+    /// 只用陀螺仪移动光标
+    Gyro,
+    /// 左摇杆和陀螺仪都可以移动光标
+    #[default]
+    Both,
+    /// 不移动光标（只用于滚动、导航和按键）
+    None,
+}
+
+impl CursorSource {
+    /// 左摇杆是否移动光标
+    pub fn uses_left_stick(self) -> bool {
+        matches!(self, CursorSource::LeftStick | CursorSource::Both)
+    }
+
+    /// 陀螺仪映射到光标的轴是否移动光标
+    pub fn uses_gyro(self) -> bool {
+        matches!(self, CursorSource::Gyro | CursorSource::Both)
+    }
+}
+
+/// 输出后端类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum OutputBackend {
+    /// 通过 Enigo 发送输入事件（跨平台，默认）
+    #[default]
+    Enigo,
+    /// 直接通过 CoreGraphics 发送鼠标事件（仅 macOS，延迟更低）
+    CoreGraphics,
+}
+
+/// 内置动作按哪个操作系统的快捷键发送
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Platform {
+    /// macOS：主修饰键为 Cmd，Y 键默认打开调度中心
+    MacOS,
+    /// Linux（X11 / Wayland）：主修饰键为 Ctrl，调度中心改为按 Super 键打开活动概览
+    Linux,
+    /// Windows：主修饰键为 Ctrl，调度中心改为 Win+Tab 打开任务视图
+    Windows,
+}
+
+impl Platform {
+    /// 程序编译目标所在的系统
+    pub fn current() -> Self {
+        if cfg!(target_os = "macos") {
+            Platform::MacOS
+        } else if cfg!(target_os = "windows") {
+            Platform::Windows
+        } else {
+            Platform::Linux
+        }
+    }
+}
+
+/// 页面前进/后退的导航方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum NavMethod {
+    /// 发送 Cmd+[ / Cmd+] 快捷键
+    #[default]
+    Shortcut,
+    /// 模拟双指左右滑动手势（需 CoreGraphics 输出后端，其他后端退回快捷键）
+    Swipe,
+}
+
+/// 右摇杆左右推动的用途
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum RightStickHorizontalMode {
+    /// 页面前进/后退导航（默认）
+    #[default]
+    Navigate,
+    /// 水平滚动
+    HorizontalScroll,
+}
+
+/// 滚动来源
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ScrollSource {
+    /// 右摇杆上下滚动（默认）
+    #[default]
+    RightStick,
+    /// RT 向上、LT 向下按压力滚动，右摇杆只用于导航
+    ///
+    /// 此模式下扳机不再作为修饰键：LT 组合键、陀螺仪、单独轻按扳机和两段式扳机均不生效。
+    Triggers,
+}
+
+/// 两个摇杆同时推出死区时的仲裁策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum DualStickPolicy {
+    /// 两个摇杆各自独立生效（左摇杆移动光标，右摇杆滚动/导航）
+    #[default]
+    Independent,
+    /// 左摇杆优先：左摇杆推动期间忽略右摇杆
+    LeftPriority,
+    /// 右摇杆优先：右摇杆推动期间忽略左摇杆
+    RightPriority,
+}
+
+/// 鼠标按键
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+/// 屏幕上的光标定位点
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ScreenAnchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Center,
+}
+
+/// 滑动手势方向
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SwipeDirection {
+    /// 后退（双指向右滑动）
+    Back,
+    /// 前进（双指向左滑动）
+    Forward,
+}
+
+/// 输入处理 panic 处理策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum InputPanicPolicy {
+    /// 记录错误并继续处理下一帧
+    #[default]
+    Continue,
+    /// 退出程序
+    Exit,
+}
+
+/// 两段式扳机配置
+///
+/// 启用后扳机越过轻按阈值时执行 "LT_Light"/"RT_Light" 绑定，越过按到底阈值时执行
+/// "LT_Full"/"RT_Full" 绑定。回落时需低于阈值减去回差才会退回上一段，避免在阈值附近抖动。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TriggerStageConfig {
+    /// 是否启用两段式扳机
+    pub enabled: bool,
+    /// 轻按阈值 (0-255)
+    pub light_threshold: u8,
+    /// 按到底阈值 (0-255)
+    pub full_threshold: u8,
+    /// 回差
+    pub hysteresis: u8,
+}
+
+impl Default for TriggerStageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            light_threshold: 60,
+            full_threshold: 230,
+            hysteresis: 12,
+        }
+    }
+}
+
+/// 光标网格吸附配置
+///
+/// 启用后光标只停留在每隔 `size` 像素的网格点上，便于重复、精确地定位。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GridSnapConfig {
+    /// 是否启用网格吸附
+    pub enabled: bool,
+    /// 网格间距（像素）
+    pub size: u32,
+}
+
+impl Default for GridSnapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            size: 8,
+        }
+    }
+}
+
+/// 按住按键时重复执行动作的参数
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RepeatConfig {
+    /// 按下后开始重复前的等待时间（毫秒）
+    pub initial_delay_ms: u64,
+    /// 重复执行的间隔（毫秒）
+    pub interval_ms: u64,
+}
+
+impl Default for RepeatConfig {
+    fn default() -> Self {
+        Self {
+            initial_delay_ms: 400,
+            interval_ms: 100,
+        }
+    }
+}
+
+/// 初始连接失败时的提示方式
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum InitialFailurePolicy {
+    /// 打印提示后等待设备连接
+    Wait,
+    /// 不打印提示，静默等待
+    Silent,
+    /// 打印提示并响铃提醒
+    Beep,
+}
+
+/// 光标惯性配置
+///
+/// 启用后摇杆回中时光标不会立即停止，而是以逐帧衰减的速度继续滑行一段距离。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CursorMomentumConfig {
+    /// 是否启用光标惯性
+    pub enabled: bool,
+    /// 速度每 10 毫秒的衰减系数 (0.0-1.0)，越大滑行越远
+    pub decay: f64,
+}
+
+impl Default for CursorMomentumConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            decay: 0.85,
+        }
+    }
+}
+
+/// 摇杆推动时的陀螺仪抑制配置
+///
+/// 启用后左摇杆推出死区期间，陀螺仪输出乘以 `gyro_scale`，
+/// 避免同时移动摇杆和手柄时光标移动量叠加。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GyroStickSuppressionConfig {
+    /// 是否启用陀螺仪抑制
+    pub enabled: bool,
+    /// 摇杆推动期间陀螺仪输出的缩放系数 (0.0-1.0)，0 表示完全停用陀螺仪
+    pub gyro_scale: f64,
+}
+
+impl Default for GyroStickSuppressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            gyro_scale: 0.0,
+        }
+    }
+}
+
+/// 配置档案，未设置的字段沿用基础配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileOverride {
+    /// 摇杆灵敏度，单独设置的水平/垂直灵敏度按相同比例缩放
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub joystick_sensitivity: Option<f64>,
+    /// 陀螺仪灵敏度
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gyro_sensitivity: Option<f64>,
+    /// 按键绑定，与基础绑定合并（同名绑定以档案为准，绑定为 "None" 可停用基础绑定）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub button_mappings: Option<HashMap<String, ButtonAction>>,
+    /// 组合键绑定，与基础组合键绑定合并（同名绑定以档案为准）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub combo_mappings: Option<HashMap<String, ButtonAction>>,
+}
+
+/// 解锁组合键配置
+///
+/// 用于共用电脑等场景：启用后手柄在每次运行时都处于锁定状态，
+/// 同时按下解锁组合键后才开始移动光标和执行绑定，避免闲置的手柄误操作。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArmingConfig {
+    /// 是否需要先解锁
+    pub require_arm: bool,
+    /// 解锁组合键（按键名称，与按键绑定中的名称相同，另支持 "LT"/"RT"）
+    pub arm_chord: Vec<String>,
+    /// 重新锁定的组合键，为空表示解锁后一直保持到程序退出
+    pub disarm_chord: Vec<String>,
+}
+
+impl Default for ArmingConfig {
+    fn default() -> Self {
+        Self {
+            require_arm: false,
+            arm_chord: vec!["LB".to_string(), "RB".to_string()],
+            disarm_chord: Vec::new(),
+        }
+    }
+}
+
 /// 重连配置结构体
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ReconnectionConfig {
     /// 是否启用自动重连
     pub enable_auto_reconnect: bool,
-    /// 重连尝试间隔（毫秒）
+    /// 重连尝试间隔（毫秒），启用退避时为首次等待的间隔
     pub reconnect_interval_ms: u64,
+    /// 重连退避倍率：每次重连失败后等待间隔乘以该值（1.0表示固定间隔）
+    pub reconnect_backoff_multiplier: f64,
+    /// 退避后重连间隔的上限（毫秒）
+    pub reconnect_max_interval_ms: u64,
     /// 最大重连尝试次数（0表示无限制）
     pub max_reconnect_attempts: u32,
     /// 是否显示重连消息
     pub show_reconnect_messages: bool,
     /// 最大静默失败次数（超过此次数后开始显示重连消息）
     pub max_silent_failures: u32,
+    /// 断线宽限期（毫秒），期间保持按住的输出不释放，宽限期内重连视为无缝恢复（0表示立即释放）
+    pub disconnect_grace_ms: u64,
+    /// 初始连接失败时的提示方式
+    pub on_initial_failure: InitialFailurePolicy,
+    /// 从未连接成功时的等待超时（毫秒），超时后退出程序（0表示一直等待）
+    pub initial_connect_timeout_ms: u64,
+    /// 重连成功后是否重新打印完整的操作说明（否则只打印一行重连提示）
+    pub reprint_instructions: bool,
 }
 
 impl Default for ReconnectionConfig {
@@ -206,9 +2094,588 @@ impl Default for ReconnectionConfig {
         Self {
             enable_auto_reconnect: true,
             reconnect_interval_ms: 2000,
+            reconnect_backoff_multiplier: 1.0,
+            reconnect_max_interval_ms: 30000,
             max_reconnect_attempts: 0, // 无限制
             show_reconnect_messages: true,
             max_silent_failures: 5,
+            disconnect_grace_ms: 0,
+            on_initial_failure: InitialFailurePolicy::Wait,
+            initial_connect_timeout_ms: 0,
+            reprint_instructions: false,
+        }
+    }
+}
+
+/// 多手柄配置
+///
+/// 同时连接多个手柄时，未指定配置档案的手柄共用当前配置，输入合并后一起控制光标；
+/// 指定了配置档案的手柄按档案分组，每组使用独立的按键绑定和灵敏度。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MultiControllerConfig {
+    /// 同时连接的手柄数量上限（1表示只连接第一个找到的手柄）
+    pub max_devices: usize,
+    /// 按连接顺序为每个手柄指定的配置档案，空字符串或未列出的手柄共用当前配置
+    pub device_profiles: Vec<String>,
+}
+
+impl Default for MultiControllerConfig {
+    fn default() -> Self {
+        Self {
+            max_devices: 1,
+            device_profiles: Vec::new(),
+        }
+    }
+}
+
+impl MultiControllerConfig {
+    /// 第 `index` 个连接的手柄（从0开始）指定的配置档案
+    pub fn profile_for(&self, index: usize) -> Option<&str> {
+        self.device_profiles
+            .get(index)
+            .map(String::as_str)
+            .filter(|name| !name.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 旧版本配置文件中必须出现的字段
+    const BASELINE_TOML: &str = r#"
+analog_trigger_threshold = 20
+joystick_deadzone = 1000
+right_joystick_deadzone = 5000
+gyro_deadzone = 10
+nav_trigger_threshold = 32001
+dominant_axis_factor = 1.5
+joystick_sensitivity = 15.0
+gyro_sensitivity = 0.08
+direct_scroll_sensitivity = 20.0
+pacer_loop_hz = 75
+
+[reconnection]
+enable_auto_reconnect = true
+reconnect_interval_ms = 2000
+max_reconnect_attempts = 0
+show_reconnect_messages = true
+max_silent_failures = 5
+
+[button_mappings]
+"A" = "LeftClick"
+"#;
+
+    fn parse(toml: &str) -> Result<ControllerConfig, toml::de::Error> {
+        toml::from_str(toml)
+    }
+
+    #[test]
+    fn baseline_config_loads_with_empty_on_connect_actions() {
+        let config = parse(BASELINE_TOML).unwrap();
+        assert!(config.on_connect_actions.is_empty());
+        assert_eq!(config.main_loop_max_hz, 250);
+        assert_eq!(config.button_mappings.len(), 1);
+    }
+
+    #[test]
+    fn on_connect_actions_round_trip() {
+        let toml = format!(
+            "on_connect_actions = [\"Refresh\", {{ LaunchApp = \"Safari\" }}]\n{}",
+            BASELINE_TOML
+        );
+        let config = parse(&toml).unwrap();
+        assert!(matches!(
+            config.on_connect_actions.as_slice(),
+            [ButtonAction::Refresh, ButtonAction::LaunchApp(app)] if app == "Safari"
+        ));
+
+        let reparsed = parse(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(reparsed.on_connect_actions.len(), 2);
+    }
+
+    #[test]
+    fn baseline_fields_are_still_required() {
+        let without_bindings = BASELINE_TOML.split("[button_mappings]").next().unwrap();
+        assert!(parse(without_bindings).is_err());
+        assert!(parse(&BASELINE_TOML.replace("pacer_loop_hz = 75", "")).is_err());
+    }
+
+    #[test]
+    fn preset_fills_fields_not_set_explicitly() {
+        let toml = format!(
+            "preset = \"precise\"\n{}",
+            BASELINE_TOML.replace("joystick_sensitivity = 15.0\n", "")
+        );
+        let config = ControllerConfig::from_toml_str(&toml).unwrap();
+        let precise = TuningPreset::find("precise").unwrap();
+
+        assert_eq!(config.joystick_sensitivity, precise.joystick_sensitivity);
+        assert_eq!(config.acceleration_curve, precise.acceleration_curve);
+        // 显式写出的字段优先于预设
+        assert_eq!(config.joystick_deadzone, 1000);
+    }
+
+    #[test]
+    fn preset_does_not_override_explicit_curve() {
+        let toml = format!(
+            "preset = \"FAST\"\nacceleration_curve = {{ type = \"Linear\" }}\n{}",
+            BASELINE_TOML
+        );
+        let config = ControllerConfig::from_toml_str(&toml).unwrap();
+        assert_eq!(config.acceleration_curve, AccelerationCurve::Linear);
+    }
+
+    #[test]
+    fn unknown_preset_is_rejected() {
+        let toml = format!("preset = \"turbo\"\n{}", BASELINE_TOML);
+        assert!(ControllerConfig::from_toml_str(&toml).is_err());
+    }
+
+    #[test]
+    fn default_template_loads_through_the_same_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let created = ControllerConfig::load_or_create_default(&path).unwrap();
+        let reloaded = ControllerConfig::load_from_file(&path).unwrap();
+        assert_eq!(created.joystick_sensitivity, reloaded.joystick_sensitivity);
+        assert_eq!(
+            created.button_mappings.len(),
+            reloaded.button_mappings.len()
+        );
+    }
+
+    #[test]
+    fn partial_shortcuts_table_keeps_other_defaults() {
+        let toml = format!(
+            "{}\n\"RB\" = \"ScrollToBottom\"\n\n[shortcuts]\nscroll_to_top = {{ modifiers = [\"alt\"], key = \"home\" }}\n",
+            BASELINE_TOML.trim_end()
+        );
+        let config = parse(&toml).unwrap();
+        let defaults = ActionShortcuts::default();
+
+        assert_eq!(config.shortcuts.scroll_to_top.modifiers, ["alt"]);
+        assert_eq!(config.shortcuts.scroll_to_top.key, "home");
+        assert_eq!(config.shortcuts.scroll_to_top.presses, 1);
+        assert_eq!(
+            config.shortcuts.scroll_to_bottom.key,
+            defaults.scroll_to_bottom.key
+        );
+        assert!(matches!(
+            config.button_mappings.get("RB"),
+            Some(ButtonAction::ScrollToBottom)
+        ));
+
+        let reparsed = parse(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(reparsed.shortcuts.scroll_to_top.key, "home");
+        assert!(matches!(
+            reparsed.button_mappings.get("RB"),
+            Some(ButtonAction::ScrollToBottom)
+        ));
+    }
+
+    #[test]
+    fn lock_screen_binding_and_shortcut_round_trip() {
+        let toml = format!(
+            "{}\n\"Back\" = \"LockScreen\"\n\n[shortcuts]\nlock_screen = {{ modifiers = [\"meta\"], key = \"l\" }}\n",
+            BASELINE_TOML.trim_end()
+        );
+        let config = parse(&toml).unwrap();
+        assert!(matches!(
+            config.button_mappings.get("Back"),
+            Some(ButtonAction::LockScreen)
+        ));
+        assert_eq!(config.shortcuts.lock_screen.modifiers, ["meta"]);
+
+        let reparsed = parse(&toml::to_string(&config).unwrap()).unwrap();
+        assert!(matches!(
+            reparsed.button_mappings.get("Back"),
+            Some(ButtonAction::LockScreen)
+        ));
+        assert_eq!(reparsed.shortcuts.lock_screen.key, "l");
+
+        // 未写出时使用当前系统的默认快捷键
+        let defaults = parse(BASELINE_TOML).unwrap();
+        assert_eq!(
+            defaults.shortcuts.lock_screen.key,
+            ActionShortcuts::default().lock_screen.key
+        );
+    }
+
+    #[test]
+    fn paste_plain_binding_and_shortcut_round_trip() {
+        let toml = format!(
+            "{}\n\"Y\" = \"PastePlain\"\n\n[shortcuts]\npaste_plain = {{ modifiers = [\"ctrl\", \"shift\"], key = \"v\" }}\n",
+            BASELINE_TOML.trim_end()
+        );
+        let config = parse(&toml).unwrap();
+        let reparsed = parse(&toml::to_string(&config).unwrap()).unwrap();
+        for config in [config, reparsed] {
+            assert!(matches!(
+                config.button_mappings.get("Y"),
+                Some(ButtonAction::PastePlain)
+            ));
+            assert_eq!(config.shortcuts.paste_plain.modifiers, ["ctrl", "shift"]);
+            assert_eq!(config.shortcuts.paste_plain.key, "v");
+        }
+    }
+
+    #[test]
+    fn max_scroll_power_defaults_to_unlimited() {
+        let config = parse(BASELINE_TOML).unwrap();
+        assert_eq!(config.max_scroll_power, 0.0);
+        assert!(config.validate().is_ok());
+
+        let negative = format!("max_scroll_power = -1.0\n{}", BASELINE_TOML);
+        assert!(parse(&negative).unwrap().validate().is_err());
+    }
+
+    #[test]
+    fn disabled_binding_stays_in_config() {
+        let toml = BASELINE_TOML.replace(
+            "[button_mappings]",
+            "[binding_enabled]\n\"A\" = false\n\n[button_mappings]",
+        );
+        let config = parse(&toml).unwrap();
+        assert!(config.button_mappings.contains_key("A"));
+        assert!(!config.is_binding_enabled("A"));
+        assert!(config.get_button_action("A").is_none());
+        // 未列出的绑定默认启用
+        assert!(config.is_binding_enabled("B"));
+
+        let reparsed = parse(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(reparsed.binding_enabled.get("A"), Some(&false));
+    }
+
+    /// 序列化后的默认配置中没有说明的字段
+    ///
+    /// 顶层字段和各个表本身需要说明，表中的普通字段也需要说明；
+    /// 以按键或档案名称为键的表只要求表本身有说明。
+    fn undocumented_fields(config: &ControllerConfig) -> Vec<String> {
+        let documented = |section: &str, key: &str| {
+            FIELD_DOCS
+                .iter()
+                .any(|(s, k, _)| *s == section && *k == key)
+        };
+        let value = toml::Value::try_from(config).unwrap();
+        let mut missing = Vec::new();
+
+        for (key, value) in value.as_table().unwrap() {
+            let Some(table) = value.as_table() else {
+                if !documented("", key) {
+                    missing.push(key.clone());
+                }
+                continue;
+            };
+            if !documented(key, "") {
+                missing.push(format!("[{}]", key));
+            }
+            let named_entries = [
+                "button_mappings",
+                "combo_mappings",
+                "binding_enabled",
+                "repeat_actions",
+                "profiles",
+            ];
+            if named_entries.contains(&key.as_str()) {
+                continue;
+            }
+            for (child, child_value) in table {
+                let section = format!("{}.{}", key, child);
+                let ok = if child_value.is_table() {
+                    documented(&section, "")
+                } else {
+                    documented(key, child)
+                };
+                if !ok {
+                    missing.push(section);
+                }
+            }
+        }
+        missing
+    }
+
+    #[test]
+    fn default_template_documents_every_field_and_parses_back() {
+        let defaults = ControllerConfig::default();
+        assert_eq!(undocumented_fields(&defaults), Vec::<String>::new());
+
+        let template = ControllerConfig::default_template_toml().unwrap();
+        let parsed = ControllerConfig::from_toml_str(&template).unwrap();
+        assert!(parsed.validate().is_ok());
+        assert_eq!(
+            toml::Value::try_from(&parsed).unwrap(),
+            toml::Value::try_from(&defaults).unwrap()
+        );
+    }
+
+    #[test]
+    fn optional_fields_are_documented_when_set() {
+        let mut config = ControllerConfig {
+            joystick_sensitivity_x: Some(10.0),
+            joystick_sensitivity_y: Some(12.0),
+            speed_modifier_button: Some("RB".to_string()),
+            zoom_scroll_button: Some("LB".to_string()),
+            preset: Some("balanced".to_string()),
+            action_log_path: Some("actions.log".to_string()),
+            default_action: Some(ButtonAction::Refresh),
+            primary_modifier: Some("ctrl".to_string()),
+            profile: Some("work".to_string()),
+            ..ControllerConfig::default()
+        };
+        config
+            .profiles
+            .insert("work".to_string(), ProfileOverride::default());
+        config.binding_enabled.insert("A".to_string(), false);
+        config
+            .combo_mappings
+            .insert("RB+A".to_string(), ButtonAction::LeftClick);
+        config
+            .repeat_actions
+            .insert("DPad_Down".to_string(), RepeatConfig::default());
+        assert_eq!(undocumented_fields(&config), Vec::<String>::new());
+    }
+
+    #[test]
+    fn toggle_fullscreen_binding_and_shortcut_round_trip() {
+        let toml = format!(
+            "{}\n\"Start\" = \"ToggleFullscreen\"\n\n[shortcuts]\ntoggle_fullscreen = {{ modifiers = [\"cmd\", \"shift\"], key = \"f\" }}\n",
+            BASELINE_TOML.trim_end()
+        );
+        let config = parse(&toml).unwrap();
+        let reparsed = parse(&toml::to_string(&config).unwrap()).unwrap();
+        for config in [config, reparsed] {
+            assert!(matches!(
+                config.button_mappings.get("Start"),
+                Some(ButtonAction::ToggleFullscreen)
+            ));
+            assert_eq!(
+                config.shortcuts.toggle_fullscreen.modifiers,
+                ["cmd", "shift"]
+            );
+            assert_eq!(config.shortcuts.toggle_fullscreen.key, "f");
+        }
+
+        // 未写出时使用当前系统的默认快捷键
+        let defaults = parse(BASELINE_TOML).unwrap();
+        let expected = ActionShortcuts::default().toggle_fullscreen;
+        assert_eq!(defaults.shortcuts.toggle_fullscreen.key, expected.key);
+        assert_eq!(
+            defaults.shortcuts.toggle_fullscreen.modifiers,
+            expected.modifiers
+        );
+        assert!(matches!(
+            ControllerConfig::default()
+                .button_mappings
+                .get("LT+DPad_Right"),
+            Some(ButtonAction::ToggleFullscreen)
+        ));
+    }
+
+    #[test]
+    fn drift_check_is_off_unless_configured() {
+        assert_eq!(parse(BASELINE_TOML).unwrap().drift_check_ms, 0);
+        assert_eq!(ControllerConfig::default().drift_check_ms, 0);
+
+        let enabled = format!("drift_check_ms = 300\n{}", BASELINE_TOML);
+        assert_eq!(parse(&enabled).unwrap().drift_check_ms, 300);
+    }
+
+    #[test]
+    fn window_actions_and_shortcuts_round_trip() {
+        let toml = format!(
+            "{}\n\"LB\" = \"MinimizeWindow\"\n\"RB\" = \"HideApp\"\n\n[shortcuts]\nminimize_window = {{ modifiers = [\"cmd\"], key = \"m\" }}\nhide_app = {{ modifiers = [\"cmd\", \"alt\"], key = \"h\" }}\n",
+            BASELINE_TOML.trim_end()
+        );
+        let config = parse(&toml).unwrap();
+        let reparsed = parse(&toml::to_string(&config).unwrap()).unwrap();
+        for config in [config, reparsed] {
+            assert!(matches!(
+                config.button_mappings.get("LB"),
+                Some(ButtonAction::MinimizeWindow)
+            ));
+            assert!(matches!(
+                config.button_mappings.get("RB"),
+                Some(ButtonAction::HideApp)
+            ));
+            assert_eq!(config.shortcuts.minimize_window.modifiers, ["cmd"]);
+            assert_eq!(config.shortcuts.minimize_window.key, "m");
+            assert_eq!(config.shortcuts.hide_app.modifiers, ["cmd", "alt"]);
+            assert_eq!(config.shortcuts.hide_app.key, "h");
         }
+
+        // 只写出其中一个时另一个使用当前系统的默认快捷键
+        let partial = format!(
+            "{}\n\n[shortcuts]\nhide_app = {{ modifiers = [\"cmd\"], key = \"h\" }}\n",
+            BASELINE_TOML.trim_end()
+        );
+        let config = parse(&partial).unwrap();
+        let defaults = ActionShortcuts::default();
+        assert_eq!(
+            config.shortcuts.minimize_window.key,
+            defaults.minimize_window.key
+        );
+        assert_eq!(
+            config.shortcuts.minimize_window.modifiers,
+            defaults.minimize_window.modifiers
+        );
+    }
+
+    #[test]
+    fn fn_key_shortcut_is_only_accepted_on_macos() {
+        let toml = format!(
+            "{}\n\n[shortcuts]\nstart_dictation = {{ modifiers = [], key = \"fn\", presses = 2 }}\n",
+            BASELINE_TOML.trim_end()
+        );
+        let config = parse(&toml).unwrap();
+        assert_eq!(config.shortcuts.start_dictation.presses, 2);
+        assert_eq!(
+            config.validate().is_ok(),
+            cfg!(target_os = "macos"),
+            "{:?}",
+            config.validate()
+        );
+    }
+
+    #[test]
+    fn default_shortcuts_are_valid_on_this_platform() {
+        let config = parse(BASELINE_TOML).unwrap();
+        assert!(config.validate().is_ok(), "{:?}", config.validate());
+    }
+
+    #[test]
+    fn unknown_shortcut_key_is_rejected() {
+        let toml = format!(
+            "{}\n\n[shortcuts]\nlock_screen = {{ modifiers = [\"hyper\"], key = \"l\" }}\n",
+            BASELINE_TOML.trim_end()
+        );
+        let error = parse(&toml).unwrap().validate().unwrap_err();
+        assert!(error.contains("shortcuts.lock_screen"), "{}", error);
+    }
+
+    #[test]
+    fn switch_tab_round_trips_and_validates_index() {
+        let toml = format!(
+            "{}\n\"DPad_Left\" = {{ SwitchTab = {{ index = 1 }} }}\n\"DPad_Right\" = {{ Macro = [{{ Action = {{ SwitchTab = {{ index = 9 }} }} }}] }}\n",
+            BASELINE_TOML.trim_end()
+        );
+        let config = parse(&toml).unwrap();
+        let reparsed = parse(&toml::to_string(&config).unwrap()).unwrap();
+        for config in [config, reparsed] {
+            assert!(config.validate().is_ok());
+            assert!(matches!(
+                config.button_mappings.get("DPad_Left"),
+                Some(ButtonAction::SwitchTab { index: 1 })
+            ));
+            match config.button_mappings.get("DPad_Right") {
+                Some(ButtonAction::Macro(steps)) => assert!(matches!(
+                    steps.as_slice(),
+                    [MacroStep::Action(ButtonAction::SwitchTab { index: 9 })]
+                )),
+                other => panic!("unexpected mapping: {:?}", other),
+            }
+        }
+
+        // 序号没有默认值，超出 1-9 时在校验时报错
+        let missing = format!(
+            "{}\n\"DPad_Left\" = {{ SwitchTab = {{}} }}\n",
+            BASELINE_TOML.trim_end()
+        );
+        assert!(parse(&missing).is_err());
+        for index in [0, 10] {
+            let toml = format!(
+                "{}\n\"DPad_Left\" = {{ SwitchTab = {{ index = {} }} }}\n",
+                BASELINE_TOML.trim_end(),
+                index
+            );
+            assert!(parse(&toml).unwrap().validate().is_err(), "index {}", index);
+        }
+    }
+
+    #[test]
+    fn sigmoid_steepness_has_a_minimum() {
+        let curve = |steepness: &str| {
+            let toml = format!(
+                "acceleration_curve = {{ type = \"Sigmoid\", steepness = {} }}\n{}",
+                steepness, BASELINE_TOML
+            );
+            parse(&toml).unwrap().validate()
+        };
+        assert!(curve("1.0").is_ok());
+        assert!(curve("10.0").is_ok());
+        for steepness in ["0.5", "0.0", "-3.0", "nan"] {
+            assert!(curve(steepness).is_err(), "steepness {}", steepness);
+        }
+    }
+
+    #[test]
+    fn macro_steps_are_validated_when_loading() {
+        let with_mapping = |mapping: &str| {
+            let toml = format!("{}\n\"Y\" = {}\n", BASELINE_TOML.trim_end(), mapping);
+            parse(&toml).unwrap().validate()
+        };
+
+        // 延时在后台计时，不再限制上限
+        assert!(with_mapping("{ Macro = [{ Delay = 60000 }, { TypeText = \"hi\" }] }").is_ok());
+        assert!(with_mapping("{ Macro = [{ TypeText = \"\" }] }").is_err());
+        assert!(
+            with_mapping(
+                "{ Macro = [{ KeyShortcut = { modifiers = [\"cmd\"], key = \"nope\" } }] }"
+            )
+            .is_err()
+        );
+        assert!(
+            with_mapping(
+                "{ Macro = [{ KeyShortcut = { modifiers = [\"hyper\"], key = \"l\" } }] }"
+            )
+            .is_err()
+        );
+        assert!(
+            with_mapping("{ Macro = [{ Action = { Macro = [{ TypeText = \"\" }] } }] }").is_err()
+        );
+        assert!(with_mapping("{ HoldKey = \"nope\" }").is_err());
+        assert!(
+            with_mapping("{ CustomShortcut = { modifiers = [\"ctrl\"], key = \"pageup\" } }")
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn scroll_mode_accepts_legacy_scroll_smooth() {
+        assert_eq!(
+            parse(BASELINE_TOML).unwrap().scroll_mode,
+            ScrollMode::Smooth
+        );
+
+        let line = parse(&format!("scroll_mode = \"line\"\n{}", BASELINE_TOML)).unwrap();
+        assert_eq!(line.scroll_mode, ScrollMode::Line);
+        let reparsed = parse(&toml::to_string(&line).unwrap()).unwrap();
+        assert_eq!(reparsed.scroll_mode, ScrollMode::Line);
+
+        for (smooth, mode) in [(true, ScrollMode::Smooth), (false, ScrollMode::Line)] {
+            let toml = format!("scroll_smooth = {}\n{}", smooth, BASELINE_TOML);
+            assert_eq!(parse(&toml).unwrap().scroll_mode, mode);
+        }
+    }
+
+    #[test]
+    fn profile_sensitivity_scales_per_axis_overrides() {
+        let mut config = ControllerConfig {
+            joystick_sensitivity: 10.0,
+            joystick_sensitivity_x: Some(20.0),
+            ..ControllerConfig::default()
+        };
+        config.profiles.insert(
+            "precise".to_string(),
+            ProfileOverride {
+                joystick_sensitivity: Some(5.0),
+                ..ProfileOverride::default()
+            },
+        );
+
+        let precise = config.with_profile("precise").unwrap();
+        assert_eq!(precise.stick_sensitivity(), (10.0, 5.0));
+        assert_eq!(precise.joystick_sensitivity_y, None);
     }
 }
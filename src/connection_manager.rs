@@ -1,6 +1,8 @@
 use crate::config::ControllerConfig;
 use crate::error::ControllerResult;
-use crate::hid::HidController;
+use crate::hid::{DeviceOpener, HidOpener};
+use log::{Level, debug, info, log, warn};
+use std::sync::mpsc::{SyncSender, TrySendError};
 use std::{thread, time::Duration};
 
 /// 连接状态枚举
@@ -17,21 +19,44 @@ pub enum ConnectionState {
 }
 
 /// 连接管理器
-pub struct ConnectionManager {
+pub struct ConnectionManager<O: DeviceOpener = HidOpener> {
     state: ConnectionState,
     reconnect_config: crate::config::ReconnectionConfig,
     reconnect_attempts: u32,
     silent_failures: u32,
+    /// 同时连接的手柄数量上限
+    max_devices: usize,
+    /// 打开手柄的方式
+    opener: O,
+    /// 连接状态变化的接收方（例如菜单栏指示器）
+    listener: Option<SyncSender<ConnectionState>>,
 }
 
 impl ConnectionManager {
-    /// 创建新的连接管理器
-    pub fn new(config: &ControllerConfig) -> Self {
+    /// 创建通过 HidApi 连接手柄的连接管理器
+    ///
+    /// 传入 `listener` 时，每次连接状态变化都会把新状态发送给它。
+    /// 发送不会阻塞：接收方处理不及时导致通道已满时丢弃本次通知。
+    pub fn new(config: &ControllerConfig, listener: Option<SyncSender<ConnectionState>>) -> Self {
+        Self::with_opener(config, listener, HidOpener::new(config.hid_report_size))
+    }
+}
+
+impl<O: DeviceOpener> ConnectionManager<O> {
+    /// 创建使用指定方式打开手柄的连接管理器
+    pub fn with_opener(
+        config: &ControllerConfig,
+        listener: Option<SyncSender<ConnectionState>>,
+        opener: O,
+    ) -> Self {
         Self {
             state: ConnectionState::Disconnected,
             reconnect_config: config.reconnection.clone(),
             reconnect_attempts: 0,
             silent_failures: 0,
+            max_devices: config.multi_controller.max_devices,
+            opener,
+            listener,
         }
     }
 
@@ -40,18 +65,39 @@ impl ConnectionManager {
         &self.state
     }
 
+    /// 切换连接状态，状态确有变化时通知接收方
+    fn set_state(&mut self, state: ConnectionState) {
+        if self.state == state {
+            return;
+        }
+        self.state = state;
+
+        if let Some(listener) = &self.listener {
+            match listener.try_send(self.state.clone()) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => debug!("连接状态通知通道已满，丢弃本次通知"),
+                Err(TrySendError::Disconnected(_)) => self.listener = None,
+            }
+        }
+    }
+
+    /// 连接所有匹配的手柄（不超过数量上限）
+    fn connect_devices(&mut self) -> ControllerResult<Vec<O::Device>> {
+        self.opener.open_all(self.max_devices)
+    }
+
     /// 尝试初始连接
-    pub fn initial_connect(&mut self) -> ControllerResult<HidController> {
-        self.state = ConnectionState::Reconnecting;
+    pub fn initial_connect(&mut self) -> ControllerResult<Vec<O::Device>> {
+        self.set_state(ConnectionState::Reconnecting);
 
-        match HidController::new() {
-            Ok(controller) => {
-                self.state = ConnectionState::Connected;
+        match self.connect_devices() {
+            Ok(controllers) => {
+                self.set_state(ConnectionState::Connected);
                 self.reset_counters();
-                Ok(controller)
+                Ok(controllers)
             }
             Err(e) => {
-                self.state = ConnectionState::Disconnected;
+                self.set_state(ConnectionState::Disconnected);
                 Err(e)
             }
         }
@@ -60,19 +106,19 @@ impl ConnectionManager {
     /// 处理设备断开事件
     pub fn handle_disconnect(&mut self) {
         if self.state == ConnectionState::Connected {
-            self.state = ConnectionState::Disconnected;
+            self.set_state(ConnectionState::Disconnected);
             self.silent_failures = 0;
 
             if self.reconnect_config.show_reconnect_messages {
-                println!("手柄已断开连接，等待重新连接...");
+                warn!("手柄已断开连接，等待重新连接...");
             }
         }
     }
 
     /// 尝试重新连接
-    /// 返回 Some(controller) 表示重连成功
+    /// 返回 Some(Ok(controllers)) 表示重连成功
     /// 返回 None 表示重连失败或不需要重连
-    pub fn try_reconnect(&mut self) -> Option<ControllerResult<HidController>> {
+    pub fn try_reconnect(&mut self) -> Option<ControllerResult<Vec<O::Device>>> {
         // 检查是否启用自动重连
         if !self.reconnect_config.enable_auto_reconnect {
             return None;
@@ -83,7 +129,7 @@ impl ConnectionManager {
             && self.reconnect_attempts >= self.reconnect_config.max_reconnect_attempts
         {
             if self.reconnect_config.show_reconnect_messages {
-                println!("已达到最大重连尝试次数，停止重连。");
+                warn!("已达到最大重连尝试次数，停止重连。");
             }
             return None;
         }
@@ -95,7 +141,7 @@ impl ConnectionManager {
             return None;
         }
 
-        self.state = ConnectionState::Reconnecting;
+        self.set_state(ConnectionState::Reconnecting);
         self.reconnect_attempts += 1;
 
         // 决定是否显示重连消息
@@ -103,37 +149,46 @@ impl ConnectionManager {
             && (self.silent_failures >= self.reconnect_config.max_silent_failures
                 || self.reconnect_attempts % 10 == 1); // 每10次尝试显示一次
 
-        if should_show_message {
-            if self.reconnect_config.max_reconnect_attempts > 0 {
-                println!(
-                    "正在尝试重新连接手柄... (第 {}/{} 次)",
-                    self.reconnect_attempts, self.reconnect_config.max_reconnect_attempts
-                );
-            } else {
-                println!(
-                    "正在尝试重新连接手柄... (第 {} 次)",
-                    self.reconnect_attempts
-                );
-            }
+        // 不显示的尝试降为调试日志，RUST_LOG=debug 时可看到每一次尝试
+        let level = if should_show_message {
+            Level::Info
+        } else {
+            Level::Debug
+        };
+        if self.reconnect_config.max_reconnect_attempts > 0 {
+            log!(
+                level,
+                "正在尝试重新连接手柄... (第 {}/{} 次)",
+                self.reconnect_attempts,
+                self.reconnect_config.max_reconnect_attempts
+            );
+        } else {
+            log!(
+                level,
+                "正在尝试重新连接手柄... (第 {} 次)",
+                self.reconnect_attempts
+            );
         }
 
-        match HidController::try_reconnect() {
-            Ok(controller) => {
-                self.state = ConnectionState::Connected;
+        match self.connect_devices() {
+            Ok(controllers) => {
+                self.set_state(ConnectionState::Connected);
                 self.reset_counters();
 
                 if self.reconnect_config.show_reconnect_messages {
-                    println!("手柄已重新连接！");
+                    info!("手柄已重新连接！");
                 }
 
-                Some(Ok(controller))
+                Some(Ok(controllers))
             }
             Err(e) => {
-                self.state = ConnectionState::WaitingReconnect;
+                self.set_state(ConnectionState::WaitingReconnect);
                 self.silent_failures += 1;
 
                 if should_show_message {
-                    println!("重连失败: {}", e);
+                    warn!("重连失败: {}", e);
+                } else {
+                    debug!("重连失败: {}", e);
                 }
 
                 Some(Err(e))
@@ -144,12 +199,19 @@ impl ConnectionManager {
     /// 等待重连间隔
     pub fn wait_reconnect_interval(&self) {
         if self.state == ConnectionState::WaitingReconnect {
-            thread::sleep(Duration::from_millis(
-                self.reconnect_config.reconnect_interval_ms,
-            ));
+            thread::sleep(self.reconnect_delay());
         }
     }
 
+    /// 本次重连失败后的等待间隔：基础间隔按退避倍率逐次增大，不超过上限
+    fn reconnect_delay(&self) -> Duration {
+        let config = &self.reconnect_config;
+        let exponent = self.reconnect_attempts.saturating_sub(1) as i32;
+        let delay = config.reconnect_interval_ms as f64
+            * config.reconnect_backoff_multiplier.powi(exponent);
+        Duration::from_millis(delay.min(config.reconnect_max_interval_ms as f64) as u64)
+    }
+
     /// 是否应该继续运行（用于主循环判断）
     pub fn should_continue(&self) -> bool {
         match self.state {
@@ -160,7 +222,7 @@ impl ConnectionManager {
         }
     }
 
-    /// 重置计数器
+    /// 重置计数器（重连间隔随之回到基础间隔）
     fn reset_counters(&mut self) {
         self.reconnect_attempts = 0;
         self.silent_failures = 0;
@@ -183,3 +245,141 @@ pub struct ReconnectStats {
     pub silent_failures: u32,
     pub state: ConnectionState,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ControllerError;
+    use std::collections::VecDeque;
+    use std::sync::mpsc;
+
+    /// 按顺序返回预设结果的模拟打开方式，记录每次请求的数量上限
+    struct MockOpener {
+        results: VecDeque<ControllerResult<Vec<u32>>>,
+        limits: Vec<usize>,
+    }
+
+    impl MockOpener {
+        fn new(results: Vec<ControllerResult<Vec<u32>>>) -> Self {
+            Self {
+                results: results.into(),
+                limits: Vec::new(),
+            }
+        }
+    }
+
+    impl DeviceOpener for MockOpener {
+        type Device = u32;
+
+        fn open_all(&mut self, limit: usize) -> ControllerResult<Vec<u32>> {
+            self.limits.push(limit);
+            self.results
+                .pop_front()
+                .unwrap_or(Err(ControllerError::DeviceNotFound))
+        }
+    }
+
+    fn manager(results: Vec<ControllerResult<Vec<u32>>>) -> ConnectionManager<MockOpener> {
+        ConnectionManager::with_opener(&ControllerConfig::default(), None, MockOpener::new(results))
+    }
+
+    #[test]
+    fn initial_connect_returns_opened_devices() {
+        let mut manager = manager(vec![Ok(vec![1, 2])]);
+        assert_eq!(manager.initial_connect().unwrap(), vec![1, 2]);
+        assert_eq!(manager.state(), &ConnectionState::Connected);
+        assert_eq!(
+            manager.opener.limits,
+            [ControllerConfig::default().multi_controller.max_devices]
+        );
+    }
+
+    #[test]
+    fn reconnect_after_failures_resets_counters() {
+        let mut manager = manager(vec![
+            Err(ControllerError::DeviceNotFound),
+            Err(ControllerError::DeviceNotFound),
+            Ok(vec![7]),
+        ]);
+        assert!(manager.initial_connect().is_err());
+        assert_eq!(manager.state(), &ConnectionState::Disconnected);
+
+        assert!(matches!(manager.try_reconnect(), Some(Err(_))));
+        let stats = manager.get_stats();
+        assert_eq!(stats.state, ConnectionState::WaitingReconnect);
+        assert_eq!((stats.attempts, stats.silent_failures), (1, 1));
+
+        assert_eq!(manager.try_reconnect().unwrap().unwrap(), vec![7]);
+        let stats = manager.get_stats();
+        assert_eq!(stats.state, ConnectionState::Connected);
+        assert_eq!((stats.attempts, stats.silent_failures), (0, 0));
+
+        // 已连接时不再尝试
+        assert!(manager.try_reconnect().is_none());
+        assert_eq!(manager.opener.limits.len(), 3);
+    }
+
+    #[test]
+    fn reconnect_stops_after_max_attempts() {
+        let mut config = ControllerConfig::default();
+        config.reconnection.max_reconnect_attempts = 2;
+        let mut manager = ConnectionManager::with_opener(&config, None, MockOpener::new(vec![]));
+        manager.handle_disconnect();
+
+        assert!(matches!(manager.try_reconnect(), Some(Err(_))));
+        assert!(matches!(manager.try_reconnect(), Some(Err(_))));
+        assert!(manager.try_reconnect().is_none());
+        assert_eq!(manager.opener.limits.len(), 2);
+    }
+
+    #[test]
+    fn reconnect_is_skipped_when_disabled() {
+        let mut config = ControllerConfig::default();
+        config.reconnection.enable_auto_reconnect = false;
+        let mut manager =
+            ConnectionManager::with_opener(&config, None, MockOpener::new(vec![Ok(vec![1])]));
+
+        assert!(manager.try_reconnect().is_none());
+        assert!(!manager.should_continue());
+        assert!(manager.opener.limits.is_empty());
+    }
+
+    #[test]
+    fn reconnect_delay_backs_off_up_to_the_limit() {
+        let mut config = ControllerConfig::default();
+        config.reconnection.reconnect_interval_ms = 100;
+        config.reconnection.reconnect_backoff_multiplier = 2.0;
+        config.reconnection.reconnect_max_interval_ms = 350;
+        let mut manager = ConnectionManager::with_opener(&config, None, MockOpener::new(vec![]));
+
+        let delays: Vec<u64> = (1..=4)
+            .map(|attempts| {
+                manager.reconnect_attempts = attempts;
+                manager.reconnect_delay().as_millis() as u64
+            })
+            .collect();
+        assert_eq!(delays, [100, 200, 350, 350]);
+    }
+
+    #[test]
+    fn state_changes_are_sent_to_listener() {
+        let (sender, receiver) = mpsc::sync_channel(8);
+        let mut manager = ConnectionManager::with_opener(
+            &ControllerConfig::default(),
+            Some(sender),
+            MockOpener::new(vec![Ok(vec![1])]),
+        );
+        manager.initial_connect().unwrap();
+        manager.handle_disconnect();
+
+        let states: Vec<ConnectionState> = receiver.try_iter().collect();
+        assert_eq!(
+            states,
+            [
+                ConnectionState::Reconnecting,
+                ConnectionState::Connected,
+                ConnectionState::Disconnected,
+            ]
+        );
+    }
+}
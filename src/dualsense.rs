@@ -0,0 +1,267 @@
+use crate::hid::{
+    BUTTON_A, BUTTON_B, BUTTON_GUIDE, BUTTON_L3, BUTTON_LB, BUTTON_R3, BUTTON_RB, BUTTON_SHARE,
+    BUTTON_START, BUTTON_X, BUTTON_Y, BatteryStatus, ControllerState, DPAD_DOWN, DPAD_LEFT,
+    DPAD_RIGHT, DPAD_UP, ReportParser,
+};
+use hidapi::{HidDevice, HidResult};
+use std::collections::HashSet;
+use std::time::Instant;
+
+// --- HID设备标识 ---
+pub const SONY_VENDOR_ID: u16 = 0x054C;
+
+// 支持的 DualSense 手柄产品ID列表
+pub const DUALSENSE_PRODUCT_IDS: &[u16] = &[
+    0x0CE6, // DualSense Wireless Controller
+    0x0DF2, // DualSense Edge Wireless Controller
+];
+
+// --- HID报告ID定义 ---
+const USB_INPUT_REPORT_ID: u8 = 0x01; // USB 连接的输入报告
+const BT_INPUT_REPORT_ID: u8 = 0x31; // 蓝牙连接的完整输入报告（比 USB 报告多一个序号字节）
+// 校准数据特性报告。蓝牙连接的手柄默认只发送不含陀螺仪和电量的精简 0x01 报告，
+// 读取一次该特性报告后才切换为完整的 0x31 报告；USB 连接时读取无副作用
+const CALIBRATION_FEATURE_REPORT_ID: u8 = 0x05;
+const CALIBRATION_FEATURE_REPORT_LEN: usize = 41;
+
+// --- 报告长度 ---
+const USB_INPUT_REPORT_LEN: usize = 64;
+const BT_INPUT_REPORT_LEN: usize = 78;
+
+// --- 输入数据偏移量（相对于报告ID之后的数据起点） ---
+const LX_OFFSET: usize = 0;
+const LY_OFFSET: usize = 1;
+const RX_OFFSET: usize = 2;
+const RY_OFFSET: usize = 3;
+const L2_OFFSET: usize = 4;
+const R2_OFFSET: usize = 5;
+const BUTTONS_0_OFFSET: usize = 7; // 低4位为方向键（八方向），高4位为方块/叉/圆/三角
+const BUTTONS_1_OFFSET: usize = 8; // L1/R1/L2/R2/创建/选项/L3/R3
+const BUTTONS_2_OFFSET: usize = 9; // PS键/触摸板按下/麦克风静音
+const GYRO_PITCH_OFFSET: usize = 15; // 绕X轴的角速度 (i16)
+const GYRO_YAW_OFFSET: usize = 17; // 绕Y轴的角速度 (i16)
+const STATUS_OFFSET: usize = 52; // 低4位为电量 (0-10)，高4位为充电状态
+
+// --- 按钮掩码定义 ---
+const SQUARE: u8 = 0x10;
+const CROSS: u8 = 0x20;
+const CIRCLE: u8 = 0x40;
+const TRIANGLE: u8 = 0x80;
+const L1: u8 = 0x01;
+const R1: u8 = 0x02;
+const CREATE: u8 = 0x10;
+const OPTIONS: u8 = 0x20;
+const L3: u8 = 0x40;
+const R3: u8 = 0x80;
+const PS: u8 = 0x01;
+
+// DualSense 陀螺仪为 16 位，缩小到与 Xbox 手柄 12 位陀螺仪相近的范围
+const GYRO_SHIFT: u32 = 4;
+
+/// DualSense 手柄的报告解析器
+///
+/// 按钮按位置映射到 Xbox 布局：叉→A、圆→B、方块→X、三角→Y、L1/R1→LB/RB、创建键→Share。
+/// 摇杆为 8 位无符号值，换算为与 Xbox 手柄一致的 i16 范围和方向。
+pub struct DualSenseReportParser;
+
+impl DualSenseReportParser {
+    /// 返回报告中输入数据的起点，不是完整输入报告时返回 None
+    fn data_start(buf: &[u8]) -> Option<usize> {
+        match buf.first() {
+            Some(&USB_INPUT_REPORT_ID) if buf.len() >= USB_INPUT_REPORT_LEN => Some(1),
+            Some(&BT_INPUT_REPORT_ID) if buf.len() >= BT_INPUT_REPORT_LEN => Some(2),
+            _ => None,
+        }
+    }
+
+    /// 将 0-255（128为中心）的摇杆值换算到 i16 范围
+    fn axis(value: u8) -> i16 {
+        (value as i16 - 128) * 256
+    }
+
+    /// 解析八方向的方向键（0为上，顺时针递增，8表示未按下）
+    fn insert_dpad(hat: u8, pressed_buttons: &mut HashSet<u8>) {
+        let directions: &[u8] = match hat {
+            0 => &[DPAD_UP],
+            1 => &[DPAD_UP, DPAD_RIGHT],
+            2 => &[DPAD_RIGHT],
+            3 => &[DPAD_DOWN, DPAD_RIGHT],
+            4 => &[DPAD_DOWN],
+            5 => &[DPAD_DOWN, DPAD_LEFT],
+            6 => &[DPAD_LEFT],
+            7 => &[DPAD_UP, DPAD_LEFT],
+            _ => &[],
+        };
+        pressed_buttons.extend(directions);
+    }
+}
+
+impl ReportParser for DualSenseReportParser {
+    fn parse(&self, buf: &[u8], threshold: u8) -> Option<ControllerState> {
+        let data = &buf[Self::data_start(buf)?..];
+        let lt = data[L2_OFFSET];
+        let rt = data[R2_OFFSET];
+
+        let mut pressed_buttons = HashSet::new();
+        let buttons_0 = data[BUTTONS_0_OFFSET];
+        let buttons_1 = data[BUTTONS_1_OFFSET];
+        let buttons_2 = data[BUTTONS_2_OFFSET];
+        Self::insert_dpad(buttons_0 & 0x0F, &mut pressed_buttons);
+        let mapping = [
+            (buttons_0, CROSS, BUTTON_A),
+            (buttons_0, CIRCLE, BUTTON_B),
+            (buttons_0, SQUARE, BUTTON_X),
+            (buttons_0, TRIANGLE, BUTTON_Y),
+            (buttons_1, L1, BUTTON_LB),
+            (buttons_1, R1, BUTTON_RB),
+            (buttons_1, CREATE, BUTTON_SHARE),
+            (buttons_1, OPTIONS, BUTTON_START),
+            (buttons_1, L3, BUTTON_L3),
+            (buttons_1, R3, BUTTON_R3),
+            (buttons_2, PS, BUTTON_GUIDE),
+        ];
+        for (byte, mask, button) in mapping {
+            if (byte & mask) != 0 {
+                pressed_buttons.insert(button);
+            }
+        }
+
+        // 陀螺仪数据（仅当LT按下时），方向与 Xbox 手柄一致：向右转为正、向上抬为负
+        let gyro = |offset: usize| i16::from_le_bytes([data[offset], data[offset + 1]]);
+        let (gyro_yaw, gyro_pitch) = if lt > threshold {
+            (
+                gyro(GYRO_YAW_OFFSET).saturating_neg() >> GYRO_SHIFT,
+                gyro(GYRO_PITCH_OFFSET).saturating_neg() >> GYRO_SHIFT,
+            )
+        } else {
+            (0, 0)
+        };
+
+        Some(ControllerState {
+            lx: Self::axis(data[LX_OFFSET]),
+            ly: Self::axis(data[LY_OFFSET]).saturating_neg(),
+            rx: Self::axis(data[RX_OFFSET]),
+            ry: Self::axis(data[RY_OFFSET]).saturating_neg(),
+            lt,
+            rt,
+            gyro_yaw,
+            gyro_pitch,
+            pressed_buttons,
+            tapped_buttons: HashSet::new(),
+            timestamp: Instant::now(),
+        })
+    }
+
+    fn report_len(&self) -> usize {
+        BT_INPUT_REPORT_LEN
+    }
+
+    fn is_input(&self, buf: &[u8]) -> bool {
+        Self::data_start(buf).is_some()
+    }
+
+    fn init_device(&self, device: &HidDevice) -> HidResult<()> {
+        let mut buf = [0u8; CALIBRATION_FEATURE_REPORT_LEN];
+        buf[0] = CALIBRATION_FEATURE_REPORT_ID;
+        device.get_feature_report(&mut buf).map(|_| ())
+    }
+
+    /// 电量信息随每份输入报告发送
+    fn battery(&self, buf: &[u8]) -> Option<BatteryStatus> {
+        let status = buf[Self::data_start(buf)? + STATUS_OFFSET];
+        Some(BatteryStatus {
+            level: ((status & 0x0F) * 10).min(100),
+            charging: (status >> 4) == 0x01,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::Capture;
+    use crate::hid::{BUTTON_LB, Report, parser_for};
+
+    const USB_CAPTURE: &str = include_str!("../testdata/dualsense_usb.capture");
+    const BT_CAPTURE: &str = include_str!("../testdata/dualsense_bt.capture");
+
+    fn decode(content: &str) -> Vec<Report> {
+        let capture = Capture::parse(content).unwrap();
+        let parser = parser_for(capture.vendor_id, capture.product_id);
+        capture
+            .reports
+            .iter()
+            .map(|report| Report::parse(&report.data, 20, parser.as_ref()))
+            .collect()
+    }
+
+    /// USB 和蓝牙录制中的三份完整输入报告内容相同，解析结果也应相同
+    fn assert_input_frames(reports: &[Report]) {
+        let states: Vec<&ControllerState> = reports
+            .iter()
+            .map(|report| match report {
+                Report::Input(state) => state,
+                _ => panic!("expected a full input report"),
+            })
+            .collect();
+        let [idle, pressed, gyro] = states.as_slice() else {
+            panic!("expected three input reports");
+        };
+
+        assert!(idle.pressed_buttons.is_empty());
+        assert_eq!((idle.lx, idle.ly, idle.rx, idle.ry), (0, 0, 0, 0));
+
+        assert_eq!(
+            pressed.pressed_buttons,
+            HashSet::from([BUTTON_A, DPAD_RIGHT, BUTTON_LB])
+        );
+        assert_eq!(pressed.lx, 127 * 256);
+        assert_eq!(pressed.ly, i16::MAX);
+        assert_eq!((pressed.gyro_yaw, pressed.gyro_pitch), (0, 0));
+
+        assert_eq!(gyro.lt, 255);
+        assert_eq!(gyro.pressed_buttons, HashSet::from([BUTTON_GUIDE]));
+        assert_eq!((gyro.gyro_yaw, gyro.gyro_pitch), (32, -16));
+    }
+
+    #[test]
+    fn usb_fixture_decodes_input_and_battery() {
+        let reports = decode(USB_CAPTURE);
+        assert_input_frames(&reports);
+
+        let capture = Capture::parse(USB_CAPTURE).unwrap();
+        let battery = DualSenseReportParser.battery(&capture.reports[0].data);
+        assert_eq!(
+            battery,
+            Some(BatteryStatus {
+                level: 80,
+                charging: true
+            })
+        );
+    }
+
+    #[test]
+    fn bt_fixture_ignores_simple_reports_until_full_mode() {
+        let reports = decode(BT_CAPTURE);
+        // 读取特性报告 0x05 之前的精简报告无法解析
+        assert!(matches!(reports[0], Report::Unknown));
+        assert_input_frames(&reports[1..]);
+    }
+
+    #[test]
+    fn dpad_hat_maps_diagonals() {
+        let mut pressed = HashSet::new();
+        DualSenseReportParser::insert_dpad(7, &mut pressed);
+        assert_eq!(pressed, HashSet::from([DPAD_UP, DPAD_LEFT]));
+
+        pressed.clear();
+        DualSenseReportParser::insert_dpad(8, &mut pressed);
+        assert!(pressed.is_empty());
+    }
+
+    #[test]
+    fn dualsense_has_no_rumble_report() {
+        let parser = parser_for(SONY_VENDOR_ID, DUALSENSE_PRODUCT_IDS[0]);
+        assert_eq!(parser.rumble_report(u8::MAX, u8::MAX), None);
+    }
+}
@@ -1,6 +1,11 @@
+use crate::dualsense::{DUALSENSE_PRODUCT_IDS, DualSenseReportParser, SONY_VENDOR_ID};
 use crate::error::{ControllerError, ControllerResult};
-use hidapi::{HidApi, HidDevice};
+use hidapi::{HidApi, HidDevice, HidResult};
+use log::{info, warn};
+use std::cell::{Cell, RefCell};
 use std::collections::HashSet;
+use std::thread;
+use std::time::{Duration, Instant};
 
 // --- HID设备标识 ---
 pub const TARGET_VENDOR_ID: u16 = 0x045E;
@@ -12,11 +17,29 @@ pub const SUPPORTED_PRODUCT_IDS: &[u16] = &[
     0x02FD, // Xbox One Controller (有线)
     0x02EA, // Xbox One Controller (蓝牙)
     0x0719, // Xbox 360 Wireless Receiver
+    0x02E3, // Xbox Elite Controller (有线)
+    0x0B00, // Xbox Elite Controller Series 2 (有线)
+    0x0B05, // Xbox Elite Controller Series 2 (蓝牙)
+    0x0B22, // Xbox Elite Controller Series 2 (蓝牙，新版固件)
+    0x0B12, // Xbox Series X|S Controller (有线)
+    0x0B13, // Xbox Series X|S Controller (蓝牙)
 ];
 
+// 带分享键的产品ID列表（Xbox Series X|S 手柄）
+// 分享键使用字节3的 bit3，该位在其他手柄的报告中是保留位，只对这些手柄解析
+pub const SHARE_BUTTON_PRODUCT_IDS: &[u16] = &[0x0B12, 0x0B13];
+
+// Elite 手柄的产品ID列表，只有这些手柄解析背键字节
+pub const ELITE_PRODUCT_IDS: &[u16] = &[0x02E3, 0x0B00, 0x0B05, 0x0B22];
+
+// LT/RT 共用一个轴字节的产品ID列表（通用HID驱动下的 Xbox 360 无线接收器）
+pub const COMBINED_TRIGGER_PRODUCT_IDS: &[u16] = &[0x0719];
+
 // --- 按钮掩码定义 ---
 pub const BUTTON_LB: u8 = 0x01;
 pub const BUTTON_RB: u8 = 0x02;
+pub const BUTTON_GUIDE: u8 = 0x04; // Xbox 徽标键，常被系统占用
+pub const BUTTON_SHARE: u8 = 0x08; // 分享/截图键（仅 SHARE_BUTTON_PRODUCT_IDS 中的手柄）
 pub const BUTTON_A: u8 = 0x10;
 pub const BUTTON_B: u8 = 0x20;
 pub const BUTTON_X: u8 = 0x40;
@@ -28,10 +51,43 @@ pub const DPAD_DOWN: u8 = 0x02 | 0x80; // 0x82
 pub const DPAD_LEFT: u8 = 0x04 | 0x80; // 0x84
 pub const DPAD_RIGHT: u8 = 0x08 | 0x80; // 0x88
 
+// 摇杆按下按钮编号（偏移量2的高位）- 报告中的位与偏移量3的按钮掩码重叠，另行编号
+pub const BUTTON_L3: u8 = 0x90;
+pub const BUTTON_R3: u8 = 0xA0;
+
+// 菜单/视图按钮编号（偏移量2的高位），编号方式同上
+pub const BUTTON_START: u8 = 0xB0; // 菜单键 (Start/Menu)
+pub const BUTTON_BACK: u8 = 0xC0; // 视图键 (Back/View)
+
+// Elite 手柄背键编号（PADDLES_OFFSET 字节的低4位），编号方式同上
+pub const BUTTON_P1: u8 = 0xD1; // 右上背键
+pub const BUTTON_P2: u8 = 0xD2; // 右下背键
+pub const BUTTON_P3: u8 = 0xD3; // 左上背键
+pub const BUTTON_P4: u8 = 0xD4; // 左下背键
+
+// --- HID报告ID定义 ---
+// 部分手柄在同一接口上通过不同的报告ID复用输入、电量等数据
+const BATTERY_REPORT_ID: u8 = 0x04; // 电量/充电状态报告
+const RUMBLE_REPORT_ID: u8 = 0x03; // 震动输出报告
+
 // --- HID报告偏移量定义 ---
 const BUTTONS_BYTE_2_OFFSET: usize = 2; // 方向键所在字节
 const BUTTONS_BYTE_3_OFFSET: usize = 3;
+const START_MASK: u8 = 0x10; // 偏移量2 bit4：菜单键
+const BACK_MASK: u8 = 0x20; // 偏移量2 bit5：视图键
+const L3_MASK: u8 = 0x40; // 偏移量2 bit6：左摇杆按下
+const R3_MASK: u8 = 0x80; // 偏移量2 bit7：右摇杆按下
+// Elite 手柄背键字节：取陀螺仪数据之后的第一个字节，尚无真实报告核实，
+// 因此只对 ELITE_PRODUCT_IDS 且报告足够长时解析，其他手柄不受影响
+const PADDLES_OFFSET: usize = 17;
+const PADDLE_MASKS: [(u8, u8); 4] = [
+    (0x01, BUTTON_P1), // bit0：P1
+    (0x02, BUTTON_P2), // bit1：P2
+    (0x04, BUTTON_P3), // bit2：P3
+    (0x08, BUTTON_P4), // bit3：P4
+];
 const LT_OFFSET: usize = 4;
+const RT_OFFSET: usize = 5;
 const LX_OFFSET: usize = 6;
 const LY_OFFSET: usize = 8;
 const RX_OFFSET: usize = 10;
@@ -39,6 +95,210 @@ const RY_OFFSET: usize = 12;
 const GYRO_YAW_LOW_OFFSET: usize = 14;
 const GYRO_PITCH_LOW_OFFSET: usize = 15;
 const GYRO_HIGH_NIBBLES_OFFSET: usize = 16;
+const INPUT_REPORT_MIN_LEN: usize = GYRO_HIGH_NIBBLES_OFFSET + 1;
+const BATTERY_STATUS_OFFSET: usize = 1;
+const XBOX_REPORT_LEN: usize = 64;
+
+// 电量不高于该百分比且未充电时视为电量低
+const BATTERY_LOW_LEVEL: u8 = 33;
+
+// --- 震动输出报告 ---
+const RUMBLE_ENABLE_ALL_MOTORS: u8 = 0x0F; // 启用左右扳机马达和左右手柄马达
+const RUMBLE_MAX_STRENGTH: u16 = 100; // 报告中马达强度的上限
+const RUMBLE_FEEDBACK_STRENGTH: u8 = 160;
+const RUMBLE_FEEDBACK_DURATION: Duration = Duration::from_millis(80);
+
+// --- 报告读取 ---
+const READ_TIMEOUT_MS: i32 = 10; // 等待新报告的超时时间
+const MAX_DRAINED_REPORTS: usize = 64; // 每帧最多取出的积压报告数，防止读取循环无法结束
+
+// --- HidApi 初始化重试 ---
+const HIDAPI_INIT_ATTEMPTS: u32 = 3;
+const HIDAPI_INIT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// 扳机数据在 HID 报告中的布局
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriggerLayout {
+    /// LT 和 RT 各占一个字节（0-255）
+    Independent,
+    /// LT 和 RT 共用 LT 所在的一个字节：小于128为LT，大于128为RT，128表示都未按下
+    Combined,
+}
+
+impl TriggerLayout {
+    /// 根据产品ID选择扳机布局
+    pub fn for_product_id(product_id: u16) -> Self {
+        if COMBINED_TRIGGER_PRODUCT_IDS.contains(&product_id) {
+            TriggerLayout::Combined
+        } else {
+            TriggerLayout::Independent
+        }
+    }
+
+    /// 解析 LT 和 RT 的值，统一换算到 0-255
+    fn decode(self, buf: &[u8]) -> (u8, u8) {
+        match self {
+            TriggerLayout::Independent => (buf[LT_OFFSET], buf[RT_OFFSET]),
+            TriggerLayout::Combined => {
+                let axis = buf[LT_OFFSET] as u16;
+                let lt = if axis < 128 { (128 - axis) * 255 / 128 } else { 0 };
+                let rt = if axis > 128 { (axis - 128) * 255 / 127 } else { 0 };
+                (lt as u8, rt as u8)
+            }
+        }
+    }
+}
+
+/// 手柄电量状态
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatteryStatus {
+    /// 电量百分比（按 4 档换算：0/33/66/100）
+    pub level: u8,
+    /// 是否正在充电
+    pub charging: bool,
+}
+
+impl BatteryStatus {
+    /// 从电量报告解析电量状态
+    fn from_buffer(buf: &[u8]) -> Self {
+        const LEVELS: [u8; 4] = [0, 33, 66, 100];
+        let status = buf[BATTERY_STATUS_OFFSET];
+        Self {
+            level: LEVELS[(status & 0x03) as usize],
+            charging: (status & 0x10) != 0,
+        }
+    }
+
+    /// 是否电量低（充电中不算）
+    pub fn is_low(&self) -> bool {
+        !self.charging && self.level <= BATTERY_LOW_LEVEL
+    }
+}
+
+/// HID 报告解析器，负责某一类手柄的报告布局
+pub trait ReportParser {
+    /// 将输入报告解析为手柄状态（调用前需经 `is_input` 确认），报告长度不足时返回 None
+    fn parse(&self, buf: &[u8], threshold: u8) -> Option<ControllerState>;
+    /// 读取报告时使用的缓冲区长度
+    fn report_len(&self) -> usize;
+    /// 报告是否为可解析的输入报告
+    fn is_input(&self, buf: &[u8]) -> bool;
+    /// 报告中携带的电量状态，不含电量信息时返回 None
+    fn battery(&self, buf: &[u8]) -> Option<BatteryStatus>;
+    /// 打开设备后的初始化，例如切换到可解析的报告模式
+    fn init_device(&self, _device: &HidDevice) -> HidResult<()> {
+        Ok(())
+    }
+    /// 设置左右马达震动强度（0-255）的输出报告，不支持震动的手柄返回 None
+    fn rumble_report(&self, _left_motor: u8, _right_motor: u8) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+/// Xbox 手柄的报告解析器
+pub struct XboxReportParser {
+    trigger_layout: TriggerLayout,
+    share_button: bool, // 手柄是否带分享键，不带时忽略报告中的保留位
+    paddles: bool,      // 手柄是否为带背键的 Elite 手柄
+}
+
+impl XboxReportParser {
+    pub fn new(product_id: u16) -> Self {
+        Self {
+            trigger_layout: TriggerLayout::for_product_id(product_id),
+            share_button: SHARE_BUTTON_PRODUCT_IDS.contains(&product_id),
+            paddles: ELITE_PRODUCT_IDS.contains(&product_id),
+        }
+    }
+}
+
+impl ReportParser for XboxReportParser {
+    fn parse(&self, buf: &[u8], threshold: u8) -> Option<ControllerState> {
+        let mut state = ControllerState::from_buffer(buf, threshold, self.trigger_layout)?;
+        if !self.share_button {
+            state.pressed_buttons.remove(&BUTTON_SHARE);
+        }
+        // 背键字节不在最小长度内，报告较短时视为未按下
+        if self.paddles
+            && let Some(&paddles) = buf.get(PADDLES_OFFSET)
+        {
+            for (mask, button) in PADDLE_MASKS {
+                if (paddles & mask) != 0 {
+                    state.pressed_buttons.insert(button);
+                }
+            }
+        }
+        Some(state)
+    }
+
+    fn report_len(&self) -> usize {
+        XBOX_REPORT_LEN
+    }
+
+    /// 长度足够的报告一律按输入报告解析，不看首字节，兼容首字节不是报告ID的设备
+    /// （这类设备的首字节是输入数据，可能恰好等于电量报告ID）
+    fn is_input(&self, buf: &[u8]) -> bool {
+        buf.len() >= INPUT_REPORT_MIN_LEN
+    }
+
+    /// 电量报告比输入报告短，只有首字节为电量报告ID且长度不足输入报告时才按电量报告解析
+    /// 报告格式：报告ID、马达启用掩码、左右扳机马达、左右手柄马达、持续时间（10ms）、延迟、重复次数。
+    /// 手柄马达强度取值 0-100，这里由 0-255 换算；扳机马达保持关闭，持续时间取最大值。
+    fn rumble_report(&self, left_motor: u8, right_motor: u8) -> Option<Vec<u8>> {
+        let strength = |value: u8| (value as u16 * RUMBLE_MAX_STRENGTH / u8::MAX as u16) as u8;
+        Some(vec![
+            RUMBLE_REPORT_ID,
+            RUMBLE_ENABLE_ALL_MOTORS,
+            0,
+            0,
+            strength(left_motor),
+            strength(right_motor),
+            u8::MAX,
+            0,
+            0,
+        ])
+    }
+
+    fn battery(&self, buf: &[u8]) -> Option<BatteryStatus> {
+        (buf.first() == Some(&BATTERY_REPORT_ID)
+            && (BATTERY_STATUS_OFFSET + 1..INPUT_REPORT_MIN_LEN).contains(&buf.len()))
+        .then(|| BatteryStatus::from_buffer(buf))
+    }
+}
+
+/// 根据厂商ID和产品ID选择报告解析器
+pub fn parser_for(vendor_id: u16, product_id: u16) -> Box<dyn ReportParser> {
+    if vendor_id == SONY_VENDOR_ID {
+        Box::new(DualSenseReportParser)
+    } else {
+        Box::new(XboxReportParser::new(product_id))
+    }
+}
+
+/// 一份 HID 报告的解析结果
+pub enum Report {
+    /// 输入报告
+    Input(ControllerState),
+    /// 电量报告
+    Battery(BatteryStatus),
+    /// 无法识别或长度不足的报告
+    Unknown,
+}
+
+impl Report {
+    /// 用手柄对应的解析器识别并解析报告
+    pub fn parse(buf: &[u8], analog_trigger_threshold: u8, parser: &dyn ReportParser) -> Self {
+        if parser.is_input(buf) {
+            parser
+                .parse(buf, analog_trigger_threshold)
+                .map_or(Report::Unknown, Report::Input)
+        } else if let Some(status) = parser.battery(buf) {
+            Report::Battery(status)
+        } else {
+            Report::Unknown
+        }
+    }
+}
 
 /// 封装了手柄所有输入状态的结构体
 #[derive(Clone, Debug)]
@@ -48,9 +308,14 @@ pub struct ControllerState {
     pub rx: i16,
     pub ry: i16,
     pub lt: u8,
+    pub rt: u8,
     pub gyro_yaw: i16,
     pub gyro_pitch: i16,
     pub pressed_buttons: HashSet<u8>,
+    /// 在同一帧被跳过的较早报告中按下、本报告中已松开的按键（见 `coalesce`）
+    pub tapped_buttons: HashSet<u8>,
+    /// 报告解析时刻（单调时钟），用于计算相邻报告的时间间隔
+    pub timestamp: Instant,
 }
 
 impl ControllerState {
@@ -60,9 +325,59 @@ impl ControllerState {
         self.rx.saturating_abs().min(i16::MAX) * self.rx.signum()
     }
 
-    /// 从 HID 缓冲区解析手柄状态
-    pub fn from_buffer(buf: &[u8], analog_trigger_threshold: u8) -> Self {
-        let lt = buf[LT_OFFSET];
+    /// 合并多个手柄的状态，用于多个手柄共同控制同一组输入
+    ///
+    /// 每个摇杆取偏移最大的手柄，扳机取最大值，陀螺仪叠加，按键取并集。
+    pub fn merge<'a>(states: impl IntoIterator<Item = &'a ControllerState>) -> Option<Self> {
+        let magnitude = |x: i16, y: i16| (x as f64).hypot(y as f64);
+        let mut states = states.into_iter();
+        let mut merged = states.next()?.clone();
+        for state in states {
+            if magnitude(state.lx, state.ly) > magnitude(merged.lx, merged.ly) {
+                (merged.lx, merged.ly) = (state.lx, state.ly);
+            }
+            if magnitude(state.rx, state.ry) > magnitude(merged.rx, merged.ry) {
+                (merged.rx, merged.ry) = (state.rx, state.ry);
+            }
+            merged.lt = merged.lt.max(state.lt);
+            merged.rt = merged.rt.max(state.rt);
+            merged.gyro_yaw = merged.gyro_yaw.saturating_add(state.gyro_yaw);
+            merged.gyro_pitch = merged.gyro_pitch.saturating_add(state.gyro_pitch);
+            merged
+                .pressed_buttons
+                .extend(state.pressed_buttons.iter().copied());
+            merged
+                .tapped_buttons
+                .extend(state.tapped_buttons.iter().copied());
+            merged.timestamp = merged.timestamp.max(state.timestamp);
+        }
+        Some(merged)
+    }
+
+    /// 合并同一帧内先后收到的两份状态，只保留较新的一份
+    ///
+    /// 较早状态中按下、较新状态中已松开的按键记入 `tapped_buttons`，
+    /// 使一帧之内按下又松开的轻按不会因跳过中间报告而丢失。
+    pub fn coalesce(older: ControllerState, mut newer: ControllerState) -> ControllerState {
+        newer.tapped_buttons.extend(
+            older
+                .pressed_buttons
+                .iter()
+                .chain(&older.tapped_buttons)
+                .filter(|button| !newer.pressed_buttons.contains(button)),
+        );
+        newer
+    }
+
+    /// 从 HID 缓冲区解析手柄状态，缓冲区短于输入报告所需长度时返回 None
+    pub fn from_buffer(
+        buf: &[u8],
+        analog_trigger_threshold: u8,
+        layout: TriggerLayout,
+    ) -> Option<Self> {
+        // 下面读取的偏移量都小于 INPUT_REPORT_MIN_LEN，截取后不会越界
+        let buf = buf.get(..INPUT_REPORT_MIN_LEN)?;
+        let (lt, rt) = layout.decode(buf);
 
         // 解析陀螺仪数据（仅当LT按下时）
         let (raw_gyro_yaw, raw_gyro_pitch) = if lt > analog_trigger_threshold {
@@ -77,7 +392,7 @@ impl ControllerState {
         };
 
         // 解析按钮状态
-        let button_byte_2 = buf[BUTTONS_BYTE_2_OFFSET]; // 方向键
+        let button_byte_2 = buf[BUTTONS_BYTE_2_OFFSET]; // 方向键、菜单/视图键和摇杆按下
         let button_byte_3 = buf[BUTTONS_BYTE_3_OFFSET]; // 面部按钮和肩部按钮
         let mut pressed_buttons = HashSet::new();
 
@@ -94,6 +409,18 @@ impl ControllerState {
         if (button_byte_2 & 0x08) != 0 {
             pressed_buttons.insert(DPAD_RIGHT);
         }
+        if (button_byte_2 & START_MASK) != 0 {
+            pressed_buttons.insert(BUTTON_START);
+        }
+        if (button_byte_2 & BACK_MASK) != 0 {
+            pressed_buttons.insert(BUTTON_BACK);
+        }
+        if (button_byte_2 & L3_MASK) != 0 {
+            pressed_buttons.insert(BUTTON_L3);
+        }
+        if (button_byte_2 & R3_MASK) != 0 {
+            pressed_buttons.insert(BUTTON_R3);
+        }
 
         if (button_byte_3 & BUTTON_A) != 0 {
             pressed_buttons.insert(BUTTON_A);
@@ -113,13 +440,20 @@ impl ControllerState {
         if (button_byte_3 & BUTTON_RB) != 0 {
             pressed_buttons.insert(BUTTON_RB);
         }
+        if (button_byte_3 & BUTTON_SHARE) != 0 {
+            pressed_buttons.insert(BUTTON_SHARE);
+        }
+        if (button_byte_3 & BUTTON_GUIDE) != 0 {
+            pressed_buttons.insert(BUTTON_GUIDE);
+        }
 
-        Self {
+        Some(Self {
             lx: i16::from_le_bytes([buf[LX_OFFSET], buf[LX_OFFSET + 1]]),
             ly: i16::from_le_bytes([buf[LY_OFFSET], buf[LY_OFFSET + 1]]).saturating_neg(),
             rx: i16::from_le_bytes([buf[RX_OFFSET], buf[RX_OFFSET + 1]]),
             ry: i16::from_le_bytes([buf[RY_OFFSET], buf[RY_OFFSET + 1]]).saturating_neg(),
             lt,
+            rt,
             gyro_yaw: if raw_gyro_yaw >= 2048 {
                 (raw_gyro_yaw as i16).saturating_sub(4096)
             } else {
@@ -131,47 +465,226 @@ impl ControllerState {
                 raw_gyro_pitch as i16
             },
             pressed_buttons,
+            tapped_buttons: HashSet::new(),
+            timestamp: Instant::now(),
+        })
+    }
+}
+
+/// 最多尝试 `attempts` 次执行 `operation`，第 n 次失败后等待 n 倍 `backoff` 再重试
+fn retry_with_backoff<T, E>(
+    attempts: u32,
+    backoff: Duration,
+    mut operation: impl FnMut() -> Result<T, E>,
+) -> Result<T, E> {
+    let mut attempt = 1;
+    loop {
+        match operation() {
+            Err(_) if attempt < attempts => {
+                thread::sleep(backoff * attempt);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// 摇杆静止时的平均偏移，用于检测摇杆漂移
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StickOffsets {
+    pub left: (i16, i16),
+    pub right: (i16, i16),
+}
+
+impl StickOffsets {
+    /// 计算一组采样中左右摇杆各轴的平均值，没有采样时返回 None
+    pub fn average(samples: &[ControllerState]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let count = samples.len() as i64;
+        let mean = |axis: fn(&ControllerState) -> i16| {
+            (samples.iter().map(|s| axis(s) as i64).sum::<i64>() / count) as i16
+        };
+        Some(Self {
+            left: (mean(|s| s.lx), mean(|s| s.ly)),
+            right: (mean(|s| s.rx), mean(|s| s.ry)),
+        })
+    }
+
+    /// 计算一组采样中左右摇杆各轴偏离中心的最大幅度，没有采样时返回 None
+    pub fn peak(samples: &[ControllerState]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let peak = |axis: fn(&ControllerState) -> i16| {
+            samples
+                .iter()
+                .map(|s| axis(s).saturating_abs())
+                .max()
+                .unwrap_or(0)
+        };
+        Some(Self {
+            left: (peak(|s| s.lx), peak(|s| s.ly)),
+            right: (peak(|s| s.rx), peak(|s| s.ry)),
+        })
+    }
+}
+
+/// 打开手柄的方式，连接管理器通过它连接设备（测试时可替换为模拟实现）
+pub trait DeviceOpener {
+    /// 连接后得到的设备
+    type Device;
+
+    /// 连接所有匹配的手柄（最多 `limit` 个），没有找到时返回 `DeviceNotFound`
+    fn open_all(&mut self, limit: usize) -> ControllerResult<Vec<Self::Device>>;
+}
+
+/// 通过 HidApi 打开手柄
+///
+/// HidApi 只在首次连接时初始化（失败时短暂退避后重试），之后每次连接只刷新设备列表，
+/// 重连轮询不会反复创建 HidApi。
+pub struct HidOpener {
+    api: Option<HidApi>,
+    report_size: usize, // 新连接手柄的读取缓冲区大小
+}
+
+impl HidOpener {
+    pub fn new(report_size: usize) -> Self {
+        Self {
+            api: None,
+            report_size,
         }
     }
 }
 
+impl DeviceOpener for HidOpener {
+    type Device = HidController;
+
+    fn open_all(&mut self, limit: usize) -> ControllerResult<Vec<HidController>> {
+        let api = match &mut self.api {
+            Some(api) => {
+                api.refresh_devices()?;
+                api
+            }
+            None => self.api.insert(HidController::init_api()?),
+        };
+
+        let mut controllers = HidController::open_all(api, limit)?;
+        for controller in &mut controllers {
+            controller.set_report_size(self.report_size);
+        }
+        Ok(controllers)
+    }
+}
+
 /// HID设备管理器，负责设备的查找、连接和数据读取
 pub struct HidController {
     device: HidDevice,
+    vendor_id: u16,
+    product_id: u16,
+    parser: Box<dyn ReportParser>,
+    buf: RefCell<Vec<u8>>,                 // 复用的读取缓冲区，长度即报告大小
+    battery: Cell<Option<BatteryStatus>>,  // 最近一次收到的电量状态
+    rumble_failed: Cell<bool>,             // 震动报告是否写入失败过（不支持震动的手柄只提示一次）
+    rumble_stop_at: Cell<Option<Instant>>, // 震动反馈的停止时刻
 }
 
 impl HidController {
     /// 查找并连接到目标HID设备
     pub fn new() -> ControllerResult<Self> {
-        let api = HidApi::new()
-            .map_err(|e| ControllerError::HidDevice(format!("HidApi 初始化失败: {}", e)))?;
+        let api = Self::init_api()?;
 
-        let device = Self::find_and_open_device(&api).ok_or(ControllerError::DeviceNotFound)?;
+        let (device, vendor_id, product_id) = Self::open_matching_devices(&api, 1)
+            .into_iter()
+            .next()
+            .ok_or(ControllerError::DeviceNotFound)?;
 
-        Ok(Self { device })
+        Ok(Self::from_device(device, vendor_id, product_id))
     }
 
-    /// 查找并打开目标 HID 设备
-    fn find_and_open_device(api: &HidApi) -> Option<HidDevice> {
-        // 搜索所有支持的产品ID
-        for &product_id in SUPPORTED_PRODUCT_IDS {
-            if let Some(dev_info) = api
+    /// 用已初始化的 HidApi 连接所有匹配的HID设备（最多 `limit` 个），按找到的顺序返回
+    fn open_all(api: &HidApi, limit: usize) -> ControllerResult<Vec<Self>> {
+        let controllers: Vec<Self> = Self::open_matching_devices(api, limit)
+            .into_iter()
+            .map(|(device, vendor_id, product_id)| Self::from_device(device, vendor_id, product_id))
+            .collect();
+        if controllers.is_empty() {
+            return Err(ControllerError::DeviceNotFound);
+        }
+        Ok(controllers)
+    }
+
+    fn from_device(device: HidDevice, vendor_id: u16, product_id: u16) -> Self {
+        let parser = parser_for(vendor_id, product_id);
+        if let Err(e) = parser.init_device(&device) {
+            warn!("初始化设备 (PID: {:#06X}) 失败: {}", product_id, e);
+        }
+        Self {
+            device,
+            vendor_id,
+            product_id,
+            buf: RefCell::new(vec![0u8; parser.report_len()]),
+            parser,
+            battery: Cell::new(None),
+            rumble_failed: Cell::new(false),
+            rumble_stop_at: Cell::new(None),
+        }
+    }
+
+    /// 初始化 HidApi，失败时短暂退避后重试
+    ///
+    /// macOS 枚举设备期间 HidApi 初始化偶尔会暂时失败，重试几次通常即可恢复。
+    fn init_api() -> ControllerResult<HidApi> {
+        retry_with_backoff(HIDAPI_INIT_ATTEMPTS, HIDAPI_INIT_BACKOFF, HidApi::new)
+            .map_err(|e| ControllerError::HidDevice(format!("HidApi 初始化失败: {}", e)))
+    }
+
+    /// 查找并打开目标 HID 设备（最多 `limit` 个），返回设备及其厂商ID、产品ID
+    fn open_matching_devices(api: &HidApi, limit: usize) -> Vec<(HidDevice, u16, u16)> {
+        let mut opened = Vec::new();
+        // 搜索所有支持的设备（先 Xbox 后 DualSense）
+        let candidates = SUPPORTED_PRODUCT_IDS
+            .iter()
+            .map(|&pid| (TARGET_VENDOR_ID, pid))
+            .chain(
+                DUALSENSE_PRODUCT_IDS
+                    .iter()
+                    .map(|&pid| (SONY_VENDOR_ID, pid)),
+            );
+        for (vendor_id, product_id) in candidates {
+            let matching = api
                 .device_list()
-                .find(|d| d.vendor_id() == TARGET_VENDOR_ID && d.product_id() == product_id)
-            {
+                .filter(|d| d.vendor_id() == vendor_id && d.product_id() == product_id);
+            for dev_info in matching {
+                if opened.len() >= limit {
+                    return opened;
+                }
                 let device_name = dev_info.product_string().unwrap_or("未知设备");
-                println!("找到设备: {} (PID: {:#06X})", device_name, product_id);
+                info!("找到设备: {} (PID: {:#06X})", device_name, product_id);
 
                 match dev_info.open_device(api) {
-                    Ok(device) => return Some(device),
-                    Err(e) => {
-                        eprintln!("无法打开设备 {}: {}", device_name, e);
-                        continue;
-                    }
+                    Ok(device) => opened.push((device, vendor_id, product_id)),
+                    Err(e) => warn!("无法打开设备 {}: {}", device_name, e),
                 }
             }
         }
-        None
+        opened
+    }
+
+    /// 设置读取报告的缓冲区大小，不会小于报告格式所需的长度
+    ///
+    /// 缓冲区比实际报告长时只解析实际读到的字节，比报告短时报告会被截断，
+    /// 因此只允许调大，用于报告比常见格式更长的手柄。
+    pub fn set_report_size(&mut self, size: usize) {
+        let required = self.parser.report_len();
+        if size < required {
+            warn!(
+                "hid_report_size ({} 字节) 小于该手柄报告所需的 {} 字节，已按 {} 字节读取",
+                size, required, required
+            );
+        }
+        self.buf.get_mut().resize(size.max(required), 0);
     }
 
     /// 读取HID设备数据并解析为控制器状态
@@ -179,39 +692,439 @@ impl HidController {
         &self,
         analog_trigger_threshold: u8,
     ) -> ControllerResult<Option<ControllerState>> {
-        let mut buf = [0u8; 64];
+        let mut buf = self.buf.borrow_mut();
+
+        match self.read_into(&mut buf, READ_TIMEOUT_MS)? {
+            0 => Ok(None), // 没有数据
+            len => Ok(self.handle_report(&buf[..len], analog_trigger_threshold)),
+        }
+    }
+
+    /// 取出所有积压的报告，返回其中最新的输入状态
+    ///
+    /// 主循环限制频率时，两帧之间手柄可能已发送多份报告。逐帧读取一份会让积压越来越多，
+    /// 这里先等待第一份报告，再以零超时读到没有数据为止，只对最新的状态做出响应；
+    /// 中间报告里按下又松开的按键记在返回状态的 `tapped_buttons` 中。
+    pub fn read_latest_state(
+        &self,
+        analog_trigger_threshold: u8,
+    ) -> ControllerResult<Option<ControllerState>> {
+        let mut buf = self.buf.borrow_mut();
+        let mut latest = None;
+        let mut timeout = READ_TIMEOUT_MS;
+
+        for _ in 0..MAX_DRAINED_REPORTS {
+            let len = self.read_into(&mut buf, timeout)?;
+            if len == 0 {
+                break;
+            }
+            if let Some(state) = self.handle_report(&buf[..len], analog_trigger_threshold) {
+                latest = Some(match latest {
+                    Some(older) => ControllerState::coalesce(older, state),
+                    None => state,
+                });
+            }
+            timeout = 0;
+        }
+        Ok(latest)
+    }
+
+    /// 解析一份报告并缓存其中的电量信息，返回输入状态（不是输入报告时返回 None）
+    fn handle_report(
+        &self,
+        report: &[u8],
+        analog_trigger_threshold: u8,
+    ) -> Option<ControllerState> {
+        match Report::parse(report, analog_trigger_threshold, self.parser.as_ref()) {
+            Report::Input(state) => {
+                // 部分手柄的输入报告中也携带电量信息
+                if let Some(status) = self.parser.battery(report) {
+                    self.update_battery(status);
+                }
+                Some(state)
+            }
+            Report::Battery(status) => {
+                self.update_battery(status);
+                None
+            }
+            Report::Unknown => None,
+        }
+    }
 
-        match self.device.read_timeout(&mut buf, 10) {
-            Ok(0) => Ok(None), // 没有数据
-            Ok(_) => {
-                let state = ControllerState::from_buffer(&buf, analog_trigger_threshold);
-                Ok(Some(state))
+    /// 缓存电量状态，电量或充电状态变化时记录日志
+    fn update_battery(&self, status: BatteryStatus) {
+        if self.battery.replace(Some(status)) != Some(status) {
+            let charging = if status.charging {
+                "（充电中）"
+            } else {
+                ""
+            };
+            info!("手柄电量: {}%{}", status.level, charging);
+        }
+    }
+
+    /// 最近一次收到的电量状态
+    ///
+    /// 电量报告由无线手柄随输入报告一起周期性发送，`read_state` 收到后缓存在这里。
+    /// 有线手柄不发送电量报告，始终返回 `Ok(None)`。
+    pub fn read_battery_level(&self) -> ControllerResult<Option<BatteryStatus>> {
+        Ok(self.battery.get())
+    }
+
+    /// 读取一份未解析的原始报告（用于录制），没有数据时返回 None
+    pub fn read_raw_report(&self) -> ControllerResult<Option<Vec<u8>>> {
+        let mut buf = self.buf.borrow_mut();
+        let len = self.read_into(&mut buf, READ_TIMEOUT_MS)?;
+        Ok((len > 0).then(|| buf[..len].to_vec()))
+    }
+
+    /// 以给定超时（毫秒）读取一份报告到缓冲区，返回读取的字节数（超时为 0 时不等待）
+    fn read_into(&self, buf: &mut [u8], timeout_ms: i32) -> ControllerResult<usize> {
+        self.device
+            .read_timeout(buf, timeout_ms)
+            .map_err(|e| ControllerError::HidDevice(format!("读取设备时出错: {}", e)))
+    }
+
+    /// 设置左右马达的震动强度（0-255），两者都为 0 时停止震动
+    ///
+    /// 输出报告由手柄对应的解析器编码，不支持震动的手柄不发送任何报告。
+    pub fn set_rumble(&self, left_motor: u8, right_motor: u8) -> ControllerResult<()> {
+        let Some(report) = self.parser.rumble_report(left_motor, right_motor) else {
+            return Ok(());
+        };
+        self.device
+            .write(&report)
+            .map(|_| ())
+            .map_err(|e| ControllerError::HidDevice(format!("写入震动报告失败: {}", e)))
+    }
+
+    /// 开始一次短促的震动反馈，由 `update_rumble` 到时停止；失败时只打印一次错误
+    pub fn rumble_feedback(&self) {
+        if self.rumble_failed.get() {
+            return;
+        }
+        match self.set_rumble(RUMBLE_FEEDBACK_STRENGTH, RUMBLE_FEEDBACK_STRENGTH) {
+            Ok(()) => self
+                .rumble_stop_at
+                .set(Some(Instant::now() + RUMBLE_FEEDBACK_DURATION)),
+            Err(e) => {
+                warn!("手柄震动失败，本次连接不再尝试震动: {}", e);
+                self.rumble_failed.set(true);
             }
-            Err(e) => Err(ControllerError::HidDevice(format!("读取设备时出错: {}", e))),
         }
     }
 
-    /// 尝试重新连接设备（用于重连逻辑）
-    pub fn try_reconnect() -> ControllerResult<Self> {
-        let api = HidApi::new()
-            .map_err(|e| ControllerError::HidDevice(format!("HidApi 初始化失败: {}", e)))?;
+    /// 震动反馈到时后停止震动，需在主循环中每帧调用
+    pub fn update_rumble(&self) {
+        if let Some(at) = self.rumble_stop_at.get()
+            && Instant::now() >= at
+        {
+            self.rumble_stop_at.set(None);
+            if let Err(e) = self.set_rumble(0, 0) {
+                warn!("停止手柄震动失败: {}", e);
+            }
+        }
+    }
+
+    /// 已连接手柄的厂商ID
+    pub fn vendor_id(&self) -> u16 {
+        self.vendor_id
+    }
 
-        let device = Self::find_and_open_device(&api).ok_or(ControllerError::DeviceNotFound)?;
+    /// 已连接手柄的产品ID
+    pub fn product_id(&self) -> u16 {
+        self.product_id
+    }
 
-        Ok(Self { device })
+    /// 在给定时长内采样输入报告，返回摇杆静止偏移的平均值（期间未收到输入报告时返回 None）
+    pub fn sample_resting_sticks(
+        &self,
+        analog_trigger_threshold: u8,
+        duration: Duration,
+    ) -> ControllerResult<Option<StickOffsets>> {
+        let samples = self.sample_states(analog_trigger_threshold, duration)?;
+        Ok(StickOffsets::average(&samples))
+    }
+
+    /// 在给定时长内持续读取，返回期间收到的所有输入状态
+    pub fn sample_states(
+        &self,
+        analog_trigger_threshold: u8,
+        duration: Duration,
+    ) -> ControllerResult<Vec<ControllerState>> {
+        let started_at = Instant::now();
+        let mut samples = Vec::new();
+        while started_at.elapsed() < duration {
+            if let Some(state) = self.read_state(analog_trigger_threshold)? {
+                samples.push(state);
+            }
+        }
+        Ok(samples)
     }
 
     /// 获取设备信息字符串
     pub fn get_device_info() -> String {
-        let pids: Vec<String> = SUPPORTED_PRODUCT_IDS
-            .iter()
-            .map(|&pid| format!("{:#06X}", pid))
-            .collect();
+        let pids = |ids: &[u16]| {
+            ids.iter()
+                .map(|&pid| format!("{:#06X}", pid))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
 
         format!(
-            "Xbox手柄设备 (VID: {:#06X}, 支持的PID: {})",
+            "Xbox手柄设备 (VID: {:#06X}, 支持的PID: {}) 或 DualSense 手柄 (VID: {:#06X}, 支持的PID: {})",
             TARGET_VENDOR_ID,
-            pids.join(", ")
+            pids(SUPPORTED_PRODUCT_IDS),
+            SONY_VENDOR_ID,
+            pids(DUALSENSE_PRODUCT_IDS)
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 构造一份 Xbox 输入报告：按钮字节2/3 及摇杆值，其余字节为 0
+    fn xbox_report(byte_2: u8, byte_3: u8, lx: i16, ry: i16) -> Vec<u8> {
+        let mut buf = vec![0u8; XBOX_REPORT_LEN];
+        buf[BUTTONS_BYTE_2_OFFSET] = byte_2;
+        buf[BUTTONS_BYTE_3_OFFSET] = byte_3;
+        buf[LX_OFFSET..LX_OFFSET + 2].copy_from_slice(&lx.to_le_bytes());
+        buf[RY_OFFSET..RY_OFFSET + 2].copy_from_slice(&ry.to_le_bytes());
+        buf
+    }
+
+    fn parse(product_id: u16, buf: &[u8]) -> ControllerState {
+        match Report::parse(buf, 20, parser_for(TARGET_VENDOR_ID, product_id).as_ref()) {
+            Report::Input(state) => state,
+            _ => panic!("not an input report"),
+        }
+    }
+
+    #[test]
+    fn share_bit_is_decoded_only_for_share_capable_controllers() {
+        let buf = xbox_report(0, BUTTON_SHARE | BUTTON_A, 0, 0);
+
+        let series = parse(0x0B12, &buf);
+        assert!(series.pressed_buttons.contains(&BUTTON_SHARE));
+        assert!(series.pressed_buttons.contains(&BUTTON_A));
+
+        let wireless = parse(0x02E0, &buf);
+        assert!(!wireless.pressed_buttons.contains(&BUTTON_SHARE));
+        assert!(wireless.pressed_buttons.contains(&BUTTON_A));
+    }
+
+    #[test]
+    fn paddles_are_decoded_only_for_elite_controllers() {
+        let mut buf = xbox_report(0, BUTTON_A, 0, 0);
+        buf[PADDLES_OFFSET] = 0x01 | 0x08;
+
+        let elite = parse(0x0B00, &buf);
+        assert!(elite.pressed_buttons.contains(&BUTTON_P1));
+        assert!(elite.pressed_buttons.contains(&BUTTON_P4));
+        assert!(!elite.pressed_buttons.contains(&BUTTON_P2));
+
+        let series = parse(0x0B12, &buf);
+        assert!(!series.pressed_buttons.contains(&BUTTON_P1));
+        assert!(series.pressed_buttons.contains(&BUTTON_A));
+
+        // 报告不含背键字节时视为未按下
+        let short = parse(0x0B00, &buf[..PADDLES_OFFSET]);
+        assert!(!short.pressed_buttons.contains(&BUTTON_P1));
+        assert!(short.pressed_buttons.contains(&BUTTON_A));
+    }
+
+    #[test]
+    fn buttons_and_sticks_are_decoded() {
+        let buf = xbox_report(
+            0x01 | START_MASK | R3_MASK,
+            BUTTON_LB | BUTTON_Y,
+            1234,
+            4000,
+        );
+        let state = parse(0x02E0, &buf);
+
+        let expected: HashSet<u8> = [DPAD_UP, BUTTON_START, BUTTON_R3, BUTTON_LB, BUTTON_Y]
+            .into_iter()
+            .collect();
+        assert_eq!(state.pressed_buttons, expected);
+        assert_eq!(state.lx, 1234);
+        // Y 轴取反：向上推为负
+        assert_eq!(state.ry, -4000);
+    }
+
+    #[test]
+    fn short_report_is_not_an_input_report() {
+        let parser = parser_for(TARGET_VENDOR_ID, 0x02E0);
+        assert!(matches!(
+            Report::parse(&[0x01; INPUT_REPORT_MIN_LEN - 1], 20, parser.as_ref()),
+            Report::Unknown
+        ));
+    }
+
+    #[test]
+    fn combined_trigger_axis_splits_into_lt_and_rt() {
+        let decode = |axis: u8| {
+            let mut buf = xbox_report(0, 0, 0, 0);
+            buf[LT_OFFSET] = axis;
+            TriggerLayout::Combined.decode(&buf)
+        };
+        assert_eq!(decode(128), (0, 0));
+        assert_eq!(decode(0), (255, 0));
+        assert_eq!(decode(255), (0, 255));
+        assert_eq!(decode(64), (127, 0));
+    }
+
+    #[test]
+    fn trigger_layout_follows_product_id() {
+        let mut buf = xbox_report(0, 0, 0, 0);
+        buf[LT_OFFSET] = 200;
+        buf[RT_OFFSET] = 40;
+
+        let state = parse(0x02E0, &buf);
+        assert_eq!((state.lt, state.rt), (200, 40));
+
+        // 无线接收器只看 LT 字节，RT 字节被忽略
+        let receiver = parse(0x0719, &buf);
+        assert_eq!(receiver.lt, 0);
+        assert_eq!(receiver.rt, 144); // (200 - 128) * 255 / 127
+    }
+
+    #[test]
+    fn parsed_states_are_timestamped_in_order() {
+        let buf = xbox_report(0, 0, 0, 0);
+        let first = parse(0x02E0, &buf);
+        std::thread::sleep(Duration::from_millis(2));
+        let second = parse(0x02E0, &buf);
+
+        assert!(second.timestamp >= first.timestamp + Duration::from_millis(2));
+    }
+
+    #[test]
+    fn coalesce_keeps_newest_state_and_records_taps() {
+        let older = parse(0x02E0, &xbox_report(0, BUTTON_A | BUTTON_B, 100, 0));
+        let middle = parse(0x02E0, &xbox_report(0, BUTTON_B | BUTTON_X, 200, 0));
+        let newest = parse(0x02E0, &xbox_report(0, BUTTON_B, 300, 0));
+
+        let state = ControllerState::coalesce(ControllerState::coalesce(older, middle), newest);
+        assert_eq!(state.lx, 300);
+        assert_eq!(state.pressed_buttons, HashSet::from([BUTTON_B]));
+        assert_eq!(state.tapped_buttons, HashSet::from([BUTTON_A, BUTTON_X]));
+    }
+
+    #[test]
+    fn battery_report_is_dispatched_by_report_id() {
+        let parser = parser_for(TARGET_VENDOR_ID, 0x02E0);
+        // 电量 2 档（66%），充电中
+        let report = [BATTERY_REPORT_ID, 0x12];
+        match Report::parse(&report, 20, parser.as_ref()) {
+            Report::Battery(status) => {
+                assert_eq!(
+                    status,
+                    BatteryStatus {
+                        level: 66,
+                        charging: true
+                    }
+                );
+                assert!(!status.is_low());
+            }
+            _ => panic!("not a battery report"),
+        }
+    }
+
+    #[test]
+    fn low_battery_is_reported_only_when_not_charging() {
+        let low = BatteryStatus::from_buffer(&[BATTERY_REPORT_ID, 0x01]);
+        assert_eq!(low.level, 33);
+        assert!(low.is_low());
+        assert!(!BatteryStatus::from_buffer(&[BATTERY_REPORT_ID, 0x11]).is_low());
+    }
+
+    #[test]
+    fn full_length_report_starting_with_battery_id_is_input() {
+        // 不带报告ID的设备首字节为输入数据，可能恰好等于电量报告ID
+        let mut buf = xbox_report(0, BUTTON_A, 0, 0);
+        buf[0] = BATTERY_REPORT_ID;
+        assert!(parse(0x02E0, &buf).pressed_buttons.contains(&BUTTON_A));
+    }
+
+    #[test]
+    fn xbox_rumble_report_scales_motor_strength() {
+        let parser = parser_for(TARGET_VENDOR_ID, 0x02E0);
+        assert_eq!(
+            parser.rumble_report(u8::MAX, 0),
+            Some(vec![RUMBLE_REPORT_ID, 0x0F, 0, 0, 100, 0, u8::MAX, 0, 0])
+        );
+    }
+
+    #[test]
+    fn truncated_battery_report_is_unknown() {
+        let parser = parser_for(TARGET_VENDOR_ID, 0x02E0);
+        assert!(matches!(
+            Report::parse(&[BATTERY_REPORT_ID], 20, parser.as_ref()),
+            Report::Unknown
+        ));
+    }
+
+    #[test]
+    fn stick_clicks_are_decoded_with_stick_position() {
+        let state = parse(0x02E0, &xbox_report(L3_MASK, 0, 20000, 0));
+        assert_eq!(state.pressed_buttons, HashSet::from([BUTTON_L3]));
+        assert_eq!(state.lx, 20000);
+    }
+
+    fn stick_sample(lx: i16, ly: i16, rx: i16, ry: i16) -> ControllerState {
+        ControllerState {
+            lx,
+            ly,
+            rx,
+            ry,
+            ..parse(0x02E0, &xbox_report(0, 0, 0, 0))
+        }
+    }
+
+    #[test]
+    fn resting_offsets_average_each_axis() {
+        assert_eq!(StickOffsets::average(&[]), None);
+
+        let samples = [
+            stick_sample(3000, -100, 0, 50),
+            stick_sample(3200, 100, -10, 50),
+            stick_sample(3400, -301, 10, -40),
+        ];
+        assert_eq!(
+            StickOffsets::average(&samples),
+            Some(StickOffsets {
+                left: (3200, -100),
+                right: (0, 20),
+            })
+        );
+    }
+
+    #[test]
+    fn resting_average_does_not_overflow_at_full_deflection() {
+        let samples = vec![stick_sample(i16::MAX, i16::MIN, i16::MAX, i16::MIN); 64];
+        let offsets = StickOffsets::average(&samples).unwrap();
+        assert_eq!(offsets.left, (i16::MAX, i16::MIN));
+        assert_eq!(offsets.right, (i16::MAX, i16::MIN));
+    }
+
+    #[test]
+    fn retry_with_backoff_retries_transient_failures() {
+        let mut calls = 0;
+        let result: Result<u32, &str> = retry_with_backoff(3, Duration::ZERO, || {
+            calls += 1;
+            if calls < 3 { Err("busy") } else { Ok(calls) }
+        });
+        assert_eq!(result, Ok(3));
+
+        let mut calls = 0;
+        let result: Result<u32, &str> = retry_with_backoff(3, Duration::ZERO, || {
+            calls += 1;
+            Err("busy")
+        });
+        assert_eq!(result, Err("busy"));
+        assert_eq!(calls, 3);
+    }
+}
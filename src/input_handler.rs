@@ -1,462 +1,3983 @@
-use crate::config::{ButtonAction, ControllerConfig};
+use crate::action_log::ActionLog;
+use crate::config::{
+    AccelerationCurve, ButtonAction, ControllerConfig, CursorSource, DeadzoneShape,
+    DualStickPolicy, GyroTarget, MacroStep, MouseButton, NavMethod, Platform, RepeatConfig,
+    RightStickHorizontalMode, ScreenAnchor, ScrollSource, Shortcut, SwipeDirection,
+    TriggerStageConfig,
+};
 use crate::error::{ControllerError, ControllerResult};
 use crate::hid::{
-    BUTTON_A, BUTTON_B, BUTTON_LB, BUTTON_RB, BUTTON_X, BUTTON_Y, ControllerState, DPAD_DOWN,
-    DPAD_LEFT, DPAD_RIGHT, DPAD_UP,
+    BUTTON_A, BUTTON_B, BUTTON_BACK, BUTTON_GUIDE, BUTTON_L3, BUTTON_LB, BUTTON_P1, BUTTON_P2,
+    BUTTON_P3, BUTTON_P4, BUTTON_R3, BUTTON_RB, BUTTON_SHARE, BUTTON_START, BUTTON_X, BUTTON_Y,
+    ControllerState, DPAD_DOWN, DPAD_LEFT, DPAD_RIGHT, DPAD_UP,
+};
+use crate::output::{
+    OutputSink, TrackedOutput, create_output_sink, navigation_shortcut, primary_modifier,
 };
 use enigo::{
     Button as EnigoButton, Coordinate,
     Direction::{Click, Press, Release},
-    Enigo, Key, Keyboard, Mouse,
+    Key,
 };
-use std::collections::HashSet;
+use log::debug;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::process::Command;
 use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 连按快捷键（如连按两次 Fn）时相邻两次按下的间隔
+const REPEATED_PRESS_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 光标灵敏度对应的基准时长：摇杆或陀螺仪的位移按实际经过时间相对该时长换算
+const MOVEMENT_REFERENCE_INTERVAL: Duration = Duration::from_millis(10);
+
+/// 单帧换算位移时经过时间的上限，避免卡顿或长时间没有报告后光标突然跳远
+const MAX_MOVEMENT_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 输入处理器与步调器线程共享的滚动状态
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ScrollState {
+    /// 滚动力度（符号决定滚动方向）
+    pub power: f64,
+    /// 水平滚动力度（正值向右）
+    pub horizontal: f64,
+    /// 是否在滚动时按住 Cmd（缩放滚动）
+    pub zoom: bool,
+}
+
+impl ScrollState {
+    /// 将一个输入组的滚动分量从 `old` 换成 `new`，其他组的分量保持不变
+    fn replace_contribution(&mut self, old: ScrollState, new: ScrollState) {
+        // 浮点加减留下的残差归零，所有组都停止时滚动力度恰好为0
+        let settle = |value: f64| if value.abs() < 1e-9 { 0.0 } else { value };
+        self.power = settle(self.power - old.power + new.power);
+        self.horizontal = settle(self.horizontal - old.horizontal + new.horizontal);
+        if new.zoom != old.zoom {
+            self.zoom = new.zoom;
+        }
+    }
+}
+
+/// 两段式扳机所处的阶段
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum TriggerStage {
+    #[default]
+    Released,
+    Light,
+    Full,
+}
+
+impl TriggerStage {
+    /// 根据扳机值计算下一阶段，回落时需低于阈值减去回差
+    fn next(self, value: u8, config: &TriggerStageConfig) -> Self {
+        let light_release = config.light_threshold.saturating_sub(config.hysteresis);
+        let full_release = config.full_threshold.saturating_sub(config.hysteresis);
+
+        match self {
+            _ if value >= config.full_threshold => TriggerStage::Full,
+            TriggerStage::Full if value >= full_release => TriggerStage::Full,
+            TriggerStage::Released if value >= config.light_threshold => TriggerStage::Light,
+            TriggerStage::Light | TriggerStage::Full if value >= light_release => {
+                TriggerStage::Light
+            }
+            _ => TriggerStage::Released,
+        }
+    }
+}
+
+/// 扳机按下状态跟踪，用于识别单独轻按扳机（期间没有触发组合键）和两段式扳机
+#[derive(Debug, Default)]
+struct TriggerTracker {
+    /// 本次按下的时刻，未按下时为 None
+    pressed_at: Option<Instant>,
+    /// 本次按下期间是否触发过组合键
+    combo_used: bool,
+    /// 两段式扳机阶段
+    stage: TriggerStage,
+}
+
+impl TriggerTracker {
+    /// 更新扳机状态，松开时若按住时长不超过 `max_tap` 且未触发组合键则返回 true
+    fn update(&mut self, pressed: bool, now: Instant, max_tap: Duration) -> bool {
+        match (self.pressed_at, pressed) {
+            (None, true) => {
+                self.pressed_at = Some(now);
+                false
+            }
+            (Some(pressed_at), false) => {
+                self.pressed_at = None;
+                // 组合键可能与扳机在同一份报告中按下，标记在松开时才清除
+                let combo_used = std::mem::take(&mut self.combo_used);
+                !combo_used && now.saturating_duration_since(pressed_at) <= max_tap
+            }
+            _ => false,
+        }
+    }
+}
+
+/// 右摇杆方向的轻拨/按住跟踪
+///
+/// 摇杆推入某方向后在 `stick_hold_ms` 内回中视为轻拨，执行 "<方向>_Flick" 绑定；
+/// 超过时限则按下 "<方向>_Hold" 绑定，回中时释放。
+#[derive(Debug, Default)]
+struct StickGesture {
+    /// 当前跟踪的方向及推入时刻
+    entered: Option<(&'static str, Instant)>,
+    /// 是否已超过按住时限
+    held: bool,
+    /// 按住时执行的动作，回中时据此释放
+    hold_action: Option<ButtonAction>,
+}
+
+/// 摇杆中心粘滞状态
+///
+/// 摇杆静止在死区内后，有效死区扩大一个回差，直到摇杆明确移出，
+/// 避免噪声在死区边缘反复越界导致光标抖动。
+#[derive(Debug, Default, Clone, Copy)]
+struct StickyDeadzone {
+    /// 上一帧是否静止在死区内
+    resting: bool,
+}
+
+impl StickyDeadzone {
+    /// 更新静止状态并返回本帧生效的死区
+    fn update(&mut self, x: i16, y: i16, deadzone: i16, hysteresis: i16) -> i16 {
+        let effective = if self.resting {
+            deadzone.saturating_add(hysteresis)
+        } else {
+            deadzone
+        };
+        self.resting = x.saturating_abs() <= effective && y.saturating_abs() <= effective;
+        effective
+    }
+}
 
 /// 输入处理器，负责将手柄输入转换为系统操作
 pub struct InputHandler {
-    enigo: Enigo,
+    output: TrackedOutput, // 输出后端，记录仍按下的按键以便断线或退出时释放
     config: ControllerConfig,
     last_buttons: HashSet<u8>,
-    nav_flags: (bool, bool), // (左触发, 右触发)
+    button_pressed_at: HashMap<u8, Instant>, // 各按键按下时刻
+    next_repeat_at: HashMap<u8, Instant>,    // 按住重复的按键下一次重复执行的时刻
+    active_actions: HashMap<u8, ButtonAction>, // 各按键按下时实际执行的动作，松开时据此释放
+    pending_chord: Vec<u8>,                  // 已按下、正在等待凑成同按组合的按键（按按下顺序）
+    toggled_buttons: HashSet<MouseButton>,   // 通过 ToggleHold 按住、尚未松开的鼠标按键
+    nav_flags: (bool, bool),                 // (左触发, 右触发)
     screen_width: i32,
     screen_height: i32,
     lt_pressed: bool, // 跟踪LT是否按下，用于组合键检测
+    rt_pressed: bool, // 跟踪RT是否按下，用于组合键检测
+    lt_tracker: TriggerTracker,
+    rt_tracker: TriggerTracker,
+    left_stick_rest: StickyDeadzone,
+    right_stick_rest: StickyDeadzone,
+    grid_remainder: (f64, f64),  // 网格吸附时尚未体现到光标位置上的移动量
+    cursor_velocity: (f64, f64), // 上一帧的光标速度，用于光标惯性
+    subpixel: (f64, f64),        // 尚未输出的不足一像素（或不足最小位移）的移动量
+    filtered_gyro_yaw: f64,      // 平滑后的陀螺仪偏航值
+    filtered_gyro_pitch: f64,    // 平滑后的陀螺仪俯仰值
+    right_gesture: StickGesture,
+    scroll_contribution: ScrollState, // 本组最近一次写入共享滚动状态的分量
+    last_movement_at: Option<Instant>, // 上一次计算光标移动的报告时刻
+    now: Instant, // 正在处理的报告或定时事件的时刻，定时步骤的截止时间以此为基准
+    armed: bool,  // 是否已解锁（未启用解锁时始终为 true）
+    arming_chord_held: bool, // 上一帧是否按住解锁/锁定组合键，组合键需松开后才能再次切换
+    last_action: Option<ButtonAction>, // 最近一次执行的动作（不含 RepeatLast），供 RepeatLast 重复
+    action_log: Option<ActionLog>,
+    recent_actions: VecDeque<Instant>, // 最近一秒内执行的动作时刻，用于全局频率限制
+    rumble_requested: bool,            // 本帧执行了动作，等待主循环发送震动反馈
+    quit_requested: bool,              // 执行了 QuitController，等待主循环退出
+    macro_steps: VecDeque<MacroStep>,  // 宏中尚未执行的步骤（遇到延时后暂停）
+    macro_resume_at: Option<Instant>,  // 宏延时结束、继续执行剩余步骤的时刻
+    delayed_releases: Vec<(Instant, ButtonAction)>, // 补足最短按住时间后再释放的鼠标点击
+    pending_clicks: Vec<(Instant, MouseButton)>, // 双击中尚未发送的第二次点击
+    pending_presses: Vec<(Instant, Shortcut)>, // 连按快捷键中尚未执行的后续按下
 }
 
 impl InputHandler {
     /// 创建新的输入处理器
     pub fn new(config: ControllerConfig) -> ControllerResult<Self> {
-        let enigo = Enigo::new(&enigo::Settings::default()).map_err(|e| {
-            ControllerError::InitializationFailed(format!("Enigo初始化失败: {}", e))
-        })?;
+        let output = create_output_sink(config.output_backend, config.dry_run)?;
+        Self::with_output(output, config)
+    }
 
+    /// 使用指定的输出后端创建输入处理器
+    ///
+    /// 不依赖真实的手柄和系统输入，可传入自定义的 `OutputSink` 记录输出，
+    /// 再用手动构造的 `ControllerState` 调用 `handle_input` 驱动处理逻辑（用于测试或脚本）。
+    pub fn with_output(
+        output: Box<dyn OutputSink>,
+        config: ControllerConfig,
+    ) -> ControllerResult<Self> {
         // 获取屏幕尺寸（只需要获取一次）
-        let (screen_width, screen_height) = enigo.main_display().map_err(|e| {
+        let (screen_width, screen_height) = output.main_display().map_err(|e| {
             ControllerError::InitializationFailed(format!("获取屏幕尺寸失败: {}", e))
         })?;
 
+        let action_log = config
+            .action_log_path
+            .as_ref()
+            .map(|path| ActionLog::new(path, config.action_log_max_bytes));
+
+        let armed = !config.arming.require_arm;
+
         Ok(Self {
-            enigo,
+            output: TrackedOutput::new(output),
             config,
             last_buttons: HashSet::new(),
+            button_pressed_at: HashMap::new(),
+            next_repeat_at: HashMap::new(),
+            active_actions: HashMap::new(),
+            pending_chord: Vec::new(),
+            toggled_buttons: HashSet::new(),
             nav_flags: (false, false),
-            screen_width: screen_width as i32,
-            screen_height: screen_height as i32,
+            screen_width,
+            screen_height,
             lt_pressed: false,
+            rt_pressed: false,
+            lt_tracker: TriggerTracker::default(),
+            rt_tracker: TriggerTracker::default(),
+            left_stick_rest: StickyDeadzone::default(),
+            right_stick_rest: StickyDeadzone::default(),
+            grid_remainder: (0.0, 0.0),
+            cursor_velocity: (0.0, 0.0),
+            subpixel: (0.0, 0.0),
+            filtered_gyro_yaw: 0.0,
+            filtered_gyro_pitch: 0.0,
+            right_gesture: StickGesture::default(),
+            scroll_contribution: ScrollState::default(),
+            last_movement_at: None,
+            now: Instant::now(),
+            armed,
+            arming_chord_held: false,
+            last_action: None,
+            action_log,
+            recent_actions: VecDeque::new(),
+            rumble_requested: false,
+            quit_requested: false,
+            macro_steps: VecDeque::new(),
+            macro_resume_at: None,
+            delayed_releases: Vec::new(),
+            pending_clicks: Vec::new(),
+            pending_presses: Vec::new(),
         })
     }
 
+    /// 运行中替换配置（热重载）
+    ///
+    /// 输出后端只在创建时读取，修改后需重启程序才会生效。
+    pub fn update_config(&mut self, config: ControllerConfig) {
+        self.action_log = config
+            .action_log_path
+            .as_ref()
+            .map(|path| ActionLog::new(path, config.action_log_max_bytes));
+        if !config.arming.require_arm {
+            self.armed = true;
+        }
+        self.config = config;
+    }
+
     /// 处理控制器状态更新
     pub fn handle_input(
         &mut self,
         state: &ControllerState,
-        scroll_power: &Arc<Mutex<f64>>,
+        scroll_state: &Arc<Mutex<ScrollState>>,
     ) -> ControllerResult<()> {
-        // 1. 更新扳机状态用于组合键检测
-        self.lt_pressed = state.lt > self.config.analog_trigger_threshold;
+        // 本帧的定时步骤、频率限制和光标位移都按报告时刻计算，合并或重放的报告不受处理延迟影响
+        self.now = state.timestamp;
+
+        // 0. 解锁/锁定：未解锁时只检测解锁组合键
+        if self.config.arming.require_arm && !self.update_arming(state, scroll_state)? {
+            return Ok(());
+        }
+
+        // 1. 更新扳机状态用于组合键检测（扳机用于滚动时不作为修饰键）
+        let trigger_scroll = self.config.scroll_source == ScrollSource::Triggers;
+        self.lt_pressed = !trigger_scroll && state.lt > self.config.analog_trigger_threshold;
+        self.rt_pressed = !trigger_scroll && state.rt > self.config.analog_trigger_threshold;
 
         // 2. 处理按钮事件
         self.handle_button_events(state)?;
 
-        // 3. 处理光标移动（摇杆 + 陀螺仪）
-        self.handle_mouse_movement(state)?;
+        // 3. 处理单独轻按扳机（需在按钮事件之后，以便先记录组合键的使用）
+        if !trigger_scroll {
+            self.handle_lone_triggers(state)?;
+            if self.config.trigger_stages.enabled {
+                self.handle_trigger_stages(state)?;
+            }
+        }
 
-        // 4. 处理右摇杆（滚动 + 导航）
-        self.handle_right_stick(state, scroll_power)?;
+        // 4. 两个摇杆同时推动时按策略屏蔽其中一个
+        let arbitrated = self.arbitrate_sticks(state);
+        let stick_state = arbitrated.as_ref().unwrap_or(state);
 
-        Ok(())
-    }
+        // 5. 处理光标移动（摇杆 + 陀螺仪），得到陀螺仪映射到滚动的分量
+        let (gyro_scroll_v, gyro_scroll_h) = self.handle_mouse_movement(stick_state)?;
 
-    /// 处理按钮按下和释放事件
-    fn handle_button_events(&mut self, state: &ControllerState) -> ControllerResult<()> {
-        let newly_pressed = &state.pressed_buttons - &self.last_buttons;
-        let newly_released = &self.last_buttons - &state.pressed_buttons;
+        // 6. 处理右摇杆（滚动 + 导航），扳机滚动模式下右摇杆不用于垂直滚动
+        let (stick_power, stick_horizontal) = self.handle_right_stick(stick_state)?;
+        let power = if trigger_scroll {
+            self.trigger_scroll_power(state)
+        } else {
+            stick_power
+        } - gyro_scroll_v;
 
-        // 处理按下事件
-        for &button in &newly_pressed {
-            self.execute_button_action(button, true)?;
-        }
+        // 7. 限制合成后的滚动力度（含陀螺仪分量），避免高灵敏度下失控滚动；
+        // 反转滚动方向时所有来源的两个轴同时反转，与系统"自然滚动"设置的效果一致
+        let direction = if self.config.invert_scroll { -1.0 } else { 1.0 };
+        let power = self.limit_scroll_power(power) * direction;
+        let horizontal = self.limit_scroll_power(stick_horizontal + gyro_scroll_h) * direction;
 
-        // 处理释放事件
-        for &button in &newly_released {
-            self.execute_button_action(button, false)?;
-        }
+        // 8. 更新共享滚动状态，按住缩放修饰键时以缩放方式滚动
+        let zoom = power != 0.0
+            && self
+                .config
+                .zoom_scroll_button
+                .as_deref()
+                .is_some_and(|name| self.is_button_held(state, name));
+        self.set_scroll(
+            scroll_state,
+            ScrollState {
+                power,
+                horizontal,
+                zoom,
+            },
+        );
 
-        self.last_buttons = state.pressed_buttons.clone();
         Ok(())
     }
 
-    /// 执行按钮动作
-    fn execute_button_action(&mut self, button: u8, pressed: bool) -> ControllerResult<()> {
-        // 获取按钮名称
-        let button_name = self.get_button_name(button);
+    /// 更新本组在共享滚动状态中的分量
+    ///
+    /// 多个输入组共用一个滚动状态，步调器按各组分量之和滚动；每组只替换自己上次写入的分量，
+    /// 空闲的组不会打断其他组正在进行的滚动。
+    fn set_scroll(&mut self, scroll_state: &Arc<Mutex<ScrollState>>, scroll: ScrollState) {
+        if let Ok(mut shared) = scroll_state.lock() {
+            shared.replace_contribution(self.scroll_contribution, scroll);
+            self.scroll_contribution = scroll;
+        }
+    }
 
-        // 检查是否有组合键
-        let mut tried_combos = Vec::new();
+    /// 撤下本组的滚动分量（断线、锁定等场景）
+    pub fn stop_scroll(&mut self, scroll_state: &Arc<Mutex<ScrollState>>) {
+        self.set_scroll(scroll_state, ScrollState::default());
+    }
 
-        // 检查双键组合 (LT + 按键)
-        if self.lt_pressed {
-            let combo = format!("LT+{}", button_name);
-            tried_combos.push(combo.clone());
-            if let Some(action) = self.config.get_button_action(&combo).cloned() {
-                if pressed {
-                    self.execute_action(&action, pressed)?;
-                }
-                return Ok(());
-            }
+    /// 更新解锁状态，返回本帧是否继续处理输入
+    ///
+    /// 解锁组合键在锁定时生效，锁定组合键在解锁时生效；组合键松开前不会再次切换，
+    /// 避免解锁和锁定使用相同组合键时来回切换。
+    fn update_arming(
+        &mut self,
+        state: &ControllerState,
+        scroll_state: &Arc<Mutex<ScrollState>>,
+    ) -> ControllerResult<bool> {
+        let arming = &self.config.arming;
+        let chord = if self.armed {
+            &arming.disarm_chord
+        } else {
+            &arming.arm_chord
+        };
+        let chord_held =
+            !chord.is_empty() && chord.iter().all(|name| self.is_button_held(state, name));
+        let triggered = chord_held && !self.arming_chord_held;
+        self.arming_chord_held = chord_held;
+
+        if !triggered {
+            return Ok(self.armed);
         }
 
-        // 检查单独按键
-        if let Some(action) = self.config.get_button_action(&button_name).cloned() {
-            self.execute_action(&action, pressed)?;
+        if self.armed {
+            self.armed = false;
+            println!("手柄已锁定，按下 {} 解锁。", arming.arm_chord.join("+"));
+            self.stop_scroll(scroll_state);
+            self.release_all()?;
+        } else {
+            self.armed = true;
+            // 组合键本身的按下不触发绑定
+            self.last_buttons = state.pressed_buttons.clone();
+            println!("手柄已解锁。");
         }
+        Ok(false)
+    }
 
-        Ok(())
+    /// 扳机滚动模式下的滚动力度：RT 向上、LT 向下，力度随扳机深度按平方曲线增长
+    fn trigger_scroll_power(&self, state: &ControllerState) -> f64 {
+        let threshold = self.config.analog_trigger_threshold;
+        let normalize = |value: u8| {
+            if value <= threshold {
+                0.0
+            } else {
+                ((value - threshold) as f64 / (u8::MAX - threshold) as f64).powf(2.0)
+            }
+        };
+        (normalize(state.rt) - normalize(state.lt)) * self.config.direct_scroll_sensitivity
     }
 
-    /// 获取按钮名称
-    fn get_button_name(&self, button: u8) -> String {
-        match button {
-            BUTTON_A => "A".to_string(),
-            BUTTON_B => "B".to_string(),
-            BUTTON_X => "X".to_string(),
-            BUTTON_Y => "Y".to_string(),
-            BUTTON_LB => "LB".to_string(),
-            BUTTON_RB => "RB".to_string(),
-            DPAD_UP => "DPad_Up".to_string(),
-            DPAD_DOWN => "DPad_Down".to_string(),
-            DPAD_LEFT => "DPad_Left".to_string(),
-            DPAD_RIGHT => "DPad_Right".to_string(),
-            _ => format!("Unknown_{}", button),
+    /// 按 `max_scroll_power` 限制滚动力度的绝对值（0 表示不限制）
+    fn limit_scroll_power(&self, power: f64) -> f64 {
+        let max = self.config.max_scroll_power;
+        if max > 0.0 {
+            power.clamp(-max, max)
+        } else {
+            power
         }
     }
 
-    /// 执行具体的按键动作
-    fn execute_action(&mut self, action: &ButtonAction, pressed: bool) -> ControllerResult<()> {
-        match action {
-            ButtonAction::LeftClick => {
-                let direction = if pressed { Press } else { Release };
-                self.enigo
-                    .button(EnigoButton::Left, direction)
-                    .map_err(|e| {
-                        ControllerError::InputSimulation(format!("左键点击失败: {}", e))
-                    })?;
-            }
-            ButtonAction::RightClick => {
-                let direction = if pressed { Press } else { Release };
-                self.enigo
-                    .button(EnigoButton::Right, direction)
-                    .map_err(|e| {
-                        ControllerError::InputSimulation(format!("右键点击失败: {}", e))
-                    })?;
-            }
-            ButtonAction::CloseWindow => {
-                if pressed {
-                    self.execute_shortcut(&[Key::Meta], Key::Unicode('w'))?;
-                }
-            }
-            ButtonAction::MissionControl => {
-                if pressed {
-                    self.enigo.key(Key::MissionControl, Click).map_err(|e| {
-                        ControllerError::InputSimulation(format!("调度中心失败: {}", e))
-                    })?;
-                }
-            }
-            ButtonAction::PrevTab => {
-                if pressed {
-                    self.execute_shortcut(&[Key::Meta, Key::Shift], Key::Unicode('['))?;
-                }
-            }
-            ButtonAction::NextTab => {
-                if pressed {
-                    self.execute_shortcut(&[Key::Meta, Key::Shift], Key::Unicode(']'))?;
-                }
+    /// 按 `dual_stick_policy` 仲裁两个摇杆同时推动的情况
+    ///
+    /// 两个摇杆都推出各自死区时，返回将非优先摇杆归零后的状态副本；
+    /// 策略为 Independent 或只有一个摇杆在推动时返回 None，沿用原始状态。
+    fn arbitrate_sticks(&self, state: &ControllerState) -> Option<ControllerState> {
+        let policy = self.config.dual_stick_policy;
+        if policy == DualStickPolicy::Independent {
+            return None;
+        }
+
+        let left_active = state.lx.saturating_abs() > self.config.joystick_deadzone
+            || state.ly.saturating_abs() > self.config.joystick_deadzone;
+        let right_active = state.rx.saturating_abs() > self.config.right_joystick_deadzone
+            || state.ry.saturating_abs() > self.config.right_joystick_deadzone;
+        if !(left_active && right_active) {
+            return None;
+        }
+
+        let mut arbitrated = state.clone();
+        match policy {
+            DualStickPolicy::LeftPriority => (arbitrated.rx, arbitrated.ry) = (0, 0),
+            DualStickPolicy::RightPriority => (arbitrated.lx, arbitrated.ly) = (0, 0),
+            DualStickPolicy::Independent => {}
+        }
+        Some(arbitrated)
+    }
+
+    /// 处理单独轻按 LT/RT 的绑定
+    ///
+    /// 扳机同时承担组合键修饰和陀螺仪开关的作用，因此只有在限定时间内松开、
+    /// 且按住期间没有触发任何组合键时才执行 "LT"/"RT" 绑定。动作在松开时触发，
+    /// 超过时限的按住视为修饰键/瞄准用途。
+    ///
+    /// 此外每次松开扳机（从按下回落到阈值以下）都会执行 "LT_Release"/"RT_Release" 绑定，
+    /// 不论按住多久；启动时扳机未按下，不会误触发。
+    fn handle_lone_triggers(&mut self, state: &ControllerState) -> ControllerResult<()> {
+        let max_tap = Duration::from_millis(self.config.lone_trigger_max_ms);
+        let rt_pressed = state.rt > self.config.analog_trigger_threshold;
+        let lt_released = self.lt_tracker.pressed_at.is_some() && !self.lt_pressed;
+        let rt_released = self.rt_tracker.pressed_at.is_some() && !rt_pressed;
+
+        let lt_tapped = self
+            .lt_tracker
+            .update(self.lt_pressed, state.timestamp, max_tap);
+        let rt_tapped = self.rt_tracker.update(rt_pressed, state.timestamp, max_tap);
+
+        let edges = [
+            (lt_tapped, "LT"),
+            (rt_tapped, "RT"),
+            (lt_released, "LT_Release"),
+            (rt_released, "RT_Release"),
+        ];
+        for (fired, name) in edges {
+            if !fired {
+                continue;
             }
-            ButtonAction::QuitApp => {
-                if pressed {
-                    self.execute_shortcut(&[Key::Meta], Key::Unicode('q'))?;
-                }
+            if let Some(action) = self.config.get_button_action(name).cloned() {
+                self.tap_action(&action)?;
             }
-            ButtonAction::NewTab => {
-                if pressed {
-                    self.execute_shortcut(&[Key::Meta], Key::Unicode('t'))?;
-                }
+        }
+
+        Ok(())
+    }
+
+    /// 处理两段式扳机的阶段切换，进入轻按/按到底阶段时执行对应绑定
+    fn handle_trigger_stages(&mut self, state: &ControllerState) -> ControllerResult<()> {
+        let stage_config = self.config.trigger_stages.clone();
+        let lt_previous = self.lt_tracker.stage;
+        let rt_previous = self.rt_tracker.stage;
+        self.lt_tracker.stage = lt_previous.next(state.lt, &stage_config);
+        self.rt_tracker.stage = rt_previous.next(state.rt, &stage_config);
+
+        for (name, previous, current) in [
+            ("LT", lt_previous, self.lt_tracker.stage),
+            ("RT", rt_previous, self.rt_tracker.stage),
+        ] {
+            // 从松开直接按到底时依次执行轻按和按到底两个绑定
+            let mut bindings = Vec::new();
+            if previous == TriggerStage::Released && current != TriggerStage::Released {
+                bindings.push(format!("{}_Light", name));
             }
-            ButtonAction::Refresh => {
-                if pressed {
-                    self.execute_shortcut(&[Key::Meta], Key::Unicode('r'))?;
-                }
+            if previous != TriggerStage::Full && current == TriggerStage::Full {
+                bindings.push(format!("{}_Full", name));
             }
-            ButtonAction::CustomShortcut { modifiers, key } => {
-                if pressed {
-                    let modifiers_clone = modifiers.clone();
-                    let key_clone = key.clone();
-                    self.execute_custom_shortcut(&modifiers_clone, &key_clone)?;
+
+            for binding in bindings {
+                if let Some(action) = self.config.get_button_action(&binding).cloned() {
+                    self.tap_action(&action)?;
                 }
             }
-            ButtonAction::None => {}
         }
 
         Ok(())
     }
 
-    /// 执行系统快捷键
-    fn execute_shortcut(&mut self, modifiers: &[Key], key: Key) -> ControllerResult<()> {
-        // 按下修饰键
-        for modifier in modifiers {
-            self.enigo
-                .key(*modifier, Press)
-                .map_err(|e| ControllerError::InputSimulation(format!("修饰键按下失败: {}", e)))?;
-        }
-
-        // 点击主键
-        self.enigo
-            .key(key, Click)
-            .map_err(|e| ControllerError::InputSimulation(format!("主键点击失败: {}", e)))?;
-
-        // 释放修饰键（逆序）
-        for modifier in modifiers.iter().rev() {
-            self.enigo
-                .key(*modifier, Release)
-                .map_err(|e| ControllerError::InputSimulation(format!("修饰键释放失败: {}", e)))?;
+    /// 判断指定名称的按键当前是否按住（支持 "LT"/"RT" 扳机）
+    fn is_button_held(&self, state: &ControllerState, name: &str) -> bool {
+        match name {
+            "LT" => state.lt > self.config.analog_trigger_threshold,
+            "RT" => state.rt > self.config.analog_trigger_threshold,
+            _ => state
+                .pressed_buttons
+                .iter()
+                .any(|&button| self.get_button_name(button) == name),
         }
+    }
 
+    /// 在当前光标位置连续点击两次
+    ///
+    /// 第一次点击立即发送，第二次由定时步骤在间隔后发送，不阻塞输入线程；
+    /// 等待期间光标保持不动，即使摇杆正在推动也能落在同一位置被识别为双击。
+    fn double_click(&mut self, button: MouseButton) -> ControllerResult<()> {
+        self.click(button)?;
+        let interval = Duration::from_millis(self.config.double_click_interval_ms);
+        self.pending_clicks.push((self.now + interval, button));
         Ok(())
     }
 
-    /// 执行自定义快捷键
-    fn execute_custom_shortcut(&mut self, modifiers: &[String], key: &str) -> ControllerResult<()> {
-        let modifier_keys: Result<Vec<Key>, _> = modifiers
-            .iter()
-            .map(|m| InputHandler::parse_key_string_static(m))
-            .collect();
+    fn click(&mut self, button: MouseButton) -> ControllerResult<()> {
+        self.output
+            .button(Self::enigo_button(button), Click)
+            .map_err(|e| ControllerError::InputSimulation(format!("双击失败: {}", e)))
+    }
 
-        let modifier_keys = modifier_keys?;
-        let main_key = InputHandler::parse_key_string_static(key)?;
+    /// 将光标移动到屏幕中央
+    pub fn recenter_cursor(&mut self) -> ControllerResult<()> {
+        self.move_cursor_to(ScreenAnchor::Center)
+    }
 
-        self.execute_shortcut(&modifier_keys, main_key)
+    /// 将光标移动到屏幕定位点，角落与光标移动一样限制在屏幕内（最右/最下为尺寸减1）
+    fn move_cursor_to(&mut self, anchor: ScreenAnchor) -> ControllerResult<()> {
+        let (right, bottom) = (self.screen_width - 1, self.screen_height - 1);
+        let (x, y) = match anchor {
+            ScreenAnchor::TopLeft => (0, 0),
+            ScreenAnchor::TopRight => (right, 0),
+            ScreenAnchor::BottomLeft => (0, bottom),
+            ScreenAnchor::BottomRight => (right, bottom),
+            ScreenAnchor::Center => (self.screen_width / 2, self.screen_height / 2),
+        };
+        // 跳转后不再延续之前的光标惯性
+        self.cursor_velocity = (0.0, 0.0);
+        self.output
+            .move_mouse(x, y, Coordinate::Abs)
+            .map_err(|e| ControllerError::InputSimulation(format!("移动光标失败: {}", e)))
     }
 
-    /// 解析键名字符串为 Key 枚举
-    fn parse_key_string_static(key_str: &str) -> ControllerResult<Key> {
-        match key_str.to_lowercase().as_str() {
-            "cmd" | "meta" => Ok(Key::Meta),
-            "ctrl" | "control" => Ok(Key::Control),
-            "shift" => Ok(Key::Shift),
-            "alt" | "option" => Ok(Key::Alt),
-            "space" => Ok(Key::Space),
-            "return" | "enter" => Ok(Key::Return),
-            "tab" => Ok(Key::Tab),
-            "escape" | "esc" => Ok(Key::Escape),
-            "delete" | "del" => Ok(Key::Delete),
-            "backspace" => Ok(Key::Backspace),
-            "up" => Ok(Key::UpArrow),
-            "down" => Ok(Key::DownArrow),
-            "left" => Ok(Key::LeftArrow),
-            "right" => Ok(Key::RightArrow),
-            "plus" | "=" => Ok(Key::Unicode('=')),
-            "minus" | "-" => Ok(Key::Unicode('-')),
-            s if s.len() == 1 => Ok(Key::Unicode(s.chars().next().unwrap())),
-            _ => Err(ControllerError::Config(format!(
-                "无法识别的键名: {}",
-                key_str
-            ))),
+    /// 依次执行一组动作（每个动作按下后立即释放）
+    pub fn run_action_sequence(&mut self, actions: &[ButtonAction]) -> ControllerResult<()> {
+        // 不由报告触发，以当前时刻为基准
+        self.now = Instant::now();
+        for action in actions {
+            self.tap_action(action)?;
         }
+        Ok(())
     }
 
-    /// 计算鼠标（光标）移动增量
-    fn handle_mouse_movement(&mut self, state: &ControllerState) -> ControllerResult<()> {
-        let mut delta_x = 0.0;
-        let mut delta_y = 0.0;
+    /// 处理按钮按下和释放事件
+    ///
+    /// 同一帧内先处理全部按下、再处理全部释放，各自按按键编号排序，保证顺序确定
+    fn handle_button_events(&mut self, state: &ControllerState) -> ControllerResult<()> {
+        let (mut newly_pressed, mut newly_released) =
+            Self::button_diff(&self.last_buttons, &state.pressed_buttons);
+        // 同一帧内按下又松开的轻按（中间报告已被合并）：照常按下，再随释放事件松开
+        let taps = Self::frame_taps(&self.last_buttons, state);
+        newly_pressed.extend(&taps);
+        newly_released.extend(&taps);
 
-        // 左摇杆 - 使用统一的规范化函数
-        delta_x += Self::normalize_joystick_value(state.lx, self.config.joystick_deadzone, 2.0)
-            * self.config.joystick_sensitivity;
-        delta_y += Self::normalize_joystick_value(state.ly, self.config.joystick_deadzone, 2.0)
-            * self.config.joystick_sensitivity;
+        // 先执行到时的定时步骤和等待超时、没能凑成组合的按键
+        self.run_timed_steps(state.timestamp)?;
+        self.flush_expired_chord(state.timestamp)?;
 
-        // 陀螺仪（仅当按住LT时）
-        if state.lt > self.config.analog_trigger_threshold {
-            if state.gyro_yaw.saturating_abs() > self.config.gyro_deadzone {
-                delta_x += state.gyro_yaw as f64 * self.config.gyro_sensitivity;
+        // 处理按下事件
+        for button in newly_pressed {
+            self.button_pressed_at.insert(button, state.timestamp);
+            if self.is_stick_click_suppressed(button, state) {
+                // 未记录动作，松开时也不会触发释放
+                continue;
             }
-            if state.gyro_pitch.saturating_abs() > self.config.gyro_deadzone {
-                delta_y += state.gyro_pitch as f64 * self.config.gyro_sensitivity;
+            if self.config.is_chord_button(&self.get_button_name(button)) {
+                self.pending_chord.push(button);
+                continue;
             }
+            self.execute_button_action(button, true)?;
+            self.schedule_repeat(button, state.timestamp);
+        }
+        self.try_complete_chord()?;
+        if self.config.chord_window_ms == 0 {
+            self.flush_pending_chord(state.timestamp)?;
         }
 
-        // 只有当移动量足够大时才移动鼠标
-        if delta_x.abs() >= 0.01 || delta_y.abs() >= 0.01 {
-            // 获取当前光标位置
-            let current_pos = self.enigo.location().map_err(|e| {
-                ControllerError::InputSimulation(format!("获取光标位置失败: {}", e))
-            })?;
-
-            // 计算新位置
-            let new_x = (current_pos.0 as f64 + delta_x).round() as i32;
-            let new_y = (current_pos.1 as f64 + delta_y).round() as i32;
-
-            // 限制光标在屏幕边界内（使用预先获取的屏幕尺寸）
-            let clamped_x = new_x.max(0).min(self.screen_width - 1);
-            let clamped_y = new_y.max(0).min(self.screen_height - 1);
-
-            // 使用绝对坐标移动光标
-            self.enigo
-                .move_mouse(clamped_x, clamped_y, Coordinate::Abs)
-                .map_err(|e| ControllerError::InputSimulation(format!("鼠标移动失败: {}", e)))?;
+        // 处理释放事件（松开即取消重复）
+        for button in newly_released {
+            // 等待组合期间松开：先补上按下，松开时照常释放
+            if self.pending_chord.contains(&button) {
+                self.flush_pending_chord(state.timestamp)?;
+            }
+            self.next_repeat_at.remove(&button);
+            self.execute_button_action(button, false)?;
+            self.button_pressed_at.remove(&button);
         }
 
+        self.repeat_held_actions(state.timestamp)?;
+
+        self.last_buttons = state.pressed_buttons.clone();
         Ok(())
     }
 
-    /// 处理右摇杆滚动和导航功能
-    fn handle_right_stick(
-        &mut self,
-        state: &ControllerState,
-        scroll_power: &Arc<Mutex<f64>>,
-    ) -> ControllerResult<()> {
-        let (rx_abs, ry_abs) = (state.rx.saturating_abs(), state.ry.saturating_abs());
-
-        // 检查是否有LT + 右摇杆方向的组合键绑定
-        if self.lt_pressed {
-            // 检查垂直方向 (优先)
-            if ry_abs > self.config.right_joystick_deadzone
-                && (ry_abs as f64 > rx_abs as f64 * self.config.dominant_axis_factor)
-            {
-                let stick_direction = if state.ry > 0 {
-                    "RStick_Down"
-                } else {
-                    "RStick_Up"
-                };
-                let combo = format!("LT+{}", stick_direction);
+    /// 等待中的按键恰好凑成同按组合时执行组合绑定
+    ///
+    /// 组合动作记在最后按下的按键上，松开该键时释放；组合中的其他按键不再执行自己的绑定。
+    fn try_complete_chord(&mut self) -> ControllerResult<()> {
+        let Some(&last) = self.pending_chord.last() else {
+            return Ok(());
+        };
+        if self.pending_chord.len() < 2 {
+            return Ok(());
+        }
+        let names: Vec<String> = self
+            .pending_chord
+            .iter()
+            .map(|&button| self.get_button_name(button))
+            .collect();
+        if let Some(action) = self.config.get_chord_action(&names).cloned() {
+            self.pending_chord.clear();
+            if self.execute_action(&action, true)? {
+                self.active_actions.insert(last, action);
+            }
+        }
+        Ok(())
+    }
 
-                if let Some(action) = self.config.get_button_action(&combo).cloned() {
-                    // 执行自定义绑定，使用方向标志避免重复触发
-                    if state.ry > 0 && !self.nav_flags.1 {
-                        self.execute_action(&action, true)?;
-                        self.nav_flags.1 = true;
-                    } else if state.ry < 0 && !self.nav_flags.0 {
-                        self.execute_action(&action, true)?;
-                        self.nav_flags.0 = true;
-                    }
-                } else {
-                    // 没有自定义绑定，使用默认滚动行为
-                    let normalized_ry = Self::normalize_joystick_value(
-                        state.ry,
-                        self.config.right_joystick_deadzone,
-                        2.0,
-                    );
-                    let current_scroll_power =
-                        -normalized_ry * self.config.direct_scroll_sensitivity;
-                    if let Ok(mut power) = scroll_power.lock() {
-                        *power = current_scroll_power;
-                    }
-                }
+    /// 第一个等待中的按键超过 `chord_window_ms` 仍未凑成组合时，按单独按下依次执行
+    fn flush_expired_chord(&mut self, now: Instant) -> ControllerResult<()> {
+        let window = Duration::from_millis(self.config.chord_window_ms);
+        let expired = self
+            .pending_chord
+            .first()
+            .and_then(|button| self.button_pressed_at.get(button))
+            .is_some_and(|&pressed_at| now.saturating_duration_since(pressed_at) >= window);
+        if expired {
+            self.flush_pending_chord(now)?;
+        }
+        Ok(())
+    }
+
+    /// 按单独按下依次执行所有等待中的按键
+    fn flush_pending_chord(&mut self, now: Instant) -> ControllerResult<()> {
+        for button in std::mem::take(&mut self.pending_chord) {
+            self.execute_button_action(button, true)?;
+            self.schedule_repeat(button, now);
+        }
+        Ok(())
+    }
+
+    /// 处理与手柄报告无关的定时事件：宏延时结束、同按组合等待超时和按住重复
+    ///
+    /// 手柄状态不变时可能不发送报告，主循环在没有新报告时也需调用。
+    pub fn handle_timers(&mut self, now: Instant) -> ControllerResult<()> {
+        self.now = now;
+        self.run_timed_steps(now)?;
+        self.flush_expired_chord(now)?;
+        self.repeat_held_actions(now)
+    }
+
+    /// 执行到时的定时步骤（延后的点击释放、延时结束后继续执行宏）
+    fn run_timed_steps(&mut self, now: Instant) -> ControllerResult<()> {
+        let (due, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.delayed_releases)
+            .into_iter()
+            .partition(|(at, _)| *at <= now);
+        self.delayed_releases = pending;
+        for (_, action) in due {
+            self.execute_action(&action, false)?;
+        }
+
+        let (due, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending_clicks)
+            .into_iter()
+            .partition(|(at, _)| *at <= now);
+        self.pending_clicks = pending;
+        for (_, button) in due {
+            self.click(button)?;
+        }
+
+        let (due, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending_presses)
+            .into_iter()
+            .partition(|(at, _)| *at <= now);
+        self.pending_presses = pending;
+        for (_, shortcut) in due {
+            self.execute_custom_shortcut(&shortcut.modifiers, &shortcut.key)?;
+        }
+
+        if self.macro_resume_at.is_some_and(|at| now >= at) {
+            self.macro_resume_at = None;
+            self.run_macro_steps(now)?;
+        }
+        Ok(())
+    }
+
+    /// 按键配置了按住重复且按下时执行了动作时，安排首次重复
+    ///
+    /// ToggleHold 不重复：每次重复都会切换一次按住状态，松开手柄按键时结果取决于重复次数的奇偶。
+    fn schedule_repeat(&mut self, button: u8, pressed_at: Instant) {
+        match self.active_actions.get(&button) {
+            None | Some(ButtonAction::ToggleHold(_)) => return,
+            Some(_) => {}
+        }
+        if let Some(repeat) = self.repeat_config(button) {
+            let delay = Duration::from_millis(repeat.initial_delay_ms);
+            self.next_repeat_at.insert(button, pressed_at + delay);
+        }
+    }
+
+    /// 按键的按住重复参数，未配置时返回 None
+    fn repeat_config(&self, button: u8) -> Option<RepeatConfig> {
+        self.config
+            .repeat_actions
+            .get(&self.get_button_name(button))
+            .copied()
+    }
+
+    /// 重复执行已到时的按住动作（先释放再按下，按住类动作也能重复触发）
+    fn repeat_held_actions(&mut self, now: Instant) -> ControllerResult<()> {
+        let mut due: Vec<u8> = self
+            .next_repeat_at
+            .iter()
+            .filter(|&(_, &at)| now >= at)
+            .map(|(&button, _)| button)
+            .collect();
+        due.sort_unstable();
+
+        for button in due {
+            let (Some(action), Some(repeat)) = (
+                self.active_actions.get(&button).cloned(),
+                self.repeat_config(button),
+            ) else {
+                self.next_repeat_at.remove(&button);
+                continue;
+            };
+            self.next_repeat_at
+                .insert(button, now + Duration::from_millis(repeat.interval_ms));
+            self.execute_action(&action, false)?;
+            // 重复被频率限制丢弃时动作已释放，松开按键时不再释放
+            if !self.execute_action(&action, true)? {
+                self.active_actions.remove(&button);
+                self.next_repeat_at.remove(&button);
             }
-            // 检查水平方向
-            else if rx_abs > self.config.nav_trigger_threshold
-                && (rx_abs as f64 > ry_abs as f64 * self.config.dominant_axis_factor)
+        }
+        Ok(())
+    }
+
+    /// 释放所有仍处于按住状态的输出并重置按键状态（用于断线等场景）
+    pub fn release_all(&mut self) -> ControllerResult<()> {
+        let mut held: Vec<u8> = self.active_actions.keys().copied().collect();
+        held.sort_unstable();
+        self.last_buttons.clear();
+        // 清除按下时刻，释放时不再补足按住时间
+        self.button_pressed_at.clear();
+
+        // 逐个释放，即使某个按键释放失败也继续释放其余按键
+        let mut result = Ok(());
+        for button in held {
+            if let Err(e) = self.execute_button_action(button, false) {
+                result = Err(e);
+            }
+        }
+        for (_, action) in std::mem::take(&mut self.delayed_releases) {
+            if let Err(e) = self.execute_action(&action, false) {
+                result = Err(e);
+            }
+        }
+        if let Some(action) = std::mem::take(&mut self.right_gesture).hold_action
+            && let Err(e) = self.execute_action(&action, false)
+        {
+            result = Err(e);
+        }
+        for button in std::mem::take(&mut self.toggled_buttons) {
+            if let Err(e) = self
+                .output
+                .button(Self::enigo_button(button), Release)
+                .map_err(|e| ControllerError::InputSimulation(format!("松开鼠标按键失败: {}", e)))
             {
-                let normalized_rx = state.normalized_rx();
-                let stick_direction = if normalized_rx > 0 {
-                    "RStick_Right"
-                } else {
-                    "RStick_Left"
-                };
-                let combo = format!("LT+{}", stick_direction);
+                result = Err(e);
+            }
+        }
+        // 兜底：释放按绑定释放后仍处于按下状态的按键（如执行到一半出错的快捷键的修饰键）
+        if let Err(e) = self.output.release_held() {
+            result = Err(ControllerError::InputSimulation(format!(
+                "释放按住的按键失败: {}",
+                e
+            )));
+        }
 
-                if let Some(action) = self.config.get_button_action(&combo).cloned() {
-                    // 执行自定义绑定
-                    if normalized_rx > 0 && !self.nav_flags.1 {
-                        self.execute_action(&action, true)?;
-                        self.nav_flags.1 = true;
-                    } else if normalized_rx < 0 && !self.nav_flags.0 {
-                        self.execute_action(&action, true)?;
-                        self.nav_flags.0 = true;
-                    }
-                } else {
-                    // 没有自定义绑定，使用默认导航行为
-                    if normalized_rx > 0 && !self.nav_flags.1 {
-                        // 前进：Cmd + ]
-                        self.execute_shortcut(&[Key::Meta], Key::Unicode(']'))?;
-                        self.nav_flags.1 = true;
-                    } else if normalized_rx < 0 && !self.nav_flags.0 {
-                        // 后退：Cmd + [
-                        self.execute_shortcut(&[Key::Meta], Key::Unicode('['))?;
-                        self.nav_flags.0 = true;
-                    }
+        // 断线或退出时不再继续执行等待中的宏、双击和连按
+        self.macro_steps.clear();
+        self.macro_resume_at = None;
+        self.pending_clicks.clear();
+        self.pending_presses.clear();
+        self.pending_chord.clear();
+        self.next_repeat_at.clear();
+        self.nav_flags = (false, false);
+        self.cursor_velocity = (0.0, 0.0);
+        self.last_movement_at = None;
+        self.filtered_gyro_yaw = 0.0;
+        self.filtered_gyro_pitch = 0.0;
+        self.lt_pressed = false;
+        self.rt_pressed = false;
+        self.lt_tracker = TriggerTracker::default();
+        self.rt_tracker = TriggerTracker::default();
+        result
+    }
+
+    /// 摇杆按下（L3/R3）时若对应摇杆正在明显移动，视为瞄准时的误触
+    fn is_stick_click_suppressed(&self, button: u8, state: &ControllerState) -> bool {
+        let threshold = self.config.stick_click_guard_threshold;
+        if threshold == 0 {
+            return false;
+        }
+
+        let (x, y) = match self.get_button_name(button).as_str() {
+            "L3" => (state.lx, state.ly),
+            "R3" => (state.rx, state.ry),
+            _ => return false,
+        };
+        (x as f64).hypot(y as f64) > threshold as f64
+    }
+
+    /// 计算两帧之间新按下和新释放的按键，结果按按键编号排序
+    fn button_diff(previous: &HashSet<u8>, current: &HashSet<u8>) -> (Vec<u8>, Vec<u8>) {
+        let mut pressed: Vec<u8> = current.difference(previous).copied().collect();
+        let mut released: Vec<u8> = previous.difference(current).copied().collect();
+        pressed.sort_unstable();
+        released.sort_unstable();
+        (pressed, released)
+    }
+
+    /// 本帧的轻按：上一帧未按住、当前也未按住，只在被合并的中间报告中按下过的按键
+    fn frame_taps(previous: &HashSet<u8>, state: &ControllerState) -> Vec<u8> {
+        let mut taps: Vec<u8> = state
+            .tapped_buttons
+            .iter()
+            .filter(|button| !previous.contains(button) && !state.pressed_buttons.contains(button))
+            .copied()
+            .collect();
+        taps.sort_unstable();
+        taps
+    }
+
+    /// 鼠标点击按住时间不足 `min_click_hold_ms` 时返回补足后的释放时刻，
+    /// 避免单帧轻按的点击被系统忽略
+    fn padded_release_at(&self, button: u8) -> Option<Instant> {
+        let min_hold = Duration::from_millis(self.config.min_click_hold_ms);
+        let release_at = *self.button_pressed_at.get(&button)? + min_hold;
+        (release_at > self.now).then_some(release_at)
+    }
+
+    /// 立即释放所有等待补足按住时间的鼠标点击
+    fn flush_delayed_releases(&mut self) -> ControllerResult<()> {
+        for (_, action) in std::mem::take(&mut self.delayed_releases) {
+            self.execute_action(&action, false)?;
+        }
+        Ok(())
+    }
+
+    /// 执行按钮动作
+    ///
+    /// 按下时解析绑定并记录实际执行的动作；松开时释放记录的动作，
+    /// 即使期间 LT 状态发生变化，按住的鼠标键/按键也能正确释放。
+    fn execute_button_action(&mut self, button: u8, pressed: bool) -> ControllerResult<()> {
+        if !pressed {
+            if let Some(action) = self.active_actions.remove(&button) {
+                // 按住时间不足时延后释放，由定时步骤执行，不阻塞输入线程
+                if action.is_mouse_click()
+                    && let Some(release_at) = self.padded_release_at(button)
+                {
+                    self.delayed_releases.push((release_at, action));
+                    return Ok(());
                 }
+                self.execute_action(&action, false)?;
             }
-        } else {
-            // LT未按下，使用原有的滚动和导航逻辑
+            return Ok(());
+        }
 
-            // 滚动（Y轴优先）
-            let mut current_scroll_power = 0.0;
-            if ry_abs > self.config.right_joystick_deadzone
-                && (ry_abs as f64 > rx_abs as f64 * self.config.dominant_axis_factor)
-            {
-                let normalized_ry = Self::normalize_joystick_value(
-                    state.ry,
-                    self.config.right_joystick_deadzone,
-                    2.0,
-                );
-                // 反向以实现自然滚动方向
-                current_scroll_power = -normalized_ry * self.config.direct_scroll_sensitivity;
+        if let Some(action) = self.resolve_button_action(button) {
+            // 同一鼠标按键再次按下前，先完成上一次点击的释放
+            if action.is_mouse_click() {
+                self.flush_delayed_releases()?;
             }
-
-            // 更新滚动力度
-            if let Ok(mut power) = scroll_power.lock() {
-                *power = current_scroll_power;
+            if self.execute_action(&action, true)? {
+                self.active_actions.insert(button, action);
             }
+        }
 
-            // 导航（X轴优先）- 使用规范化的rx值避免不对称性问题
-            let normalized_rx = state.normalized_rx();
-            if rx_abs > self.config.nav_trigger_threshold
-                && (rx_abs as f64 > ry_abs as f64 * self.config.dominant_axis_factor)
-            {
-                if normalized_rx > 0 && !self.nav_flags.1 {
-                    // 前进：Cmd + ]
-                    self.execute_shortcut(&[Key::Meta], Key::Unicode(']'))?;
-                    self.nav_flags.1 = true;
-                } else if normalized_rx < 0 && !self.nav_flags.0 {
-                    // 后退：Cmd + [
-                    self.execute_shortcut(&[Key::Meta], Key::Unicode('['))?;
-                    self.nav_flags.0 = true;
+        Ok(())
+    }
+
+    /// 查找按钮当前生效的绑定（组合键优先于单独按键）
+    fn resolve_button_action(&mut self, button: u8) -> Option<ButtonAction> {
+        let button_name = self.get_button_name(button);
+
+        // 检查双键组合 (修饰键 + 按键)，按 LT、RT、其他按键编号的顺序取第一个有绑定的组合
+        for modifier in self.combo_modifiers(button) {
+            let combo = format!("{}+{}", modifier, button_name);
+            if let Some(action) = self.config.get_button_action(&combo).cloned() {
+                match modifier.as_str() {
+                    "LT" => self.lt_tracker.combo_used = true,
+                    "RT" => self.rt_tracker.combo_used = true,
+                    _ => {}
                 }
+                return Some(action);
             }
         }
 
-        // 重置导航标志以防止连续触发
-        if rx_abs < self.config.nav_trigger_threshold
-            && ry_abs < self.config.right_joystick_deadzone
-        {
-            self.nav_flags.1 = false;
-            self.nav_flags.0 = false;
+        // 没有任何绑定（包括已停用的绑定）的按键使用默认动作
+        if !self.config.button_mappings.contains_key(&button_name) {
+            return self.config.default_action.clone();
         }
 
-        Ok(())
+        // 检查单独按键
+        self.config.get_button_action(&button_name).cloned()
     }
 
-    /// 规范化摇杆值的统一处理函数
+    /// 按下 `button` 时可作为组合键修饰键的名称：按住的扳机，以及上一帧已按住的其他按键
+    fn combo_modifiers(&self, button: u8) -> Vec<String> {
+        let mut held: Vec<u8> = self
+            .last_buttons
+            .iter()
+            .copied()
+            .filter(|&held| held != button)
+            .collect();
+        held.sort_unstable();
+
+        [(self.lt_pressed, "LT"), (self.rt_pressed, "RT")]
+            .into_iter()
+            .filter(|&(pressed, _)| pressed)
+            .map(|(_, name)| name.to_string())
+            .chain(held.into_iter().map(|held| self.get_button_name(held)))
+            .collect()
+    }
+
+    /// 配置中的鼠标按键对应的 Enigo 按键
+    fn enigo_button(button: MouseButton) -> EnigoButton {
+        match button {
+            MouseButton::Left => EnigoButton::Left,
+            MouseButton::Right => EnigoButton::Right,
+            MouseButton::Middle => EnigoButton::Middle,
+        }
+    }
+
+    /// 获取按钮名称
+    fn get_button_name(&self, button: u8) -> String {
+        match button {
+            BUTTON_A => "A".to_string(),
+            BUTTON_B => "B".to_string(),
+            BUTTON_X => "X".to_string(),
+            BUTTON_Y => "Y".to_string(),
+            BUTTON_LB => "LB".to_string(),
+            BUTTON_RB => "RB".to_string(),
+            BUTTON_SHARE => "Share".to_string(),
+            BUTTON_L3 => "L3".to_string(),
+            BUTTON_R3 => "R3".to_string(),
+            BUTTON_START => "Start".to_string(),
+            BUTTON_BACK => "Back".to_string(),
+            BUTTON_GUIDE => "Guide".to_string(),
+            BUTTON_P1 => "P1".to_string(),
+            BUTTON_P2 => "P2".to_string(),
+            BUTTON_P3 => "P3".to_string(),
+            BUTTON_P4 => "P4".to_string(),
+            DPAD_UP => "DPad_Up".to_string(),
+            DPAD_DOWN => "DPad_Down".to_string(),
+            DPAD_LEFT => "DPad_Left".to_string(),
+            DPAD_RIGHT => "DPad_Right".to_string(),
+            _ => format!("Unknown_{}", button),
+        }
+    }
+
+    /// 执行按键动作（统一入口，按下沿受全局频率限制）
     ///
-    /// 优雅地处理 i16 边界值，避免溢出问题
-    /// 使用 saturating_abs() 自动处理 i16::MIN 溢出
-    fn normalize_joystick_value(value: i16, deadzone: i16, curve_power: f64) -> f64 {
-        let abs_value = value.saturating_abs();
-        let abs_deadzone = deadzone.saturating_abs();
+    /// 返回动作是否实际执行；按下被频率限制丢弃时返回 false，调用方不应再发送对应的释放。
+    fn execute_action(&mut self, action: &ButtonAction, pressed: bool) -> ControllerResult<bool> {
+        if pressed && !matches!(action, ButtonAction::None) && !self.allow_action() {
+            debug!("动作频率超过限制，已丢弃: {:?}", action);
+            return Ok(false);
+        }
 
-        // 死区内返回0
-        if abs_value <= abs_deadzone {
-            return 0.0;
+        let result = self.perform_action(action, pressed);
+        if pressed
+            && !matches!(action, ButtonAction::None)
+            && let Some(log) = &self.action_log
+        {
+            log.record(action, &result);
         }
+        result?;
 
-        // 计算去除死区后的规范化值 [0.0, 1.0]
-        let max_range = i16::MAX - abs_deadzone;
-        let active_range = abs_value - abs_deadzone;
-        let normalized = active_range as f64 / max_range as f64;
+        if pressed && !matches!(action, ButtonAction::None | ButtonAction::RepeatLast) {
+            self.last_action = Some(action.clone());
+        }
+        if pressed && !matches!(action, ButtonAction::None) && self.config.rumble_on_action {
+            self.rumble_requested = true;
+        }
+        Ok(true)
+    }
 
-        // 应用指数曲线并恢复符号
-        let curved = normalized.powf(curve_power);
-        if value < 0 { -curved } else { curved }
+    /// 取出待发送的震动反馈请求（手柄由主循环持有，震动在主循环中发送）
+    pub fn take_rumble_request(&mut self) -> bool {
+        std::mem::take(&mut self.rumble_requested)
+    }
+
+    /// 取出退出请求（由 `QuitController` 设置，主循环据此退出）
+    pub fn take_quit_request(&mut self) -> bool {
+        std::mem::take(&mut self.quit_requested)
+    }
+
+    /// 全局频率限制：最近一秒内的动作数未达到 `max_actions_per_second` 时放行并记录
+    fn allow_action(&mut self) -> bool {
+        let limit = self.config.max_actions_per_second as usize;
+        if limit == 0 {
+            return true;
+        }
+
+        let now = self.now;
+        let window = Duration::from_secs(1);
+        while self
+            .recent_actions
+            .front()
+            .is_some_and(|&at| now.saturating_duration_since(at) >= window)
+        {
+            self.recent_actions.pop_front();
+        }
+
+        if self.recent_actions.len() >= limit {
+            return false;
+        }
+        self.recent_actions.push_back(now);
+        true
+    }
+
+    /// 执行具体的按键动作
+    fn perform_action(&mut self, action: &ButtonAction, pressed: bool) -> ControllerResult<()> {
+        match action {
+            ButtonAction::LeftClick => {
+                let direction = if pressed { Press } else { Release };
+                self.output
+                    .button(EnigoButton::Left, direction)
+                    .map_err(|e| {
+                        ControllerError::InputSimulation(format!("左键点击失败: {}", e))
+                    })?;
+            }
+            ButtonAction::RightClick => {
+                let direction = if pressed { Press } else { Release };
+                self.output
+                    .button(EnigoButton::Right, direction)
+                    .map_err(|e| {
+                        ControllerError::InputSimulation(format!("右键点击失败: {}", e))
+                    })?;
+            }
+            ButtonAction::LeftTap => {
+                if pressed {
+                    self.output.button(EnigoButton::Left, Click).map_err(|e| {
+                        ControllerError::InputSimulation(format!("左键单击失败: {}", e))
+                    })?;
+                }
+            }
+            ButtonAction::RightTap => {
+                if pressed {
+                    self.output.button(EnigoButton::Right, Click).map_err(|e| {
+                        ControllerError::InputSimulation(format!("右键单击失败: {}", e))
+                    })?;
+                }
+            }
+            ButtonAction::DoubleClick(button) => {
+                if pressed {
+                    self.double_click(*button)?;
+                }
+            }
+            ButtonAction::CloseWindow => {
+                if pressed {
+                    self.execute_shortcut(&[self.primary_modifier()?], Key::Unicode('w'))?;
+                }
+            }
+            ButtonAction::MissionControl => {
+                if pressed {
+                    self.show_overview()?;
+                }
+            }
+            ButtonAction::PrevTab => {
+                if pressed {
+                    self.switch_adjacent_tab(SwipeDirection::Back)?;
+                }
+            }
+            ButtonAction::NextTab => {
+                if pressed {
+                    self.switch_adjacent_tab(SwipeDirection::Forward)?;
+                }
+            }
+            ButtonAction::QuitApp => {
+                if pressed {
+                    self.execute_shortcut(&[self.primary_modifier()?], Key::Unicode('q'))?;
+                }
+            }
+            ButtonAction::QuitController => {
+                if pressed {
+                    self.quit_requested = true;
+                }
+            }
+            ButtonAction::NewTab => {
+                if pressed {
+                    self.execute_shortcut(&[self.primary_modifier()?], Key::Unicode('t'))?;
+                }
+            }
+            ButtonAction::Refresh => {
+                if pressed {
+                    self.execute_shortcut(&[self.primary_modifier()?], Key::Unicode('r'))?;
+                }
+            }
+            ButtonAction::ScrollToTop => {
+                if pressed {
+                    let shortcut = self.config.shortcuts.scroll_to_top.clone();
+                    self.execute_configured_shortcut(&shortcut)?;
+                }
+            }
+            ButtonAction::ScrollToBottom => {
+                if pressed {
+                    let shortcut = self.config.shortcuts.scroll_to_bottom.clone();
+                    self.execute_configured_shortcut(&shortcut)?;
+                }
+            }
+            ButtonAction::LockScreen => {
+                if pressed {
+                    let shortcut = self.config.shortcuts.lock_screen.clone();
+                    self.execute_configured_shortcut(&shortcut)?;
+                }
+            }
+            ButtonAction::RepeatLast => {
+                // 重复期间暂时取出记录，避免宏内的 RepeatLast 递归重复自身
+                if pressed && let Some(last) = self.last_action.take() {
+                    let result = self
+                        .perform_action(&last, true)
+                        .and_then(|_| self.perform_action(&last, false));
+                    self.last_action = Some(last);
+                    result?;
+                }
+            }
+            ButtonAction::Swipe(direction) => {
+                if pressed {
+                    self.swipe(*direction)?;
+                }
+            }
+            ButtonAction::MoveCursorTo(anchor) => {
+                if pressed {
+                    self.move_cursor_to(*anchor)?;
+                }
+            }
+            ButtonAction::HoldKey(key) => {
+                let key = Self::parse_key_string_static(key)?;
+                let direction = if pressed { Press } else { Release };
+                self.output.key(key, direction).map_err(|e| {
+                    ControllerError::InputSimulation(format!("按住按键失败: {}", e))
+                })?;
+            }
+            ButtonAction::ToggleHold(button) => {
+                if pressed {
+                    let held = self.toggled_buttons.contains(button);
+                    let direction = if held { Release } else { Press };
+                    self.output
+                        .button(Self::enigo_button(*button), direction)
+                        .map_err(|e| {
+                            ControllerError::InputSimulation(format!("切换按住鼠标按键失败: {}", e))
+                        })?;
+                    // 输出成功后才切换记录的状态，失败时下次按下仍发送同一方向
+                    if held {
+                        self.toggled_buttons.remove(button);
+                    } else {
+                        self.toggled_buttons.insert(*button);
+                    }
+                }
+            }
+            ButtonAction::Paste => {
+                if pressed {
+                    self.execute_shortcut(&[self.primary_modifier()?], Key::Unicode('v'))?;
+                }
+            }
+            ButtonAction::PastePlain => {
+                if pressed {
+                    let shortcut = self.config.shortcuts.paste_plain.clone();
+                    self.execute_configured_shortcut(&shortcut)?;
+                }
+            }
+            ButtonAction::ToggleFullscreen => {
+                if pressed {
+                    let shortcut = self.config.shortcuts.toggle_fullscreen.clone();
+                    self.execute_configured_shortcut(&shortcut)?;
+                }
+            }
+            ButtonAction::MinimizeWindow => {
+                if pressed {
+                    let shortcut = self.config.shortcuts.minimize_window.clone();
+                    self.execute_configured_shortcut(&shortcut)?;
+                }
+            }
+            ButtonAction::HideApp => {
+                if pressed {
+                    let shortcut = self.config.shortcuts.hide_app.clone();
+                    self.execute_configured_shortcut(&shortcut)?;
+                }
+            }
+            ButtonAction::SwitchTab { index } => {
+                if pressed {
+                    let digit = char::from_digit(*index as u32, 10)
+                        .filter(|_| (1..=9).contains(index))
+                        .ok_or_else(|| {
+                            ControllerError::Config(format!("标签页序号必须在1到9之间: {}", index))
+                        })?;
+                    self.execute_shortcut(&[self.primary_modifier()?], Key::Unicode(digit))?;
+                }
+            }
+            ButtonAction::StartDictation => {
+                if pressed {
+                    let shortcut = self.config.shortcuts.start_dictation.clone();
+                    self.execute_configured_shortcut(&shortcut)?;
+                }
+            }
+            ButtonAction::VolumeUp => {
+                if pressed {
+                    self.press_media_key(Key::VolumeUp, "调高音量")?;
+                }
+            }
+            ButtonAction::VolumeDown => {
+                if pressed {
+                    self.press_media_key(Key::VolumeDown, "调低音量")?;
+                }
+            }
+            ButtonAction::Mute => {
+                if pressed {
+                    self.press_media_key(Key::VolumeMute, "静音")?;
+                }
+            }
+            ButtonAction::BrightnessUp => {
+                if pressed {
+                    self.press_media_key(Self::brightness_key(true)?, "调高亮度")?;
+                }
+            }
+            ButtonAction::BrightnessDown => {
+                if pressed {
+                    self.press_media_key(Self::brightness_key(false)?, "调低亮度")?;
+                }
+            }
+            ButtonAction::CustomShortcut { modifiers, key } => {
+                if pressed {
+                    let modifiers_clone = modifiers.clone();
+                    let key_clone = key.clone();
+                    self.execute_custom_shortcut(&modifiers_clone, &key_clone)?;
+                }
+            }
+            ButtonAction::TypeText(text) => {
+                if pressed {
+                    self.type_text(text)?;
+                }
+            }
+            ButtonAction::LaunchApp(app) => {
+                if pressed && self.config.dry_run {
+                    println!("[试运行] 启动应用 {}", app);
+                } else if pressed {
+                    let mut child = Self::launch_command(app).spawn().map_err(|e| {
+                        ControllerError::InputSimulation(format!("启动应用 {} 失败: {}", app, e))
+                    })?;
+                    // 在后台线程等待子进程退出并回收，避免 Linux 上留下僵尸进程
+                    thread::spawn(move || child.wait());
+                }
+            }
+            ButtonAction::Macro(steps) => {
+                if pressed {
+                    self.execute_macro(steps)?;
+                }
+            }
+            ButtonAction::None => {}
+        }
+
+        Ok(())
+    }
+
+    /// 以“按下后立即释放”的方式执行动作
+    fn tap_action(&mut self, action: &ButtonAction) -> ControllerResult<()> {
+        if self.execute_action(action, true)? {
+            self.execute_action(action, false)?;
+        }
+        Ok(())
+    }
+
+    /// 按顺序执行宏步骤
+    ///
+    /// 延时步骤不阻塞输入线程：剩余步骤排队，延时结束后由定时事件继续执行。
+    /// 上一个宏仍在等待时，新宏的步骤排在其后依次执行。
+    fn execute_macro(&mut self, steps: &[MacroStep]) -> ControllerResult<()> {
+        let idle = self.macro_steps.is_empty() && self.macro_resume_at.is_none();
+        self.macro_steps.extend(steps.iter().cloned());
+        if idle {
+            self.run_macro_steps(self.now)?;
+        }
+        Ok(())
+    }
+
+    /// 执行排队的宏步骤，遇到延时时记下继续执行的时刻并暂停；出错时丢弃剩余步骤
+    fn run_macro_steps(&mut self, now: Instant) -> ControllerResult<()> {
+        while self.macro_resume_at.is_none()
+            && let Some(step) = self.macro_steps.pop_front()
+        {
+            let result = match step {
+                MacroStep::Delay(ms) => {
+                    self.macro_resume_at = Some(now + Duration::from_millis(ms));
+                    Ok(())
+                }
+                // 嵌套的宏就地展开，保持步骤顺序
+                MacroStep::Action(ButtonAction::Macro(steps)) => {
+                    for step in steps.into_iter().rev() {
+                        self.macro_steps.push_front(step);
+                    }
+                    Ok(())
+                }
+                MacroStep::Action(action) => self
+                    .perform_action(&action, true)
+                    .and_then(|_| self.perform_action(&action, false)),
+                MacroStep::KeyShortcut { modifiers, key } => {
+                    self.execute_custom_shortcut(&modifiers, &key)
+                }
+                MacroStep::TypeText(text) => self.type_text(&text),
+            };
+            if result.is_err() {
+                self.macro_steps.clear();
+                self.macro_resume_at = None;
+                return result;
+            }
+        }
+        Ok(())
+    }
+
+    /// 主修饰键：优先使用 `primary_modifier` 配置，否则按平台选择（macOS 为 Cmd，其他系统为 Ctrl）
+    fn primary_modifier(&self) -> ControllerResult<Key> {
+        match &self.config.primary_modifier {
+            Some(name) => Self::parse_key_string_static(name),
+            None => Ok(primary_modifier(self.config.platform)),
+        }
+    }
+
+    /// 打开窗口总览：macOS 调度中心、Linux 活动概览、Windows 任务视图
+    fn show_overview(&mut self) -> ControllerResult<()> {
+        match self.config.platform {
+            #[cfg(target_os = "macos")]
+            Platform::MacOS => self
+                .output
+                .key(Key::MissionControl, Click)
+                .map_err(|e| ControllerError::InputSimulation(format!("调度中心失败: {}", e))),
+            // 只有 macOS 版 Enigo 提供调度中心键，其他系统上退回调度中心的默认快捷键
+            #[cfg(not(target_os = "macos"))]
+            Platform::MacOS => self.execute_shortcut(&[Key::Control], Key::UpArrow),
+            // GNOME / KDE：单独按一下 Super 键打开活动概览
+            Platform::Linux => self
+                .output
+                .key(Key::Meta, Click)
+                .map_err(|e| ControllerError::InputSimulation(format!("活动概览失败: {}", e))),
+            Platform::Windows => self.execute_shortcut(&[Key::Meta], Key::Tab),
+        }
+    }
+
+    /// 切换到上一个/下一个标签页：macOS 为 Cmd+Shift+[ / ]，其他系统为 Ctrl+PageUp / PageDown
+    fn switch_adjacent_tab(&mut self, direction: SwipeDirection) -> ControllerResult<()> {
+        let modifier = self.primary_modifier()?;
+        match (self.config.platform, direction) {
+            (Platform::MacOS, SwipeDirection::Back) => {
+                self.execute_shortcut(&[modifier, Key::Shift], Key::Unicode('['))
+            }
+            (Platform::MacOS, SwipeDirection::Forward) => {
+                self.execute_shortcut(&[modifier, Key::Shift], Key::Unicode(']'))
+            }
+            (_, SwipeDirection::Back) => self.execute_shortcut(&[modifier], Key::PageUp),
+            (_, SwipeDirection::Forward) => self.execute_shortcut(&[modifier], Key::PageDown),
+        }
+    }
+
+    /// 启动应用的命令：macOS 通过 `open -a` 按应用名启动，其他系统直接执行该程序
+    fn launch_command(app: &str) -> Command {
+        if cfg!(target_os = "macos") {
+            let mut command = Command::new("open");
+            command.arg("-a").arg(app);
+            command
+        } else {
+            Command::new(app)
+        }
+    }
+
+    /// 输入一段文本
+    fn type_text(&mut self, text: &str) -> ControllerResult<()> {
+        self.output
+            .text(text)
+            .map_err(|e| ControllerError::InputSimulation(format!("输入文本失败: {}", e)))
+    }
+
+    /// 点击一次媒体键（音量、亮度等）
+    fn press_media_key(&mut self, key: Key, name: &str) -> ControllerResult<()> {
+        self.output
+            .key(key, Click)
+            .map_err(|e| ControllerError::InputSimulation(format!("{}失败: {}", name, e)))
+    }
+
+    /// 调节屏幕亮度的按键
+    #[cfg(target_os = "macos")]
+    fn brightness_key(up: bool) -> ControllerResult<Key> {
+        Ok(if up {
+            Key::BrightnessUp
+        } else {
+            Key::BrightnessDown
+        })
+    }
+
+    /// 调节屏幕亮度的按键（X11 keysym XF86MonBrightnessUp / XF86MonBrightnessDown）
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn brightness_key(up: bool) -> ControllerResult<Key> {
+        Ok(Key::Other(if up { 0x1008_FF02 } else { 0x1008_FF03 }))
+    }
+
+    /// Windows 没有调节亮度的虚拟键
+    #[cfg(windows)]
+    fn brightness_key(_up: bool) -> ControllerResult<Key> {
+        Err(ControllerError::InputSimulation(
+            "Windows 不支持通过按键调节亮度".to_string(),
+        ))
+    }
+
+    /// 执行系统快捷键
+    fn execute_shortcut(&mut self, modifiers: &[Key], key: Key) -> ControllerResult<()> {
+        // 按下修饰键
+        for modifier in modifiers {
+            self.output
+                .key(*modifier, Press)
+                .map_err(|e| ControllerError::InputSimulation(format!("修饰键按下失败: {}", e)))?;
+        }
+
+        // 点击主键
+        self.output
+            .key(key, Click)
+            .map_err(|e| ControllerError::InputSimulation(format!("主键点击失败: {}", e)))?;
+
+        // 释放修饰键（逆序）
+        for modifier in modifiers.iter().rev() {
+            self.output
+                .key(*modifier, Release)
+                .map_err(|e| ControllerError::InputSimulation(format!("修饰键释放失败: {}", e)))?;
+        }
+
+        Ok(())
+    }
+
+    /// 执行自定义快捷键
+    fn execute_custom_shortcut(&mut self, modifiers: &[String], key: &str) -> ControllerResult<()> {
+        let modifier_keys: Result<Vec<Key>, _> = modifiers
+            .iter()
+            .map(|m| InputHandler::parse_key_string_static(m))
+            .collect();
+
+        let modifier_keys = modifier_keys?;
+        let main_key = InputHandler::parse_key_string_static(key)?;
+
+        self.execute_shortcut(&modifier_keys, main_key)
+    }
+
+    /// 执行配置中定义的快捷键，按 `presses` 连续按下多次
+    ///
+    /// 首次按下立即执行，后续按下由定时步骤按间隔执行，不阻塞输入线程。
+    fn execute_configured_shortcut(&mut self, shortcut: &Shortcut) -> ControllerResult<()> {
+        self.execute_custom_shortcut(&shortcut.modifiers, &shortcut.key)?;
+        for press in 1..shortcut.presses {
+            self.pending_presses
+                .push((self.now + REPEATED_PRESS_INTERVAL * press, shortcut.clone()));
+        }
+        Ok(())
+    }
+
+    /// 解析键名字符串为 Key 枚举
+    pub(crate) fn parse_key_string_static(key_str: &str) -> ControllerResult<Key> {
+        match key_str.to_lowercase().as_str() {
+            "cmd" | "meta" => Ok(Key::Meta),
+            "ctrl" | "control" => Ok(Key::Control),
+            "shift" => Ok(Key::Shift),
+            "alt" | "option" => Ok(Key::Alt),
+            "space" => Ok(Key::Space),
+            "return" | "enter" => Ok(Key::Return),
+            "tab" => Ok(Key::Tab),
+            "escape" | "esc" => Ok(Key::Escape),
+            "delete" | "del" => Ok(Key::Delete),
+            "backspace" => Ok(Key::Backspace),
+            "up" => Ok(Key::UpArrow),
+            "down" => Ok(Key::DownArrow),
+            "left" => Ok(Key::LeftArrow),
+            "right" => Ok(Key::RightArrow),
+            "home" => Ok(Key::Home),
+            "end" => Ok(Key::End),
+            "pageup" => Ok(Key::PageUp),
+            "pagedown" => Ok(Key::PageDown),
+            #[cfg(target_os = "macos")]
+            "fn" | "function" => Ok(Key::Function),
+            #[cfg(not(target_os = "macos"))]
+            "fn" | "function" => Err(ControllerError::Config(format!(
+                "{} 键仅在 macOS 上可用",
+                key_str
+            ))),
+            "f1" => Ok(Key::F1),
+            "f2" => Ok(Key::F2),
+            "f3" => Ok(Key::F3),
+            "f4" => Ok(Key::F4),
+            "f5" => Ok(Key::F5),
+            "f6" => Ok(Key::F6),
+            "f7" => Ok(Key::F7),
+            "f8" => Ok(Key::F8),
+            "f9" => Ok(Key::F9),
+            "f10" => Ok(Key::F10),
+            "f11" => Ok(Key::F11),
+            "f12" => Ok(Key::F12),
+            "volumeup" => Ok(Key::VolumeUp),
+            "volumedown" => Ok(Key::VolumeDown),
+            "mute" => Ok(Key::VolumeMute),
+            "play" | "playpause" => Ok(Key::MediaPlayPause),
+            "next" => Ok(Key::MediaNextTrack),
+            "prev" | "previous" => Ok(Key::MediaPrevTrack),
+            "plus" | "=" => Ok(Key::Unicode('=')),
+            "minus" | "-" => Ok(Key::Unicode('-')),
+            s if s.len() == 1 => Ok(Key::Unicode(s.chars().next().unwrap())),
+            _ => Err(ControllerError::Config(format!(
+                "无法识别的键名: {}",
+                key_str
+            ))),
+        }
+    }
+
+    /// 计算鼠标（光标）移动增量
+    ///
+    /// 陀螺仪各轴按配置路由到光标或滚动，返回映射到滚动的 (垂直, 水平) 分量，
+    /// 垂直分量正值表示向下。
+    fn handle_mouse_movement(&mut self, state: &ControllerState) -> ControllerResult<(f64, f64)> {
+        // 本帧位移按距上一份报告的时间换算，光标速度不随报告频率或系统负载变化
+        let now = state.timestamp;
+        let elapsed = self
+            .last_movement_at
+            .map_or(MOVEMENT_REFERENCE_INTERVAL, |at| {
+                now.saturating_duration_since(at)
+            })
+            .min(MAX_MOVEMENT_INTERVAL);
+        self.last_movement_at = Some(now);
+        let frame_scale = elapsed.as_secs_f64() / MOVEMENT_REFERENCE_INTERVAL.as_secs_f64();
+
+        let mut delta_x = 0.0;
+        let mut delta_y = 0.0;
+        let mut scroll_v = 0.0;
+        let mut scroll_h = 0.0;
+
+        // 左摇杆 - 使用统一的规范化函数（死区带中心粘滞）
+        let deadzone = self.left_stick_rest.update(
+            state.lx,
+            state.ly,
+            self.config.joystick_deadzone,
+            self.config.deadzone_hysteresis,
+        );
+        let gyro_active = self.lt_pressed;
+        // 陀螺仪瞄准时降低摇杆灵敏度，避免两种输入互相干扰
+        let mut stick_scale = if gyro_active {
+            self.config.gyro_active_stick_scale
+        } else {
+            1.0
+        };
+        // RT 加速：按下越深光标越快（扳机用于滚动时不生效）
+        if self.config.scroll_source != ScrollSource::Triggers {
+            stick_scale *= 1.0 + state.rt as f64 / 255.0 * self.config.rt_speed_multiplier;
+        }
+        let (sensitivity_x, sensitivity_y) = self.config.stick_sensitivity();
+        let (sensitivity_x, sensitivity_y) =
+            (sensitivity_x * stick_scale, sensitivity_y * stick_scale);
+        let curve = self.config.acceleration_curve;
+        let cursor_source = self.config.cursor_source;
+        if cursor_source.uses_left_stick() {
+            match self.config.deadzone_shape {
+                DeadzoneShape::Axial => {
+                    delta_x +=
+                        Self::apply_acceleration_curve(state.lx, deadzone, curve) * sensitivity_x;
+                    delta_y +=
+                        Self::apply_acceleration_curve(state.ly, deadzone, curve) * sensitivity_y;
+                }
+                DeadzoneShape::Radial => {
+                    // 加速曲线作用于推动幅度，两个分量按同一比例缩放以保持方向
+                    let (x, y) = Self::normalize_stick_radial(state.lx, state.ly, deadzone);
+                    let magnitude = x.hypot(y);
+                    if magnitude > 0.0 {
+                        let scale = Self::curve_magnitude(magnitude, curve) / magnitude;
+                        delta_x += x * scale * sensitivity_x;
+                        delta_y += y * scale * sensitivity_y;
+                    }
+                }
+            }
+        }
+
+        // 陀螺仪（仅当按住LT时），左摇杆推动期间可按配置削弱
+        let suppression = &self.config.gyro_stick_suppression;
+        let stick_moving =
+            state.lx.saturating_abs() > deadzone || state.ly.saturating_abs() > deadzone;
+        let gyro_scale = if suppression.enabled && stick_moving {
+            suppression.gyro_scale
+        } else {
+            1.0
+        };
+        if gyro_active {
+            // 指数移动平均：平滑系数越大，历史值占比越高，抖动越小但响应越慢
+            let factor = self.config.gyro_smoothing_factor;
+            self.filtered_gyro_yaw =
+                factor * self.filtered_gyro_yaw + (1.0 - factor) * state.gyro_yaw as f64;
+            self.filtered_gyro_pitch =
+                factor * self.filtered_gyro_pitch + (1.0 - factor) * state.gyro_pitch as f64;
+
+            let mut yaw = self.filtered_gyro_yaw;
+            let mut pitch = self.filtered_gyro_pitch;
+            if self.config.gyro_invert_yaw {
+                yaw = -yaw;
+            }
+            if self.config.gyro_invert_pitch {
+                pitch = -pitch;
+            }
+            if self.config.gyro_swap_axes {
+                std::mem::swap(&mut yaw, &mut pitch);
+            }
+            let axes = [
+                (yaw, self.config.gyro_yaw_target),
+                (pitch, self.config.gyro_pitch_target),
+            ];
+            for (raw, target) in axes {
+                if raw.abs() <= self.config.gyro_deadzone as f64 {
+                    continue;
+                }
+                let value = raw * self.config.gyro_sensitivity * gyro_scale;
+                match target {
+                    GyroTarget::CursorX if cursor_source.uses_gyro() => delta_x += value,
+                    GyroTarget::CursorY if cursor_source.uses_gyro() => delta_y += value,
+                    GyroTarget::ScrollV => scroll_v += value,
+                    GyroTarget::ScrollH => scroll_h += value,
+                    GyroTarget::CursorX | GyroTarget::CursorY | GyroTarget::None => {}
+                }
+            }
+        } else {
+            // 松开LT后清空平滑历史，下次启用陀螺仪时不会带着上次的残留值
+            self.filtered_gyro_yaw = 0.0;
+            self.filtered_gyro_pitch = 0.0;
+        }
+
+        // 手柄不控制光标时直接返回，也不查询光标位置；
+        // 双击等待第二次点击时同样不移动光标，保证两次点击落在同一位置
+        if cursor_source == CursorSource::None || !self.pending_clicks.is_empty() {
+            self.cursor_velocity = (0.0, 0.0);
+            self.subpixel = (0.0, 0.0);
+            return Ok((scroll_v, scroll_h));
+        }
+
+        // 按住加速修饰键时放大光标位移
+        if self
+            .config
+            .speed_modifier_button
+            .as_deref()
+            .is_some_and(|name| self.is_button_held(state, name))
+        {
+            delta_x *= self.config.speed_modifier_multiplier;
+            delta_y *= self.config.speed_modifier_multiplier;
+        }
+
+        // 光标惯性：没有输入时沿用上一帧速度并按经过的时间衰减
+        if delta_x.abs() >= 0.01 || delta_y.abs() >= 0.01 {
+            self.cursor_velocity = (delta_x, delta_y);
+        } else if self.config.cursor_momentum.enabled {
+            let decay = self.config.cursor_momentum.decay.powf(frame_scale);
+            self.cursor_velocity = (
+                self.cursor_velocity.0 * decay,
+                self.cursor_velocity.1 * decay,
+            );
+            (delta_x, delta_y) = self.cursor_velocity;
+        }
+        delta_x *= frame_scale;
+        delta_y *= frame_scale;
+
+        // 只有当移动量足够大时才移动鼠标
+        if delta_x.abs() < 0.01 && delta_y.abs() < 0.01 {
+            self.subpixel = (0.0, 0.0);
+            return Ok((scroll_v, scroll_h));
+        }
+
+        // 计算整数像素位移，不足一像素的部分累积到下一帧
+        let total_x = delta_x + self.subpixel.0;
+        let total_y = delta_y + self.subpixel.1;
+        let move_x = total_x.round();
+        let move_y = total_y.round();
+
+        // 输出端最小位移：本帧位移不足 min_output_pixels（至少一像素）时暂不移动，位移继续累积，
+        // 摇杆略微偏离中心时不必每帧读取光标位置
+        let min_pixels = self.config.min_output_pixels.max(1) as f64;
+        if move_x.abs() < min_pixels && move_y.abs() < min_pixels {
+            self.subpixel = (total_x, total_y);
+            return Ok((scroll_v, scroll_h));
+        }
+        self.subpixel = (total_x - move_x, total_y - move_y);
+
+        // 后端原生支持相对移动时直接发送位移，由后端限制在屏幕内（网格吸附需要绝对位置）
+        if self.output.native_relative_move() && !self.config.grid_snap.enabled {
+            self.output
+                .move_mouse(move_x as i32, move_y as i32, Coordinate::Rel)
+                .map_err(|e| ControllerError::InputSimulation(format!("鼠标移动失败: {}", e)))?;
+            return Ok((scroll_v, scroll_h));
+        }
+
+        // 获取当前光标位置
+        let current_pos = self
+            .output
+            .location()
+            .map_err(|e| ControllerError::InputSimulation(format!("获取光标位置失败: {}", e)))?;
+
+        // 计算新位置
+        let new_x = current_pos.0 + move_x as i32;
+        let new_y = current_pos.1 + move_y as i32;
+
+        // 限制光标在屏幕边界内（使用预先获取的屏幕尺寸）
+        let mut clamped_x = new_x.max(0).min(self.screen_width - 1);
+        let mut clamped_y = new_y.max(0).min(self.screen_height - 1);
+
+        // 网格吸附：累加上一帧未体现的移动量，保证小幅移动也能逐步跨过网格
+        // （使用含累积量的整数位移，暂不移动的帧中的位移不会丢失）
+        if self.config.grid_snap.enabled {
+            let grid = self.config.grid_snap.size as i32;
+            let max_x = (self.screen_width - 1) as f64;
+            let max_y = (self.screen_height - 1) as f64;
+            let target_x =
+                (current_pos.0 as f64 + move_x + self.grid_remainder.0).clamp(0.0, max_x);
+            let target_y =
+                (current_pos.1 as f64 + move_y + self.grid_remainder.1).clamp(0.0, max_y);
+            clamped_x = Self::snap_to_grid(target_x, grid, self.screen_width - 1);
+            clamped_y = Self::snap_to_grid(target_y, grid, self.screen_height - 1);
+            self.grid_remainder = (target_x - clamped_x as f64, target_y - clamped_y as f64);
+        }
+
+        // 使用绝对坐标移动光标
+        self.output
+            .move_mouse(clamped_x, clamped_y, Coordinate::Abs)
+            .map_err(|e| ControllerError::InputSimulation(format!("鼠标移动失败: {}", e)))?;
+
+        Ok((scroll_v, scroll_h))
+    }
+
+    /// 将坐标吸附到最近的网格点，超出屏幕边界时退回上一个网格点
+    fn snap_to_grid(value: f64, grid: i32, max: i32) -> i32 {
+        let snapped = (value / grid as f64).round() as i32 * grid;
+        if snapped > max {
+            snapped - grid
+        } else {
+            snapped
+        }
+    }
+
+    /// 右摇杆推出死区时的主导方向名称
+    fn right_stick_direction(
+        &self,
+        state: &ControllerState,
+        deadzone: i16,
+    ) -> Option<&'static str> {
+        let (rx_abs, ry_abs) = (state.rx.saturating_abs(), state.ry.saturating_abs());
+        let factor = self.config.dominant_axis_factor;
+        if ry_abs > deadzone && ry_abs as f64 > rx_abs as f64 * factor {
+            Some(if state.ry > 0 {
+                "RStick_Down"
+            } else {
+                "RStick_Up"
+            })
+        } else if rx_abs > deadzone && rx_abs as f64 > ry_abs as f64 * factor {
+            Some(if state.rx > 0 {
+                "RStick_Right"
+            } else {
+                "RStick_Left"
+            })
+        } else {
+            None
+        }
+    }
+
+    /// 方向是否配置了轻拨或按住绑定
+    fn has_gesture_binding(&self, direction: &str) -> bool {
+        ["Flick", "Hold"].iter().any(|kind| {
+            self.config
+                .get_button_action(&format!("{}_{}", direction, kind))
+                .is_some()
+        })
+    }
+
+    /// 更新右摇杆轻拨/按住状态，当前方向由轻拨/按住绑定接管时返回 true
+    fn update_stick_gesture(
+        &mut self,
+        direction: Option<&'static str>,
+        now: Instant,
+    ) -> ControllerResult<bool> {
+        // 只跟踪配置了轻拨或按住绑定的方向
+        let direction = direction.filter(|dir| self.has_gesture_binding(dir));
+        let current = self.right_gesture.entered.map(|(dir, _)| dir);
+
+        if current != direction {
+            // 离开原方向：已按住则释放按住动作，否则视为轻拨
+            let gesture = std::mem::take(&mut self.right_gesture);
+            if let Some((dir, _)) = gesture.entered {
+                if gesture.held {
+                    if let Some(action) = gesture.hold_action {
+                        self.execute_action(&action, false)?;
+                    }
+                } else if let Some(action) = self
+                    .config
+                    .get_button_action(&format!("{}_Flick", dir))
+                    .cloned()
+                {
+                    self.tap_action(&action)?;
+                }
+            }
+            self.right_gesture.entered = direction.map(|dir| (dir, now));
+        }
+
+        let Some((dir, entered_at)) = self.right_gesture.entered else {
+            return Ok(false);
+        };
+        let hold_time = Duration::from_millis(self.config.stick_hold_ms);
+        if !self.right_gesture.held && now.saturating_duration_since(entered_at) >= hold_time {
+            self.right_gesture.held = true;
+            let action = self
+                .config
+                .get_button_action(&format!("{}_Hold", dir))
+                .cloned();
+            if let Some(action) = action
+                && self.execute_action(&action, true)?
+            {
+                self.right_gesture.hold_action = Some(action);
+            }
+        }
+        Ok(true)
+    }
+
+    /// 处理右摇杆滚动和导航功能，返回本帧的（垂直, 水平）滚动力度
+    fn handle_right_stick(&mut self, state: &ControllerState) -> ControllerResult<(f64, f64)> {
+        let (rx_abs, ry_abs) = (state.rx.saturating_abs(), state.ry.saturating_abs());
+        let mut current_scroll_power = 0.0;
+        let mut horizontal_scroll_power = 0.0;
+        // 滚动判定使用带中心粘滞的死区
+        let scroll_deadzone = self.right_stick_rest.update(
+            state.rx,
+            state.ry,
+            self.config.right_joystick_deadzone,
+            self.config.deadzone_hysteresis,
+        );
+
+        // 轻拨/按住绑定接管对应方向的默认行为（按住LT时组合键优先）
+        let direction = if self.lt_pressed {
+            None
+        } else {
+            self.right_stick_direction(state, scroll_deadzone)
+        };
+        if self.update_stick_gesture(direction, state.timestamp)? {
+            return Ok((0.0, 0.0));
+        }
+
+        // 检查是否有LT + 右摇杆方向的组合键绑定
+        if self.lt_pressed {
+            // 检查垂直方向 (优先)
+            if ry_abs > scroll_deadzone
+                && (ry_abs as f64 > rx_abs as f64 * self.config.dominant_axis_factor)
+            {
+                let stick_direction = if state.ry > 0 {
+                    "RStick_Down"
+                } else {
+                    "RStick_Up"
+                };
+                let combo = format!("LT+{}", stick_direction);
+
+                if let Some(action) = self.config.get_button_action(&combo).cloned() {
+                    self.lt_tracker.combo_used = true;
+                    // 执行自定义绑定，使用方向标志避免重复触发
+                    if state.ry > 0 && !self.nav_flags.1 {
+                        self.execute_action(&action, true)?;
+                        self.nav_flags.1 = true;
+                    } else if state.ry < 0 && !self.nav_flags.0 {
+                        self.execute_action(&action, true)?;
+                        self.nav_flags.0 = true;
+                    }
+                } else {
+                    // 没有自定义绑定，使用默认滚动行为
+                    let normalized_ry = Self::normalize_joystick_value(
+                        state.ry,
+                        self.config.right_joystick_deadzone,
+                        2.0,
+                    );
+                    current_scroll_power = -normalized_ry * self.config.direct_scroll_sensitivity;
+                }
+            }
+            // 检查水平方向
+            else if rx_abs > self.config.nav_trigger_threshold
+                && (rx_abs as f64 > ry_abs as f64 * self.config.dominant_axis_factor)
+            {
+                let normalized_rx = state.normalized_rx();
+                let stick_direction = if normalized_rx > 0 {
+                    "RStick_Right"
+                } else {
+                    "RStick_Left"
+                };
+                let combo = format!("LT+{}", stick_direction);
+
+                if let Some(action) = self.config.get_button_action(&combo).cloned() {
+                    self.lt_tracker.combo_used = true;
+                    // 执行自定义绑定
+                    if normalized_rx > 0 && !self.nav_flags.1 {
+                        self.execute_action(&action, true)?;
+                        self.nav_flags.1 = true;
+                    } else if normalized_rx < 0 && !self.nav_flags.0 {
+                        self.execute_action(&action, true)?;
+                        self.nav_flags.0 = true;
+                    }
+                } else {
+                    // 没有自定义绑定，使用默认导航行为
+                    if normalized_rx > 0 && !self.nav_flags.1 {
+                        self.navigate(SwipeDirection::Forward)?;
+                        self.nav_flags.1 = true;
+                    } else if normalized_rx < 0 && !self.nav_flags.0 {
+                        self.navigate(SwipeDirection::Back)?;
+                        self.nav_flags.0 = true;
+                    }
+                }
+            }
+        } else {
+            // LT未按下，使用原有的滚动和导航逻辑
+
+            // 滚动（Y轴优先）
+            if ry_abs > scroll_deadzone
+                && (ry_abs as f64 > rx_abs as f64 * self.config.dominant_axis_factor)
+            {
+                let normalized_ry = Self::normalize_joystick_value(
+                    state.ry,
+                    self.config.right_joystick_deadzone,
+                    2.0,
+                );
+                // 反向以实现自然滚动方向
+                current_scroll_power = -normalized_ry * self.config.direct_scroll_sensitivity;
+            }
+
+            // 水平滚动模式：左右推动改为水平滚动，同样需X轴占优，避免斜推时抖动
+            let horizontal_scroll = self.config.right_stick_horizontal_mode
+                == RightStickHorizontalMode::HorizontalScroll;
+            if horizontal_scroll
+                && rx_abs > scroll_deadzone
+                && (rx_abs as f64 > ry_abs as f64 * self.config.dominant_axis_factor)
+            {
+                let normalized_rx = Self::normalize_joystick_value(
+                    state.normalized_rx(),
+                    self.config.right_joystick_deadzone,
+                    2.0,
+                );
+                horizontal_scroll_power = normalized_rx * self.config.direct_scroll_sensitivity;
+            }
+
+            // 导航（X轴优先）- 使用规范化的rx值避免不对称性问题
+            let normalized_rx = state.normalized_rx();
+            if !horizontal_scroll
+                && rx_abs > self.config.nav_trigger_threshold
+                && (rx_abs as f64 > ry_abs as f64 * self.config.dominant_axis_factor)
+            {
+                if normalized_rx > 0 && !self.nav_flags.1 {
+                    self.navigate(SwipeDirection::Forward)?;
+                    self.nav_flags.1 = true;
+                } else if normalized_rx < 0 && !self.nav_flags.0 {
+                    self.navigate(SwipeDirection::Back)?;
+                    self.nav_flags.0 = true;
+                }
+            }
+        }
+
+        // 摇杆回到死区内才重置导航标志，两个轴使用同一阈值，
+        // 避免在导航阈值附近抖动时连续触发
+        if rx_abs < self.config.right_joystick_deadzone
+            && ry_abs < self.config.right_joystick_deadzone
+        {
+            self.nav_flags.1 = false;
+            self.nav_flags.0 = false;
+        }
+
+        Ok((current_scroll_power, horizontal_scroll_power))
+    }
+
+    /// 按配置的导航方式执行页面前进/后退
+    fn navigate(&mut self, direction: SwipeDirection) -> ControllerResult<()> {
+        match self.config.nav_method {
+            NavMethod::Shortcut => self.navigation_shortcut(direction),
+            NavMethod::Swipe => self.swipe(direction),
+        }
+    }
+
+    /// 按配置的平台和主修饰键发送页面前进/后退快捷键
+    fn navigation_shortcut(&mut self, direction: SwipeDirection) -> ControllerResult<()> {
+        let primary = self.primary_modifier()?;
+        let (modifier, key) = navigation_shortcut(self.config.platform, primary, direction);
+        self.execute_shortcut(&[modifier], key)
+    }
+
+    /// 发送翻页滑动手势，输出后端无法合成手势时改用等效的导航快捷键
+    fn swipe(&mut self, direction: SwipeDirection) -> ControllerResult<()> {
+        if !self.output.native_swipe() {
+            return self.navigation_shortcut(direction);
+        }
+        self.output
+            .swipe(direction)
+            .map_err(|e| ControllerError::InputSimulation(format!("滑动手势失败: {}", e)))
+    }
+
+    /// 规范化摇杆值的统一处理函数
+    ///
+    /// 优雅地处理 i16 边界值，避免溢出问题
+    /// 使用 saturating_abs() 自动处理 i16::MIN 溢出
+    fn normalize_joystick_value(value: i16, deadzone: i16, curve_power: f64) -> f64 {
+        let abs_value = value.saturating_abs();
+        let abs_deadzone = deadzone.saturating_abs();
+
+        // 死区内返回0
+        if abs_value <= abs_deadzone {
+            return 0.0;
+        }
+
+        // 计算去除死区后的规范化值 [0.0, 1.0]
+        let max_range = i16::MAX - abs_deadzone;
+        let active_range = abs_value - abs_deadzone;
+        let normalized = active_range as f64 / max_range as f64;
+
+        // 应用指数曲线并恢复符号
+        let curved = normalized.powf(curve_power);
+        if value < 0 { -curved } else { curved }
+    }
+
+    /// 径向死区：按两轴合成的推动幅度扣除死区，再按比例缩放两个分量
+    ///
+    /// 返回的向量方向与摇杆一致，长度为去除死区后规范化的幅度 [0.0, 1.0]。
+    fn normalize_stick_radial(lx: i16, ly: i16, deadzone: i16) -> (f64, f64) {
+        let (x, y) = (lx as f64, ly as f64);
+        let magnitude = x.hypot(y);
+        let deadzone = deadzone.saturating_abs() as f64;
+        if magnitude <= deadzone {
+            return (0.0, 0.0);
+        }
+
+        // 斜向推满时幅度会超过 i16::MAX，截断到 1.0
+        let max_range = i16::MAX as f64 - deadzone;
+        let normalized = ((magnitude - deadzone) / max_range).min(1.0);
+        let scale = normalized / magnitude;
+        (x * scale, y * scale)
+    }
+
+    /// 按加速曲线将摇杆值换算为 [-1.0, 1.0] 的速度系数
+    fn apply_acceleration_curve(value: i16, deadzone: i16, curve: AccelerationCurve) -> f64 {
+        let normalized = Self::normalize_joystick_value(value, deadzone, 1.0);
+        Self::curve_magnitude(normalized.abs(), curve).copysign(normalized)
+    }
+
+    /// 对 [0.0, 1.0] 的推动幅度应用加速曲线
+    fn curve_magnitude(magnitude: f64, curve: AccelerationCurve) -> f64 {
+        match curve {
+            AccelerationCurve::Linear => magnitude,
+            AccelerationCurve::Exponential { power } => magnitude.powf(power),
+            AccelerationCurve::Sigmoid { steepness } => {
+                // 以 0.5 为中心的逻辑斯蒂曲线，缩放到 0 和 1 两端分别取 0 和 1
+                let logistic = |x: f64| 1.0 / (1.0 + (-steepness * (x - 0.5)).exp());
+                let (low, high) = (logistic(0.0), logistic(1.0));
+                (logistic(magnitude) - low) / (high - low)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{OutputEvent, RecordingSink};
+
+    /// 使用 Windows 快捷键的默认配置，断言不随编译平台变化
+    fn test_config() -> ControllerConfig {
+        ControllerConfig {
+            platform: Platform::Windows,
+            ..ControllerConfig::default()
+        }
+    }
+
+    fn handler(config: ControllerConfig) -> (InputHandler, RecordingSink) {
+        let sink = RecordingSink::new();
+        let handler = InputHandler::with_output(Box::new(sink.clone()), config).unwrap();
+        (handler, sink)
+    }
+
+    fn state(buttons: &[u8]) -> ControllerState {
+        ControllerState {
+            lx: 0,
+            ly: 0,
+            rx: 0,
+            ry: 0,
+            lt: 0,
+            rt: 0,
+            gyro_yaw: 0,
+            gyro_pitch: 0,
+            pressed_buttons: buttons.iter().copied().collect(),
+            tapped_buttons: HashSet::new(),
+            timestamp: Instant::now(),
+        }
+    }
+
+    fn scroll_state() -> Arc<Mutex<ScrollState>> {
+        Arc::new(Mutex::new(ScrollState::default()))
+    }
+
+    #[test]
+    fn left_click_follows_button_press_and_release() {
+        let (mut handler, sink) = handler(test_config());
+        let scroll = scroll_state();
+
+        handler.handle_input(&state(&[BUTTON_A]), &scroll).unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![OutputEvent::Button(EnigoButton::Left, Press)]
+        );
+
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![OutputEvent::Button(EnigoButton::Left, Release)]
+        );
+    }
+
+    #[test]
+    fn lt_combo_sends_shortcut_instead_of_single_binding() {
+        let (mut handler, sink) = handler(test_config());
+        let scroll = scroll_state();
+
+        let mut pressed = state(&[BUTTON_X]);
+        pressed.lt = 255;
+        handler.handle_input(&pressed, &scroll).unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![
+                OutputEvent::Key(Key::Control, Press),
+                OutputEvent::Key(Key::Unicode('q'), Click),
+                OutputEvent::Key(Key::Control, Release),
+            ]
+        );
+    }
+
+    #[test]
+    fn unbound_button_sends_nothing() {
+        let (mut handler, sink) = handler(test_config());
+        let scroll = scroll_state();
+
+        handler.handle_input(&state(&[DPAD_DOWN]), &scroll).unwrap();
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        assert!(sink.take().is_empty());
+    }
+
+    #[test]
+    fn left_stick_moves_cursor_within_screen() {
+        let (mut handler, sink) = handler(test_config());
+        let scroll = scroll_state();
+
+        let mut pushed = state(&[]);
+        pushed.lx = i16::MAX;
+        handler.handle_input(&pushed, &scroll).unwrap();
+        let (center_x, center_y) = (RecordingSink::DISPLAY.0 / 2, RecordingSink::DISPLAY.1 / 2);
+        match sink.take().as_slice() {
+            [OutputEvent::Move(x, y, Coordinate::Abs)] => {
+                assert!(*x > center_x);
+                assert_eq!(*y, center_y);
+            }
+            events => panic!("unexpected output: {:?}", events),
+        }
+
+        // 摇杆在死区内时不移动光标
+        let mut resting = state(&[]);
+        resting.lx = 500;
+        handler.handle_input(&resting, &scroll).unwrap();
+        assert!(sink.take().is_empty());
+    }
+
+    #[test]
+    fn right_stick_sets_scroll_power() {
+        let (mut handler, sink) = handler(test_config());
+        let scroll = scroll_state();
+
+        let mut pushed = state(&[]);
+        pushed.ry = -i16::MAX;
+        handler.handle_input(&pushed, &scroll).unwrap();
+        let power = scroll.lock().unwrap().power;
+        assert_eq!(power, handler.config.direct_scroll_sensitivity);
+        assert!(sink.take().is_empty());
+
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        assert_eq!(scroll.lock().unwrap().power, 0.0);
+    }
+
+    #[test]
+    fn right_stick_navigates_once_per_push() {
+        let (mut handler, sink) = handler(test_config());
+        let scroll = scroll_state();
+
+        let mut pushed = state(&[]);
+        pushed.rx = i16::MAX;
+        handler.handle_input(&pushed, &scroll).unwrap();
+        handler.handle_input(&pushed, &scroll).unwrap();
+        let navigate_forward = vec![
+            OutputEvent::Key(Key::Alt, Press),
+            OutputEvent::Key(Key::RightArrow, Click),
+            OutputEvent::Key(Key::Alt, Release),
+        ];
+        assert_eq!(sink.take(), navigate_forward);
+
+        // 回中后再次推动重新触发
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        handler.handle_input(&pushed, &scroll).unwrap();
+        assert_eq!(sink.take(), navigate_forward);
+    }
+
+    #[test]
+    fn macro_delay_resumes_from_timers_without_blocking() {
+        let mut config = test_config();
+        config.dry_run = true; // LaunchApp 只打印，不启动进程
+        config.button_mappings.insert(
+            "Share".to_string(),
+            ButtonAction::Macro(vec![
+                MacroStep::KeyShortcut {
+                    modifiers: vec!["ctrl".to_string()],
+                    key: "c".to_string(),
+                },
+                MacroStep::Delay(100),
+                MacroStep::Action(ButtonAction::LaunchApp("Preview".to_string())),
+                MacroStep::TypeText("done".to_string()),
+            ]),
+        );
+        let (mut handler, sink) = handler(config);
+        let scroll = scroll_state();
+
+        let pressed = state(&[BUTTON_SHARE]);
+        let started = Instant::now();
+        handler.handle_input(&pressed, &scroll).unwrap();
+        assert!(started.elapsed() < Duration::from_millis(100));
+        assert_eq!(
+            sink.take(),
+            vec![
+                OutputEvent::Key(Key::Control, Press),
+                OutputEvent::Key(Key::Unicode('c'), Click),
+                OutputEvent::Key(Key::Control, Release),
+            ]
+        );
+
+        handler
+            .handle_timers(pressed.timestamp + Duration::from_millis(50))
+            .unwrap();
+        assert!(sink.take().is_empty());
+
+        handler
+            .handle_timers(pressed.timestamp + Duration::from_millis(200))
+            .unwrap();
+        assert_eq!(sink.take(), vec![OutputEvent::Text("done".to_string())]);
+    }
+
+    #[test]
+    fn release_all_cancels_waiting_macro() {
+        let mut config = test_config();
+        config.button_mappings.insert(
+            "Share".to_string(),
+            ButtonAction::Macro(vec![
+                MacroStep::Delay(10),
+                MacroStep::TypeText("late".to_string()),
+            ]),
+        );
+        let (mut handler, sink) = handler(config);
+        let pressed = state(&[BUTTON_SHARE]);
+        handler.handle_input(&pressed, &scroll_state()).unwrap();
+        handler.release_all().unwrap();
+
+        handler
+            .handle_timers(pressed.timestamp + Duration::from_secs(1))
+            .unwrap();
+        assert!(sink.take().is_empty());
+    }
+
+    #[test]
+    fn share_is_unbound_by_default() {
+        let (mut handler, sink) = handler(test_config());
+        let scroll = scroll_state();
+        handler
+            .handle_input(&state(&[BUTTON_SHARE]), &scroll)
+            .unwrap();
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        assert!(sink.take().is_empty());
+    }
+
+    #[test]
+    fn release_all_releases_held_click() {
+        let (mut handler, sink) = handler(test_config());
+        let scroll = scroll_state();
+
+        handler.handle_input(&state(&[BUTTON_B]), &scroll).unwrap();
+        sink.take();
+        handler.release_all().unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![OutputEvent::Button(EnigoButton::Right, Release)]
+        );
+    }
+
+    #[test]
+    fn zoom_button_marks_scroll_as_zoom_only_while_scrolling() {
+        let mut config = test_config();
+        config.zoom_scroll_button = Some("DPad_Down".to_string());
+        let (mut handler, _sink) = handler(config);
+        let scroll = scroll_state();
+
+        let mut scrolling = state(&[DPAD_DOWN]);
+        scrolling.ry = i16::MAX;
+        handler.handle_input(&scrolling, &scroll).unwrap();
+        assert!(scroll.lock().unwrap().zoom);
+
+        // 只按住修饰键不推摇杆时不缩放，避免单独按住 Cmd
+        handler.handle_input(&state(&[DPAD_DOWN]), &scroll).unwrap();
+        assert!(!scroll.lock().unwrap().zoom);
+
+        scrolling.pressed_buttons.clear();
+        handler.handle_input(&scrolling, &scroll).unwrap();
+        assert!(!scroll.lock().unwrap().zoom);
+    }
+
+    fn lone_lt_config() -> ControllerConfig {
+        let mut config = test_config();
+        config.button_mappings.insert(
+            "LT".to_string(),
+            ButtonAction::Macro(vec![MacroStep::TypeText("lt".to_string())]),
+        );
+        config
+    }
+
+    /// 按下 LT（同时按住 `buttons`）后经过 `held` 松开
+    fn tap_lt(handler: &mut InputHandler, held: Duration, buttons: &[u8]) {
+        let scroll = scroll_state();
+        let mut pressed = state(buttons);
+        pressed.lt = 255;
+        handler.handle_input(&pressed, &scroll).unwrap();
+        let mut released = state(&[]);
+        released.timestamp = pressed.timestamp + held;
+        handler.handle_input(&released, &scroll).unwrap();
+    }
+
+    #[test]
+    fn lone_trigger_tap_fires_its_binding() {
+        let (mut handler, sink) = handler(lone_lt_config());
+        tap_lt(&mut handler, Duration::from_millis(100), &[]);
+        assert_eq!(sink.take(), vec![OutputEvent::Text("lt".to_string())]);
+    }
+
+    #[test]
+    fn long_trigger_hold_is_not_a_tap() {
+        let (mut handler, sink) = handler(lone_lt_config());
+        tap_lt(&mut handler, Duration::from_millis(400), &[]);
+        assert!(sink.take().is_empty());
+    }
+
+    #[test]
+    fn trigger_used_as_modifier_is_not_a_tap() {
+        let (mut handler, sink) = handler(lone_lt_config());
+        tap_lt(&mut handler, Duration::from_millis(100), &[BUTTON_X]);
+        let events = sink.take();
+        assert!(!events.contains(&OutputEvent::Text("lt".to_string())));
+        assert!(events.contains(&OutputEvent::Key(Key::Unicode('q'), Click)));
+    }
+
+    #[test]
+    fn scroll_to_top_sends_configured_shortcut() {
+        let mut config = test_config();
+        config.shortcuts.scroll_to_top = Shortcut {
+            modifiers: vec!["ctrl".to_string()],
+            key: "home".to_string(),
+            presses: 1,
+        };
+        config
+            .button_mappings
+            .insert("DPad_Up".to_string(), ButtonAction::ScrollToTop);
+        let (mut handler, sink) = handler(config);
+
+        handler
+            .handle_input(&state(&[DPAD_UP]), &scroll_state())
+            .unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![
+                OutputEvent::Key(Key::Control, Press),
+                OutputEvent::Key(Key::Home, Click),
+                OutputEvent::Key(Key::Control, Release),
+            ]
+        );
+    }
+
+    #[test]
+    fn tap_within_one_frame_still_clicks() {
+        let (mut handler, sink) = handler(test_config());
+        let mut tapped = state(&[]);
+        tapped.tapped_buttons.insert(BUTTON_A);
+        handler.handle_input(&tapped, &scroll_state()).unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![
+                OutputEvent::Button(EnigoButton::Left, Press),
+                OutputEvent::Button(EnigoButton::Left, Release),
+            ]
+        );
+    }
+
+    #[test]
+    fn release_of_held_button_is_not_a_tap() {
+        let (mut handler, sink) = handler(test_config());
+        let scroll = scroll_state();
+        handler.handle_input(&state(&[BUTTON_A]), &scroll).unwrap();
+        sink.take();
+
+        let mut released = state(&[]);
+        released.tapped_buttons.insert(BUTTON_A);
+        handler.handle_input(&released, &scroll).unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![OutputEvent::Button(EnigoButton::Left, Release)]
+        );
+    }
+
+    #[test]
+    fn short_click_release_is_delayed_without_blocking() {
+        let mut config = test_config();
+        config.min_click_hold_ms = 200;
+        let (mut handler, sink) = handler(config);
+        let scroll = scroll_state();
+
+        let pressed = state(&[BUTTON_A]);
+        let started = Instant::now();
+        handler.handle_input(&pressed, &scroll).unwrap();
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        assert!(started.elapsed() < Duration::from_millis(200));
+        assert_eq!(
+            sink.take(),
+            vec![OutputEvent::Button(EnigoButton::Left, Press)]
+        );
+
+        handler
+            .handle_timers(pressed.timestamp + Duration::from_millis(250))
+            .unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![OutputEvent::Button(EnigoButton::Left, Release)]
+        );
+    }
+
+    #[test]
+    fn new_click_flushes_pending_release_first() {
+        let mut config = test_config();
+        config.min_click_hold_ms = 1000;
+        let (mut handler, sink) = handler(config);
+        let scroll = scroll_state();
+
+        handler.handle_input(&state(&[BUTTON_A]), &scroll).unwrap();
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        handler.handle_input(&state(&[BUTTON_A]), &scroll).unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![
+                OutputEvent::Button(EnigoButton::Left, Press),
+                OutputEvent::Button(EnigoButton::Left, Release),
+                OutputEvent::Button(EnigoButton::Left, Press),
+            ]
+        );
+    }
+
+    #[test]
+    fn trigger_stage_uses_hysteresis_when_falling() {
+        let config = TriggerStageConfig::default(); // 轻按 60，按到底 230，回差 12
+        let stage = TriggerStage::Released.next(59, &config);
+        assert_eq!(stage, TriggerStage::Released);
+        let stage = stage.next(60, &config);
+        assert_eq!(stage, TriggerStage::Light);
+        let stage = stage.next(240, &config);
+        assert_eq!(stage, TriggerStage::Full);
+        // 回落到阈值以下但仍在回差内时保持
+        assert_eq!(stage.next(220, &config), TriggerStage::Full);
+        assert_eq!(stage.next(217, &config), TriggerStage::Light);
+        assert_eq!(TriggerStage::Light.next(50, &config), TriggerStage::Light);
+        assert_eq!(
+            TriggerStage::Light.next(47, &config),
+            TriggerStage::Released
+        );
+    }
+
+    #[test]
+    fn pressing_trigger_fully_fires_light_then_full() {
+        let mut config = test_config();
+        config.trigger_stages.enabled = true;
+        for (name, text) in [("LT_Light", "light"), ("LT_Full", "full")] {
+            config.button_mappings.insert(
+                name.to_string(),
+                ButtonAction::Macro(vec![MacroStep::TypeText(text.to_string())]),
+            );
+        }
+        let (mut handler, sink) = handler(config);
+        let scroll = scroll_state();
+
+        let mut pulled = state(&[]);
+        pulled.lt = 255;
+        handler.handle_input(&pulled, &scroll).unwrap();
+        handler.handle_input(&pulled, &scroll).unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![
+                OutputEvent::Text("light".to_string()),
+                OutputEvent::Text("full".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn actions_beyond_rate_limit_are_dropped() {
+        let mut config = test_config();
+        config.max_actions_per_second = 2;
+        let (mut handler, sink) = handler(config);
+        let scroll = scroll_state();
+
+        for _ in 0..3 {
+            handler.handle_input(&state(&[BUTTON_A]), &scroll).unwrap();
+            handler.handle_input(&state(&[]), &scroll).unwrap();
+        }
+        let presses = sink
+            .take()
+            .into_iter()
+            .filter(|event| *event == OutputEvent::Button(EnigoButton::Left, Press))
+            .count();
+        assert_eq!(presses, 2);
+    }
+
+    /// 按住 LT（启用陀螺仪）并带有陀螺仪读数的状态
+    fn gyro_state(yaw: i16, pitch: i16) -> ControllerState {
+        let mut state = state(&[]);
+        state.lt = 255;
+        state.gyro_yaw = yaw;
+        state.gyro_pitch = pitch;
+        state
+    }
+
+    #[test]
+    fn gyro_yaw_moves_cursor_by_default() {
+        let (mut handler, sink) = handler(test_config());
+        handler
+            .handle_input(&gyro_state(1000, 0), &scroll_state())
+            .unwrap();
+        let (center_x, center_y) = (RecordingSink::DISPLAY.0 / 2, RecordingSink::DISPLAY.1 / 2);
+        match sink.take().as_slice() {
+            [OutputEvent::Move(x, y, Coordinate::Abs)] => {
+                assert!(*x > center_x);
+                assert_eq!(*y, center_y);
+            }
+            events => panic!("unexpected output: {:?}", events),
+        }
+    }
+
+    #[test]
+    fn gyro_axis_routed_to_scroll_does_not_move_cursor() {
+        let mut config = test_config();
+        config.gyro_yaw_target = GyroTarget::ScrollH;
+        config.gyro_pitch_target = GyroTarget::ScrollV;
+        let (mut handler, sink) = handler(config);
+        let scroll = scroll_state();
+
+        handler
+            .handle_input(&gyro_state(1000, 500), &scroll)
+            .unwrap();
+        assert!(sink.take().is_empty());
+        let scroll = *scroll.lock().unwrap();
+        assert!(scroll.horizontal > 0.0);
+        // 俯仰轴正值（向上转动）向上滚动，滚动力度为负
+        assert!(scroll.power < 0.0);
+    }
+
+    #[test]
+    fn gyro_is_ignored_without_lt() {
+        let (mut handler, sink) = handler(test_config());
+        let mut state = gyro_state(1000, 1000);
+        state.lt = 0;
+        handler.handle_input(&state, &scroll_state()).unwrap();
+        assert!(sink.take().is_empty());
+    }
+
+    #[test]
+    fn released_outputs_are_not_released_again_after_reconnect() {
+        let mut config = test_config();
+        config
+            .button_mappings
+            .insert("X".to_string(), ButtonAction::HoldKey("shift".to_string()));
+        let (mut handler, sink) = handler(config);
+        let scroll = scroll_state();
+
+        handler
+            .handle_input(&state(&[BUTTON_B, BUTTON_X]), &scroll)
+            .unwrap();
+        sink.take();
+        handler.release_all().unwrap();
+        let released = sink.take();
+        assert!(released.contains(&OutputEvent::Button(EnigoButton::Right, Release)));
+        assert!(released.contains(&OutputEvent::Key(Key::Shift, Release)));
+
+        // 重连后的第一帧按键已松开，不再重复释放
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        assert!(sink.take().is_empty());
+    }
+
+    #[test]
+    fn relative_backend_receives_relative_moves() {
+        let sink = RecordingSink::relative();
+        let mut handler = InputHandler::with_output(Box::new(sink.clone()), test_config()).unwrap();
+        let mut pushed = state(&[]);
+        pushed.lx = i16::MAX;
+        handler.handle_input(&pushed, &scroll_state()).unwrap();
+        match sink.take().as_slice() {
+            [OutputEvent::Move(dx, 0, Coordinate::Rel)] => assert!(*dx > 0),
+            events => panic!("unexpected output: {:?}", events),
+        }
+    }
+
+    #[test]
+    fn grid_snap_keeps_absolute_moves_on_relative_backend() {
+        let mut config = test_config();
+        config.grid_snap.enabled = true;
+        let sink = RecordingSink::relative();
+        let mut handler = InputHandler::with_output(Box::new(sink.clone()), config).unwrap();
+        let mut pushed = state(&[]);
+        pushed.lx = i16::MAX;
+        handler.handle_input(&pushed, &scroll_state()).unwrap();
+        assert!(matches!(
+            sink.take().as_slice(),
+            [OutputEvent::Move(_, _, Coordinate::Abs)]
+        ));
+    }
+
+    #[test]
+    fn sticky_deadzone_widens_only_while_resting() {
+        let mut rest = StickyDeadzone::default();
+        assert_eq!(rest.update(900, 0, 1000, 200), 1000);
+        // 静止后需超出死区加回差才算离开
+        assert_eq!(rest.update(1100, 0, 1000, 200), 1200);
+        assert_eq!(rest.update(1300, 0, 1000, 200), 1200);
+        // 离开后恢复原死区
+        assert_eq!(rest.update(1100, 0, 1000, 200), 1000);
+    }
+
+    #[test]
+    fn resting_stick_noise_within_hysteresis_does_not_move_cursor() {
+        let mut config = test_config();
+        config.deadzone_hysteresis = 500;
+        let (mut handler, sink) = handler(config);
+        let scroll = scroll_state();
+
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        let mut noisy = state(&[]);
+        noisy.lx = 1400;
+        handler.handle_input(&noisy, &scroll).unwrap();
+        assert!(sink.take().is_empty());
+    }
+
+    #[test]
+    fn paste_plain_sends_configured_shortcut() {
+        let mut config = test_config();
+        config.shortcuts.paste_plain = Shortcut {
+            modifiers: vec!["ctrl".to_string(), "shift".to_string()],
+            key: "v".to_string(),
+            presses: 1,
+        };
+        config
+            .button_mappings
+            .insert("DPad_Down".to_string(), ButtonAction::PastePlain);
+        let (mut handler, sink) = handler(config);
+
+        handler
+            .handle_input(&state(&[DPAD_DOWN]), &scroll_state())
+            .unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![
+                OutputEvent::Key(Key::Control, Press),
+                OutputEvent::Key(Key::Shift, Press),
+                OutputEvent::Key(Key::Unicode('v'), Click),
+                OutputEvent::Key(Key::Shift, Release),
+                OutputEvent::Key(Key::Control, Release),
+            ]
+        );
+    }
+
+    #[test]
+    fn snap_to_grid_rounds_and_stays_on_screen() {
+        assert_eq!(InputHandler::snap_to_grid(14.0, 10, 1919), 10);
+        assert_eq!(InputHandler::snap_to_grid(15.0, 10, 1919), 20);
+        // 超出屏幕时退回上一个网格点
+        assert_eq!(InputHandler::snap_to_grid(1918.0, 10, 1919), 1910);
+    }
+
+    #[test]
+    fn grid_snap_moves_cursor_to_grid_points() {
+        let mut config = test_config();
+        config.grid_snap.enabled = true;
+        config.grid_snap.size = 32;
+        let (mut handler, sink) = handler(config);
+        let mut pushed = state(&[]);
+        pushed.lx = i16::MAX;
+        pushed.ly = i16::MAX;
+        handler.handle_input(&pushed, &scroll_state()).unwrap();
+        match sink.take().as_slice() {
+            [OutputEvent::Move(x, y, Coordinate::Abs)] => {
+                assert_eq!(x % 32, 0);
+                assert_eq!(y % 32, 0);
+            }
+            events => panic!("unexpected output: {:?}", events),
+        }
+    }
+
+    #[test]
+    fn repeat_last_reissues_previous_action() {
+        let mut config = test_config();
+        config
+            .button_mappings
+            .insert("DPad_Down".to_string(), ButtonAction::RepeatLast);
+        config.button_mappings.insert(
+            "DPad_Left".to_string(),
+            ButtonAction::Macro(vec![MacroStep::TypeText("hi".to_string())]),
+        );
+        let (mut handler, sink) = handler(config);
+        let scroll = scroll_state();
+
+        // 尚未执行过动作时不做任何操作
+        handler.handle_input(&state(&[DPAD_DOWN]), &scroll).unwrap();
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        assert!(sink.take().is_empty());
+
+        handler.handle_input(&state(&[DPAD_LEFT]), &scroll).unwrap();
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        for _ in 0..2 {
+            handler.handle_input(&state(&[DPAD_DOWN]), &scroll).unwrap();
+            handler.handle_input(&state(&[]), &scroll).unwrap();
+        }
+        assert_eq!(sink.take(), vec![OutputEvent::Text("hi".to_string()); 3]);
+    }
+
+    #[test]
+    fn release_uses_action_recorded_at_press() {
+        let mut config = test_config();
+        config
+            .combo_mappings
+            .insert("LT+A".to_string(), ButtonAction::HoldKey("alt".to_string()));
+        let (mut handler, sink) = handler(config);
+        let scroll = scroll_state();
+
+        let mut combo = state(&[BUTTON_A]);
+        combo.lt = 255;
+        handler.handle_input(&combo, &scroll).unwrap();
+        assert_eq!(sink.take(), vec![OutputEvent::Key(Key::Alt, Press)]);
+
+        // 先松开 LT 再松开 A：仍释放按下时的 Alt，而不是 A 的单独绑定
+        handler.handle_input(&state(&[BUTTON_A]), &scroll).unwrap();
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        assert_eq!(sink.take(), vec![OutputEvent::Key(Key::Alt, Release)]);
+    }
+
+    #[test]
+    fn dpad_hold_key_presses_and_releases_arrow() {
+        let mut config = test_config();
+        config.button_mappings.insert(
+            "DPad_Up".to_string(),
+            ButtonAction::HoldKey("up".to_string()),
+        );
+        let (mut handler, sink) = handler(config);
+        let scroll = scroll_state();
+
+        handler.handle_input(&state(&[DPAD_UP]), &scroll).unwrap();
+        assert_eq!(sink.take(), vec![OutputEvent::Key(Key::UpArrow, Press)]);
+
+        // 持续按住不重复发送
+        handler.handle_input(&state(&[DPAD_UP]), &scroll).unwrap();
+        assert!(sink.take().is_empty());
+
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        assert_eq!(sink.take(), vec![OutputEvent::Key(Key::UpArrow, Release)]);
+    }
+
+    #[test]
+    fn dpad_hold_key_released_on_disconnect() {
+        let mut config = test_config();
+        config.button_mappings.insert(
+            "DPad_Left".to_string(),
+            ButtonAction::HoldKey("left".to_string()),
+        );
+        let (mut handler, sink) = handler(config);
+        let scroll = scroll_state();
+
+        handler.handle_input(&state(&[DPAD_LEFT]), &scroll).unwrap();
+        sink.take();
+
+        handler.release_all().unwrap();
+        assert_eq!(sink.take(), vec![OutputEvent::Key(Key::LeftArrow, Release)]);
+    }
+
+    /// 首帧左摇杆推到右侧时光标的水平位移
+    fn first_stick_move_x(config: ControllerConfig, lt: u8) -> i32 {
+        let (mut handler, sink) = handler(config);
+        let mut state = state(&[]);
+        state.lx = i16::MAX;
+        state.lt = lt;
+        handler.handle_input(&state, &scroll_state()).unwrap();
+        match sink.take().as_slice() {
+            [OutputEvent::Move(x, _, Coordinate::Abs)] => *x - RecordingSink::DISPLAY.0 / 2,
+            events => panic!("unexpected output: {:?}", events),
+        }
+    }
+
+    #[test]
+    fn gyro_active_stick_scale_applies_only_while_lt_held() {
+        let mut config = test_config();
+        config.gyro_active_stick_scale = 0.5;
+        let full = first_stick_move_x(config.clone(), 0);
+        let scaled = first_stick_move_x(config, 255);
+        assert!(full > 0);
+        assert!((scaled - full / 2).abs() <= 1, "{} vs {}", scaled, full);
+    }
+
+    #[test]
+    fn gyro_active_stick_scale_defaults_to_no_change() {
+        let full = first_stick_move_x(test_config(), 0);
+        let with_lt = first_stick_move_x(test_config(), 255);
+        assert_eq!(full, with_lt);
+    }
+
+    #[test]
+    fn scroll_power_is_clamped_including_gyro_component() {
+        let mut config = test_config();
+        config.max_scroll_power = 5.0;
+        config.gyro_pitch_target = GyroTarget::ScrollV;
+        let (mut handler, _sink) = handler(config);
+        let scroll = scroll_state();
+
+        let mut pushed = gyro_state(0, -i16::MAX);
+        pushed.ry = -i16::MAX;
+        handler.handle_input(&pushed, &scroll).unwrap();
+        assert_eq!(scroll.lock().unwrap().power, 5.0);
+
+        pushed.ry = i16::MAX;
+        pushed.gyro_pitch = i16::MAX;
+        handler.handle_input(&pushed, &scroll).unwrap();
+        assert_eq!(scroll.lock().unwrap().power, -5.0);
+    }
+
+    #[test]
+    fn zero_max_scroll_power_does_not_limit() {
+        let mut config = test_config();
+        config.direct_scroll_sensitivity = 500.0;
+        let (mut handler, _sink) = handler(config);
+        let scroll = scroll_state();
+
+        let mut pushed = state(&[]);
+        pushed.ry = -i16::MAX;
+        handler.handle_input(&pushed, &scroll).unwrap();
+        assert_eq!(scroll.lock().unwrap().power, 500.0);
+    }
+
+    #[test]
+    fn disabled_binding_does_not_fire() {
+        let mut config = test_config();
+        config.binding_enabled.insert("A".to_string(), false);
+        let (mut handler, sink) = handler(config);
+        let scroll = scroll_state();
+
+        handler.handle_input(&state(&[BUTTON_A]), &scroll).unwrap();
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        assert!(sink.take().is_empty());
+        assert!(handler.config.button_mappings.contains_key("A"));
+    }
+
+    fn stick_click_config() -> ControllerConfig {
+        let mut config = test_config();
+        config.stick_click_guard_threshold = 10000;
+        config
+            .button_mappings
+            .insert("L3".to_string(), ButtonAction::LeftClick);
+        config
+            .button_mappings
+            .insert("R3".to_string(), ButtonAction::RightClick);
+        config
+    }
+
+    #[test]
+    fn stick_click_is_suppressed_while_stick_is_moving() {
+        let (mut handler, sink) = handler(stick_click_config());
+        let scroll = scroll_state();
+
+        let mut aiming = state(&[BUTTON_R3]);
+        aiming.ry = 20000;
+        handler.handle_input(&aiming, &scroll).unwrap();
+        aiming.pressed_buttons.clear();
+        handler.handle_input(&aiming, &scroll).unwrap();
+        assert!(
+            !sink
+                .take()
+                .iter()
+                .any(|event| matches!(event, OutputEvent::Button(..)))
+        );
+    }
+
+    #[test]
+    fn stick_click_is_allowed_when_stick_is_still() {
+        let (mut handler, sink) = handler(stick_click_config());
+        let scroll = scroll_state();
+
+        let mut click = state(&[BUTTON_L3]);
+        // 低于阈值的轻微偏移仍视为有意按下
+        click.lx = 5000;
+        handler.handle_input(&click, &scroll).unwrap();
+        assert!(
+            sink.take()
+                .contains(&OutputEvent::Button(EnigoButton::Left, Press))
+        );
+    }
+
+    #[test]
+    fn executed_actions_are_written_to_action_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("actions.log");
+        let config = ControllerConfig {
+            action_log_path: Some(path.to_string_lossy().into_owned()),
+            ..test_config()
+        };
+        let (mut handler, _sink) = handler(config);
+        let scroll = scroll_state();
+
+        handler.handle_input(&state(&[BUTTON_A]), &scroll).unwrap();
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+
+        let log = std::fs::read_to_string(&path).unwrap();
+        assert!(
+            log.lines().any(|line| line.ends_with(" LeftClick 成功")),
+            "{}",
+            log
+        );
+    }
+
+    /// 以一个参考帧间隔处理一帧，返回本帧的水平相对位移（没有移动时为 0）
+    fn relative_frame_x(
+        handler: &mut InputHandler,
+        sink: &RecordingSink,
+        state: &ControllerState,
+    ) -> i32 {
+        handler.last_movement_at = Some(Instant::now() - MOVEMENT_REFERENCE_INTERVAL);
+        handler.handle_input(state, &scroll_state()).unwrap();
+        match sink.take().as_slice() {
+            [] => 0,
+            [OutputEvent::Move(x, _, Coordinate::Rel)] => *x,
+            events => panic!("unexpected output: {:?}", events),
+        }
+    }
+
+    fn momentum_handler(enabled: bool) -> (InputHandler, RecordingSink) {
+        let mut config = test_config();
+        config.cursor_momentum.enabled = enabled;
+        config.cursor_momentum.decay = 0.5;
+        let sink = RecordingSink::relative();
+        let handler = InputHandler::with_output(Box::new(sink.clone()), config).unwrap();
+        (handler, sink)
+    }
+
+    #[test]
+    fn cursor_momentum_glides_with_diminishing_moves() {
+        let (mut handler, sink) = momentum_handler(true);
+        let mut pushed = state(&[]);
+        pushed.lx = i16::MAX;
+        let pushed_move = relative_frame_x(&mut handler, &sink, &pushed);
+        assert!(pushed_move > 0);
+
+        let moves: Vec<i32> = (0..12)
+            .map(|_| relative_frame_x(&mut handler, &sink, &state(&[])))
+            .collect();
+        assert!(moves[0] > 0 && moves[0] < pushed_move, "{:?}", moves);
+        assert!(
+            moves.windows(2).all(|pair| pair[1] <= pair[0]),
+            "{:?}",
+            moves
+        );
+        assert_eq!(*moves.last().unwrap(), 0, "{:?}", moves);
+    }
+
+    #[test]
+    fn cursor_stops_immediately_without_momentum() {
+        let (mut handler, sink) = momentum_handler(false);
+        let mut pushed = state(&[]);
+        pushed.lx = i16::MAX;
+        assert!(relative_frame_x(&mut handler, &sink, &pushed) > 0);
+        assert_eq!(relative_frame_x(&mut handler, &sink, &state(&[])), 0);
+    }
+
+    fn trigger_state(lt: u8, rt: u8) -> ControllerState {
+        let mut state = state(&[]);
+        state.lt = lt;
+        state.rt = rt;
+        state
+    }
+
+    #[test]
+    fn trigger_release_binding_fires_on_release_edge_only() {
+        let mut config = test_config();
+        config.button_mappings.insert(
+            "RT_Release".to_string(),
+            ButtonAction::HoldKey("f5".to_string()),
+        );
+        let (mut handler, sink) = handler(config);
+        let scroll = scroll_state();
+        let f5 = [
+            OutputEvent::Key(Key::F5, Press),
+            OutputEvent::Key(Key::F5, Release),
+        ];
+
+        // 启动时扳机未按下，不触发
+        handler.handle_input(&trigger_state(0, 0), &scroll).unwrap();
+        assert!(sink.take().is_empty());
+
+        // 按住期间不触发
+        handler
+            .handle_input(&trigger_state(0, 255), &scroll)
+            .unwrap();
+        handler
+            .handle_input(&trigger_state(0, 200), &scroll)
+            .unwrap();
+        assert!(sink.take().is_empty());
+
+        // 回落到阈值以下时触发一次
+        handler.handle_input(&trigger_state(0, 0), &scroll).unwrap();
+        assert_eq!(sink.take(), f5);
+        handler.handle_input(&trigger_state(0, 0), &scroll).unwrap();
+        assert!(sink.take().is_empty());
+    }
+
+    #[test]
+    fn lt_release_binding_is_independent_of_rt() {
+        let mut config = test_config();
+        config.button_mappings.insert(
+            "LT_Release".to_string(),
+            ButtonAction::HoldKey("f6".to_string()),
+        );
+        let (mut handler, sink) = handler(config);
+        let scroll = scroll_state();
+
+        handler
+            .handle_input(&trigger_state(0, 255), &scroll)
+            .unwrap();
+        handler.handle_input(&trigger_state(0, 0), &scroll).unwrap();
+        assert!(sink.take().is_empty());
+
+        handler
+            .handle_input(&trigger_state(255, 0), &scroll)
+            .unwrap();
+        handler.handle_input(&trigger_state(0, 0), &scroll).unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![
+                OutputEvent::Key(Key::F6, Press),
+                OutputEvent::Key(Key::F6, Release),
+            ]
+        );
+    }
+
+    /// 左摇杆轻推（每帧约 1.25 像素）若干帧，返回每帧的输出事件
+    fn slow_push_frames(
+        config: ControllerConfig,
+        sink: RecordingSink,
+        frames: usize,
+    ) -> Vec<Vec<OutputEvent>> {
+        let mut handler = InputHandler::with_output(Box::new(sink.clone()), config).unwrap();
+        let mut pushed = state(&[]);
+        pushed.lx = 10000;
+        (0..frames)
+            .map(|_| {
+                handler.last_movement_at = Some(Instant::now() - MOVEMENT_REFERENCE_INTERVAL);
+                handler.handle_input(&pushed, &scroll_state()).unwrap();
+                sink.take()
+            })
+            .collect()
+    }
+
+    fn relative_moves(frames: &[Vec<OutputEvent>]) -> Vec<i32> {
+        frames
+            .iter()
+            .map(|events| match events.as_slice() {
+                [] => 0,
+                [OutputEvent::Move(x, _, Coordinate::Rel)] => *x,
+                events => panic!("unexpected output: {:?}", events),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn min_output_pixels_holds_back_small_moves_without_losing_them() {
+        let ungated = relative_moves(&slow_push_frames(
+            test_config(),
+            RecordingSink::relative(),
+            16,
+        ));
+        let mut config = test_config();
+        config.min_output_pixels = 4;
+        let gated = relative_moves(&slow_push_frames(config, RecordingSink::relative(), 16));
+
+        assert!(ungated.iter().all(|&x| x <= 2), "{:?}", ungated);
+        assert_eq!(gated[0], 0);
+        assert!(gated.iter().all(|&x| x == 0 || x >= 4), "{:?}", gated);
+        // 暂不移动的位移累积到后续帧，总位移与不限制时相差不到一个阈值
+        let (ungated_total, gated_total) = (ungated.iter().sum::<i32>(), gated.iter().sum::<i32>());
+        assert!(
+            (ungated_total - gated_total).abs() < 4,
+            "{} vs {}",
+            ungated_total,
+            gated_total
+        );
+    }
+
+    #[test]
+    fn grid_snap_keeps_movement_held_back_by_min_output_pixels() {
+        let free = RecordingSink::new();
+        let start = OutputSink::location(&free).unwrap().0;
+        slow_push_frames(test_config(), free.clone(), 24);
+        let free_distance = OutputSink::location(&free).unwrap().0 - start;
+
+        let mut config = test_config();
+        config.min_output_pixels = 4;
+        config.grid_snap.enabled = true;
+        config.grid_snap.size = 10;
+        let snapped = RecordingSink::new();
+        slow_push_frames(config, snapped.clone(), 24);
+        let snapped_distance = OutputSink::location(&snapped).unwrap().0 - start;
+
+        // 吸附按累积后的整数位移计算，不会因跳过的帧而变慢
+        assert!(free_distance >= 20, "{}", free_distance);
+        assert!(
+            (free_distance - snapped_distance).abs() <= 10,
+            "{} vs {}",
+            free_distance,
+            snapped_distance
+        );
+    }
+
+    #[test]
+    fn toggle_fullscreen_sends_configured_shortcut_on_press() {
+        let mut config = test_config();
+        config.shortcuts.toggle_fullscreen = Shortcut {
+            modifiers: vec!["ctrl".to_string(), "cmd".to_string()],
+            key: "f".to_string(),
+            presses: 1,
+        };
+        let (mut handler, sink) = handler(config);
+        let scroll = scroll_state();
+
+        handler
+            .handle_input(&state(&[BUTTON_START]), &scroll)
+            .unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![
+                OutputEvent::Key(Key::Control, Press),
+                OutputEvent::Key(Key::Meta, Press),
+                OutputEvent::Key(Key::Unicode('f'), Click),
+                OutputEvent::Key(Key::Meta, Release),
+                OutputEvent::Key(Key::Control, Release),
+            ]
+        );
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        assert!(sink.take().is_empty());
+    }
+
+    /// 两个摇杆同时推动一帧，返回 (光标是否移动, 滚动力度)
+    fn both_sticks_frame(policy: DualStickPolicy, left: bool, right: bool) -> (bool, f64) {
+        let mut config = test_config();
+        config.dual_stick_policy = policy;
+        let (mut handler, sink) = handler(config);
+        let scroll = scroll_state();
+
+        let mut pushed = state(&[]);
+        if left {
+            pushed.lx = i16::MAX;
+        }
+        if right {
+            pushed.ry = -i16::MAX;
+        }
+        handler.handle_input(&pushed, &scroll).unwrap();
+        let moved = sink
+            .take()
+            .iter()
+            .any(|event| matches!(event, OutputEvent::Move(..)));
+        let power = scroll.lock().unwrap().power;
+        (moved, power)
+    }
+
+    #[test]
+    fn dual_stick_policies_with_both_sticks_active() {
+        let (moved, power) = both_sticks_frame(DualStickPolicy::Independent, true, true);
+        assert!(moved && power > 0.0);
+
+        let (moved, power) = both_sticks_frame(DualStickPolicy::LeftPriority, true, true);
+        assert!(moved);
+        assert_eq!(power, 0.0);
+
+        let (moved, power) = both_sticks_frame(DualStickPolicy::RightPriority, true, true);
+        assert!(!moved && power > 0.0);
+    }
+
+    #[test]
+    fn dual_stick_policy_ignores_single_stick() {
+        let (moved, _) = both_sticks_frame(DualStickPolicy::RightPriority, true, false);
+        assert!(moved);
+        let (_, power) = both_sticks_frame(DualStickPolicy::LeftPriority, false, true);
+        assert!(power > 0.0);
+    }
+
+    #[test]
+    fn navigation_rearms_only_after_stick_returns_to_deadzone() {
+        let (mut handler, sink) = handler(test_config());
+        let scroll = scroll_state();
+        let right = |rx: i16| {
+            let mut state = state(&[]);
+            state.rx = rx;
+            state
+        };
+
+        handler.handle_input(&right(i16::MAX), &scroll).unwrap();
+        assert!(!sink.take().is_empty());
+
+        // 回落到导航阈值以下但仍在死区外，再次推到底不重复导航
+        handler.handle_input(&right(20000), &scroll).unwrap();
+        handler.handle_input(&right(i16::MAX), &scroll).unwrap();
+        assert!(sink.take().is_empty());
+
+        handler.handle_input(&right(0), &scroll).unwrap();
+        handler.handle_input(&right(i16::MAX), &scroll).unwrap();
+        assert!(!sink.take().is_empty());
+    }
+
+    fn gesture_config() -> ControllerConfig {
+        let mut config = test_config();
+        config.stick_hold_ms = 200;
+        config.button_mappings.insert(
+            "RStick_Right_Flick".to_string(),
+            ButtonAction::HoldKey("f7".to_string()),
+        );
+        config.button_mappings.insert(
+            "RStick_Right_Hold".to_string(),
+            ButtonAction::HoldKey("shift".to_string()),
+        );
+        config
+    }
+
+    fn right_stick_at(rx: i16, at: Instant) -> ControllerState {
+        let mut state = state(&[]);
+        state.rx = rx;
+        state.timestamp = at;
+        state
+    }
+
+    #[test]
+    fn stick_flick_released_before_hold_time_fires_flick() {
+        let (mut handler, sink) = handler(gesture_config());
+        let scroll = scroll_state();
+        let start = Instant::now();
+
+        handler
+            .handle_input(&right_stick_at(i16::MAX, start), &scroll)
+            .unwrap();
+        handler
+            .handle_input(
+                &right_stick_at(i16::MAX, start + Duration::from_millis(199)),
+                &scroll,
+            )
+            .unwrap();
+        assert!(sink.take().is_empty());
+
+        handler
+            .handle_input(
+                &right_stick_at(0, start + Duration::from_millis(199)),
+                &scroll,
+            )
+            .unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![
+                OutputEvent::Key(Key::F7, Press),
+                OutputEvent::Key(Key::F7, Release),
+            ]
+        );
+    }
+
+    #[test]
+    fn stick_held_to_hold_time_switches_to_hold_action() {
+        let (mut handler, sink) = handler(gesture_config());
+        let scroll = scroll_state();
+        let start = Instant::now();
+
+        handler
+            .handle_input(&right_stick_at(i16::MAX, start), &scroll)
+            .unwrap();
+        handler
+            .handle_input(
+                &right_stick_at(i16::MAX, start + Duration::from_millis(200)),
+                &scroll,
+            )
+            .unwrap();
+        assert_eq!(sink.take(), vec![OutputEvent::Key(Key::Shift, Press)]);
+
+        handler
+            .handle_input(
+                &right_stick_at(i16::MAX, start + Duration::from_millis(500)),
+                &scroll,
+            )
+            .unwrap();
+        assert!(sink.take().is_empty());
+
+        // 回中时释放按住动作，不再执行轻拨动作
+        handler
+            .handle_input(
+                &right_stick_at(0, start + Duration::from_millis(600)),
+                &scroll,
+            )
+            .unwrap();
+        assert_eq!(sink.take(), vec![OutputEvent::Key(Key::Shift, Release)]);
+    }
+
+    /// 按住 LT、陀螺仪向右转动时一帧的水平相对位移
+    fn gyro_frame_x(suppression: bool, lx: i16, yaw: i16) -> i32 {
+        let mut config = test_config();
+        config.gyro_stick_suppression.enabled = suppression;
+        config.gyro_stick_suppression.gyro_scale = 0.0;
+        let sink = RecordingSink::relative();
+        let mut handler = InputHandler::with_output(Box::new(sink.clone()), config).unwrap();
+        let mut state = gyro_state(yaw, 0);
+        state.lx = lx;
+        relative_frame_x(&mut handler, &sink, &state)
+    }
+
+    #[test]
+    fn gyro_is_suppressed_only_while_stick_is_moving() {
+        // 摇杆回中时陀螺仪不受影响
+        // 帧间隔按实际时间计算，比较时允许一像素的舍入差异
+        let gyro_only = gyro_frame_x(true, 0, 2000);
+        assert!(gyro_only > 0);
+        assert!((gyro_only - gyro_frame_x(false, 0, 2000)).abs() <= 1);
+
+        // 摇杆推动时陀螺仪输出被缩放为 0，只剩摇杆的位移
+        let stick_only = gyro_frame_x(true, i16::MAX, 0);
+        assert!((gyro_frame_x(true, i16::MAX, 2000) - stick_only).abs() <= 1);
+        assert!(gyro_frame_x(false, i16::MAX, 2000) > stick_only + gyro_only / 2);
+    }
+
+    fn trigger_scroll_config() -> ControllerConfig {
+        let mut config = test_config();
+        config.scroll_source = ScrollSource::Triggers;
+        config
+    }
+
+    #[test]
+    fn triggers_map_to_opposite_scroll_power() {
+        let (mut handler, _sink) = handler(trigger_scroll_config());
+        let scroll = scroll_state();
+        let sensitivity = handler.config.direct_scroll_sensitivity;
+        let threshold = handler.config.analog_trigger_threshold;
+        let mut power = |lt: u8, rt: u8| {
+            handler
+                .handle_input(&trigger_state(lt, rt), &scroll)
+                .unwrap();
+            scroll.lock().unwrap().power
+        };
+
+        assert_eq!(power(0, 255), sensitivity);
+        assert_eq!(power(255, 0), -sensitivity);
+        assert_eq!(power(255, 255), 0.0);
+        assert_eq!(power(threshold, 0), 0.0);
+
+        // 力度随按压深度按平方曲线增长
+        let half = threshold + (u8::MAX - threshold) / 2;
+        let expected = ((half - threshold) as f64 / (u8::MAX - threshold) as f64).powf(2.0);
+        assert!((power(0, half) - expected * sensitivity).abs() < 1e-9);
+    }
+
+    #[test]
+    fn triggers_are_not_modifiers_in_trigger_scroll_mode() {
+        let (mut handler, sink) = handler(trigger_scroll_config());
+        let scroll = scroll_state();
+
+        // LT+X 组合键不生效，执行 X 的单独绑定
+        let mut pressed = state(&[BUTTON_X]);
+        pressed.lt = 255;
+        handler.handle_input(&pressed, &scroll).unwrap();
+
+        let (mut plain, plain_sink) = self::handler(test_config());
+        plain
+            .handle_input(&state(&[BUTTON_X]), &scroll_state())
+            .unwrap();
+        let expected = plain_sink.take();
+        assert!(!expected.is_empty());
+        assert_eq!(sink.take(), expected);
+    }
+
+    #[test]
+    fn unbound_buttons_use_default_action() {
+        let mut config = test_config();
+        config.default_action = Some(ButtonAction::HoldKey("f8".to_string()));
+        config.binding_enabled.insert("B".to_string(), false);
+        assert!(!config.button_mappings.contains_key("P1"));
+        let (mut handler, sink) = handler(config);
+        let scroll = scroll_state();
+
+        handler.handle_input(&state(&[BUTTON_P1]), &scroll).unwrap();
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![
+                OutputEvent::Key(Key::F8, Press),
+                OutputEvent::Key(Key::F8, Release),
+            ]
+        );
+
+        // 已绑定的按键保持原绑定，已停用的绑定也不使用默认动作
+        handler.handle_input(&state(&[BUTTON_A]), &scroll).unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![OutputEvent::Button(EnigoButton::Left, Press)]
+        );
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        sink.take();
+        handler.handle_input(&state(&[BUTTON_B]), &scroll).unwrap();
+        assert!(sink.take().is_empty());
+    }
+
+    #[test]
+    fn unbound_buttons_do_nothing_without_default_action() {
+        let (mut handler, sink) = handler(test_config());
+        let scroll = scroll_state();
+        handler.handle_input(&state(&[BUTTON_P1]), &scroll).unwrap();
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        assert!(sink.take().is_empty());
+    }
+
+    fn arming_config(disarm_chord: &[&str]) -> ControllerConfig {
+        let mut config = test_config();
+        config.arming.require_arm = true;
+        config.arming.disarm_chord = disarm_chord.iter().map(|name| name.to_string()).collect();
+        config
+    }
+
+    #[test]
+    fn locked_handler_ignores_input_until_arm_chord() {
+        let (mut handler, sink) = handler(arming_config(&[]));
+        let scroll = scroll_state();
+
+        let mut pushed = state(&[BUTTON_A]);
+        pushed.lx = i16::MAX;
+        pushed.ry = -i16::MAX;
+        handler.handle_input(&pushed, &scroll).unwrap();
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        assert!(sink.take().is_empty());
+        assert_eq!(scroll.lock().unwrap().power, 0.0);
+
+        // 只按下组合键中的一个不会解锁
+        handler.handle_input(&state(&[BUTTON_LB]), &scroll).unwrap();
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        handler.handle_input(&state(&[BUTTON_A]), &scroll).unwrap();
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        assert!(sink.take().is_empty());
+
+        // 解锁组合键本身不触发 LB/RB 的绑定
+        handler
+            .handle_input(&state(&[BUTTON_LB, BUTTON_RB]), &scroll)
+            .unwrap();
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        assert!(sink.take().is_empty());
+
+        handler.handle_input(&state(&[BUTTON_A]), &scroll).unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![OutputEvent::Button(EnigoButton::Left, Press)]
+        );
+    }
+
+    #[test]
+    fn disarm_chord_locks_and_releases_held_input() {
+        let (mut handler, sink) = handler(arming_config(&["Back"]));
+        let scroll = scroll_state();
+
+        handler
+            .handle_input(&state(&[BUTTON_LB, BUTTON_RB]), &scroll)
+            .unwrap();
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        let mut held = state(&[BUTTON_A]);
+        held.ry = -i16::MAX;
+        handler.handle_input(&held, &scroll).unwrap();
+        assert_ne!(scroll.lock().unwrap().power, 0.0);
+        sink.take();
+
+        let mut disarm = held.clone();
+        disarm.pressed_buttons.insert(BUTTON_BACK);
+        handler.handle_input(&disarm, &scroll).unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![OutputEvent::Button(EnigoButton::Left, Release)]
+        );
+        assert_eq!(scroll.lock().unwrap().power, 0.0);
+
+        // 锁定后输入再次被忽略
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        handler.handle_input(&held, &scroll).unwrap();
+        assert!(sink.take().is_empty());
+        assert_eq!(scroll.lock().unwrap().power, 0.0);
+    }
+
+    #[test]
+    fn shared_arm_and_disarm_chord_toggles_once_per_press() {
+        let (mut handler, sink) = handler(arming_config(&["LB", "RB"]));
+        let scroll = scroll_state();
+        let chord = state(&[BUTTON_LB, BUTTON_RB]);
+
+        // 按住不放只解锁一次，不会在同一次按下中再次锁定
+        handler.handle_input(&chord, &scroll).unwrap();
+        handler.handle_input(&chord, &scroll).unwrap();
+        assert!(handler.armed);
+
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        handler.handle_input(&chord, &scroll).unwrap();
+        assert!(!handler.armed);
+        handler.handle_input(&chord, &scroll).unwrap();
+        assert!(!handler.armed);
+        sink.take();
+    }
+
+    #[test]
+    fn disabling_require_arm_on_reload_unlocks() {
+        let (mut handler, sink) = handler(arming_config(&[]));
+        let scroll = scroll_state();
+
+        handler.update_config(test_config());
+        handler.handle_input(&state(&[BUTTON_A]), &scroll).unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![OutputEvent::Button(EnigoButton::Left, Press)]
+        );
+    }
+
+    #[test]
+    fn switch_tab_sends_primary_modifier_and_digit() {
+        let mut config = test_config();
+        config.button_mappings.insert(
+            "DPad_Left".to_string(),
+            ButtonAction::SwitchTab { index: 3 },
+        );
+        let (mut handler, sink) = handler(config);
+        let scroll = scroll_state();
+
+        handler.handle_input(&state(&[DPAD_LEFT]), &scroll).unwrap();
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![
+                OutputEvent::Key(Key::Control, Press),
+                OutputEvent::Key(Key::Unicode('3'), Click),
+                OutputEvent::Key(Key::Control, Release),
+            ]
+        );
+    }
+
+    #[test]
+    fn invert_scroll_flips_both_axes_at_the_source() {
+        let mut config = test_config();
+        config.right_stick_horizontal_mode = RightStickHorizontalMode::HorizontalScroll;
+        let scroll_for = |invert: bool, rx: i16, ry: i16| {
+            let (mut handler, _) = handler(ControllerConfig {
+                invert_scroll: invert,
+                ..config.clone()
+            });
+            let mut pushed = state(&[]);
+            pushed.rx = rx;
+            pushed.ry = ry;
+            let scroll = scroll_state();
+            handler.handle_input(&pushed, &scroll).unwrap();
+            *scroll.lock().unwrap()
+        };
+
+        let (normal, inverted) = (
+            scroll_for(false, 0, -i16::MAX),
+            scroll_for(true, 0, -i16::MAX),
+        );
+        assert_ne!(normal.power, 0.0);
+        assert_eq!(inverted.power, -normal.power);
+
+        let (normal, inverted) = (
+            scroll_for(false, i16::MAX, 0),
+            scroll_for(true, i16::MAX, 0),
+        );
+        assert_ne!(normal.horizontal, 0.0);
+        assert_eq!(inverted.horizontal, -normal.horizontal);
+    }
+
+    fn toggle_hold_config() -> ControllerConfig {
+        let mut config = test_config();
+        config
+            .button_mappings
+            .insert("A".to_string(), ButtonAction::ToggleHold(MouseButton::Left));
+        config
+    }
+
+    #[test]
+    fn toggle_hold_state_flips_only_after_output_succeeds() {
+        let (mut handler, sink) = handler(toggle_hold_config());
+        let scroll = scroll_state();
+
+        sink.set_failing(true);
+        assert!(handler.handle_input(&state(&[BUTTON_A]), &scroll).is_err());
+        sink.set_failing(false);
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        assert!(handler.toggled_buttons.is_empty());
+
+        // 上次按下失败，再次按下仍然是按住而不是松开
+        handler.handle_input(&state(&[BUTTON_A]), &scroll).unwrap();
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![OutputEvent::Button(EnigoButton::Left, Press)]
+        );
+
+        handler.handle_input(&state(&[BUTTON_A]), &scroll).unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![OutputEvent::Button(EnigoButton::Left, Release)]
+        );
+    }
+
+    #[test]
+    fn toggle_hold_is_not_repeated_while_held() {
+        let mut config = toggle_hold_config();
+        config.repeat_actions.insert(
+            "A".to_string(),
+            RepeatConfig {
+                initial_delay_ms: 0,
+                interval_ms: 1,
+            },
+        );
+        let (mut handler, sink) = handler(config);
+        let scroll = scroll_state();
+
+        for _ in 0..3 {
+            handler.handle_input(&state(&[BUTTON_A]), &scroll).unwrap();
+            thread::sleep(Duration::from_millis(2));
+        }
+        assert_eq!(
+            sink.take(),
+            vec![OutputEvent::Button(EnigoButton::Left, Press)]
+        );
+    }
+
+    #[test]
+    fn idle_group_does_not_cancel_another_groups_scroll() {
+        let (mut first, _) = handler(test_config());
+        let (mut second, _) = handler(test_config());
+        let scroll = scroll_state();
+
+        let mut pushed = state(&[]);
+        pushed.ry = -i16::MAX;
+        first.handle_input(&pushed, &scroll).unwrap();
+        let power = scroll.lock().unwrap().power;
+        assert_ne!(power, 0.0);
+
+        second.handle_input(&state(&[]), &scroll).unwrap();
+        assert_eq!(scroll.lock().unwrap().power, power);
+
+        // 两组同时滚动时力度相加，各自停止时只撤下自己的分量
+        second.handle_input(&pushed, &scroll).unwrap();
+        assert_eq!(scroll.lock().unwrap().power, power * 2.0);
+        second.stop_scroll(&scroll);
+        assert_eq!(scroll.lock().unwrap().power, power);
+        first.handle_input(&state(&[]), &scroll).unwrap();
+        assert_eq!(scroll.lock().unwrap().power, 0.0);
+    }
+
+    /// 单帧陀螺仪输入产生的相对光标位移 (x, y)
+    fn gyro_move(config: &ControllerConfig, yaw: i16, pitch: i16) -> (i32, i32) {
+        let sink = RecordingSink::relative();
+        let mut handler =
+            InputHandler::with_output(Box::new(sink.clone()), config.clone()).unwrap();
+        handler.last_movement_at = Some(Instant::now() - MOVEMENT_REFERENCE_INTERVAL);
+        handler
+            .handle_input(&gyro_state(yaw, pitch), &scroll_state())
+            .unwrap();
+        match sink.take().as_slice() {
+            [OutputEvent::Move(x, y, Coordinate::Rel)] => (*x, *y),
+            events => panic!("unexpected output: {:?}", events),
+        }
+    }
+
+    #[test]
+    fn gyro_invert_and_swap_compose() {
+        let mut config = test_config();
+        config.gyro_smoothing_factor = 0.0;
+        // 帧间隔按实际时间计算，位移大小可能有一像素的差异，这里只比较方向
+        let direction = |(x, y): (i32, i32)| (x.signum(), y.signum());
+        assert_eq!(direction(gyro_move(&config, 2000, 0)), (1, 0));
+        assert_eq!(direction(gyro_move(&config, 0, 2000)), (0, 1));
+
+        for invert_yaw in [false, true] {
+            for invert_pitch in [false, true] {
+                for swap in [false, true] {
+                    config.gyro_invert_yaw = invert_yaw;
+                    config.gyro_invert_pitch = invert_pitch;
+                    config.gyro_swap_axes = swap;
+                    let yaw_sign = if invert_yaw { -1 } else { 1 };
+                    let pitch_sign = if invert_pitch { -1 } else { 1 };
+
+                    // 先反转再交换：交换后偏航驱动垂直方向、俯仰驱动水平方向，
+                    // 各自的反转跟随原来的轴
+                    let (expected_yaw, expected_pitch) = if swap {
+                        ((0, yaw_sign), (pitch_sign, 0))
+                    } else {
+                        ((yaw_sign, 0), (0, pitch_sign))
+                    };
+                    let label = (invert_yaw, invert_pitch, swap);
+                    assert_eq!(
+                        direction(gyro_move(&config, 2000, 0)),
+                        expected_yaw,
+                        "{:?}",
+                        label
+                    );
+                    assert_eq!(
+                        direction(gyro_move(&config, 0, 2000)),
+                        expected_pitch,
+                        "{:?}",
+                        label
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn function_and_media_key_names_parse_case_insensitively() {
+        let function_keys = [
+            Key::F1,
+            Key::F2,
+            Key::F3,
+            Key::F4,
+            Key::F5,
+            Key::F6,
+            Key::F7,
+            Key::F8,
+            Key::F9,
+            Key::F10,
+            Key::F11,
+            Key::F12,
+        ];
+        for (index, expected) in function_keys.into_iter().enumerate() {
+            for name in [format!("f{}", index + 1), format!("F{}", index + 1)] {
+                assert_eq!(
+                    InputHandler::parse_key_string_static(&name).unwrap(),
+                    expected
+                );
+            }
+        }
+
+        for (names, expected) in [
+            (&["volumeup", "VolumeUp"][..], Key::VolumeUp),
+            (&["volumedown", "VOLUMEDOWN"][..], Key::VolumeDown),
+            (&["mute", "Mute"][..], Key::VolumeMute),
+            (&["play", "PlayPause"][..], Key::MediaPlayPause),
+            (&["next", "Next"][..], Key::MediaNextTrack),
+            (&["prev", "Previous"][..], Key::MediaPrevTrack),
+        ] {
+            for name in names {
+                assert_eq!(
+                    InputHandler::parse_key_string_static(name).unwrap(),
+                    expected
+                );
+            }
+        }
+
+        for name in ["f0", "f13", "volume", ""] {
+            assert!(matches!(
+                InputHandler::parse_key_string_static(name),
+                Err(ControllerError::Config(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn double_click_schedules_second_click_and_holds_cursor() {
+        let mut config = test_config();
+        config.double_click_interval_ms = 100;
+        config.button_mappings.insert(
+            "Share".to_string(),
+            ButtonAction::DoubleClick(MouseButton::Left),
+        );
+        let (mut handler, sink) = handler(config);
+        let scroll = scroll_state();
+
+        let pressed = state(&[BUTTON_SHARE]);
+        handler.handle_input(&pressed, &scroll).unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![OutputEvent::Button(EnigoButton::Left, Click)]
+        );
+
+        // 等待第二次点击期间摇杆不移动光标
+        let mut pushed = state(&[]);
+        pushed.lx = i16::MAX;
+        handler.handle_input(&pushed, &scroll).unwrap();
+        assert!(sink.take().is_empty());
+
+        handler
+            .handle_timers(pressed.timestamp + Duration::from_millis(100))
+            .unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![OutputEvent::Button(EnigoButton::Left, Click)]
+        );
+
+        // 第二次点击发出后光标恢复移动
+        pushed.timestamp += Duration::from_millis(110);
+        handler.handle_input(&pushed, &scroll).unwrap();
+        assert!(matches!(sink.take().as_slice(), [OutputEvent::Move(..)]));
+    }
+
+    #[test]
+    fn repeated_shortcut_presses_are_scheduled() {
+        let mut config = test_config();
+        config.shortcuts.scroll_to_top = Shortcut {
+            modifiers: Vec::new(),
+            key: "home".to_string(),
+            presses: 3,
+        };
+        config
+            .button_mappings
+            .insert("DPad_Up".to_string(), ButtonAction::ScrollToTop);
+        let (mut handler, sink) = handler(config);
+
+        let pressed = state(&[DPAD_UP]);
+        handler.handle_input(&pressed, &scroll_state()).unwrap();
+        assert_eq!(sink.take(), vec![OutputEvent::Key(Key::Home, Click)]);
+
+        handler
+            .handle_timers(pressed.timestamp + REPEATED_PRESS_INTERVAL * 2)
+            .unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![
+                OutputEvent::Key(Key::Home, Click),
+                OutputEvent::Key(Key::Home, Click),
+            ]
+        );
+    }
+
+    #[test]
+    fn swipe_falls_back_to_configured_navigation_shortcut() {
+        let mut config = test_config();
+        config
+            .button_mappings
+            .insert("LB".to_string(), ButtonAction::Swipe(SwipeDirection::Back));
+
+        // 不支持手势的后端按配置的平台发送导航快捷键（Windows 为 Alt+左箭头）
+        let (mut input, sink) = handler(config.clone());
+        input
+            .handle_input(&state(&[BUTTON_LB]), &scroll_state())
+            .unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![
+                OutputEvent::Key(Key::Alt, Press),
+                OutputEvent::Key(Key::LeftArrow, Click),
+                OutputEvent::Key(Key::Alt, Release),
+            ]
+        );
+
+        // macOS 上使用配置的主修饰键
+        config.platform = Platform::MacOS;
+        config.primary_modifier = Some("ctrl".to_string());
+        let (mut input, sink) = handler(config.clone());
+        input
+            .handle_input(&state(&[BUTTON_LB]), &scroll_state())
+            .unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![
+                OutputEvent::Key(Key::Control, Press),
+                OutputEvent::Key(Key::Unicode('['), Click),
+                OutputEvent::Key(Key::Control, Release),
+            ]
+        );
+
+        // 支持手势的后端直接发送手势
+        let sink = RecordingSink::relative();
+        let mut input = InputHandler::with_output(Box::new(sink.clone()), config).unwrap();
+        input
+            .handle_input(&state(&[BUTTON_LB]), &scroll_state())
+            .unwrap();
+        assert_eq!(sink.take(), vec![OutputEvent::Swipe(SwipeDirection::Back)]);
+    }
+
+    #[test]
+    fn rate_limited_press_is_not_released() {
+        let mut config = test_config();
+        config.max_actions_per_second = 1;
+        let (mut handler, sink) = handler(config);
+        let scroll = scroll_state();
+
+        handler.handle_input(&state(&[BUTTON_A]), &scroll).unwrap();
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        assert_eq!(sink.take().len(), 2);
+
+        // 被频率限制丢弃的按下，松开时也不发送释放
+        handler.handle_input(&state(&[BUTTON_A]), &scroll).unwrap();
+        handler.handle_input(&state(&[]), &scroll).unwrap();
+        assert!(sink.take().is_empty());
+    }
+
+    #[test]
+    fn cursor_movement_is_scaled_by_report_time() {
+        let (mut handler, sink) = handler(test_config());
+        let scroll = scroll_state();
+
+        let mut pushed = state(&[]);
+        pushed.lx = i16::MAX;
+        handler.last_movement_at = Some(pushed.timestamp - MOVEMENT_REFERENCE_INTERVAL);
+        handler.handle_input(&pushed, &scroll).unwrap();
+        assert!(matches!(sink.take().as_slice(), [OutputEvent::Move(..)]));
+
+        // 同一时刻的报告晚些才处理，也不会按处理时的时间产生位移
+        thread::sleep(Duration::from_millis(5));
+        handler.handle_input(&pushed, &scroll).unwrap();
+        assert!(sink.take().is_empty());
+    }
+
+    #[test]
+    fn sub_pixel_frame_skips_location_and_move() {
+        let sink = RecordingSink::new();
+        let mut handler = InputHandler::with_output(Box::new(sink.clone()), test_config()).unwrap();
+        let mut nudged = state(&[]);
+        nudged.lx = 4000; // 略微偏离中心，一帧位移不足一像素
+        handler.last_movement_at = Some(nudged.timestamp - MOVEMENT_REFERENCE_INTERVAL);
+        handler.handle_input(&nudged, &scroll_state()).unwrap();
+
+        assert!(sink.take().is_empty());
+        assert_eq!(sink.location_calls(), 0);
+        assert!(handler.subpixel.0 > 0.0);
     }
 }
@@ -1,54 +1,179 @@
-use enigo::{Axis, Enigo, Mouse, Settings};
+use enigo::{
+    Axis,
+    Direction::{Press, Release},
+    InputResult,
+};
+use log::{error, info, warn};
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, Mutex};
 use std::{thread, time};
 
 // 模块导入
+mod action_log;
+mod capture;
+mod cli;
 mod config;
 mod connection_manager;
+mod dualsense;
 mod error;
 mod hid;
 mod input_handler;
+mod multi_controller;
+mod output;
+mod tuning;
 
-use config::ControllerConfig;
+use capture::{Capture, CaptureWriter};
+use cli::Mode;
+use config::{
+    ControllerConfig, InitialFailurePolicy, InputPanicPolicy, OutputBackend,
+    RightStickHorizontalMode, ScrollMode,
+};
 use connection_manager::ConnectionManager;
 use error::{ControllerError, ControllerResult, ErrorContext, RecoveryStrategy};
-use hid::HidController;
-use input_handler::InputHandler;
+use hid::{HidController, StickOffsets};
+use input_handler::ScrollState;
+use multi_controller::{ConnectedDevice, InputGroups, merged_state};
+use output::{OutputSink, create_output_sink};
 
-/// 滚动处理器，使用独立的 Enigo 实例
+/// 即时滚动模式下每行对应的滚动力度
+const PIXELS_PER_LINE: f64 = 10.0;
+
+/// 检查手柄电量的间隔
+const BATTERY_CHECK_INTERVAL: time::Duration = time::Duration::from_secs(60);
+
+/// 校准时采样摇杆静止位置的时长
+const CALIBRATION_DURATION: time::Duration = time::Duration::from_secs(3);
+
+/// 建议死区相对于测得最大漂移的余量倍数
+const CALIBRATION_MARGIN: f64 = 1.25;
+
+/// 滚动处理器，使用独立的 Enigo 输出后端
 struct ScrollHandler {
-    enigo: Enigo,
+    output: Box<dyn OutputSink>,
+    mode: ScrollMode,
+    line_remainder: (f64, f64), // 即时滚动时尚未发送的不足一行的滚动量 (垂直, 水平)
 }
 
 impl ScrollHandler {
-    fn new() -> ControllerResult<Self> {
-        let enigo = Enigo::new(&Settings::default()).map_err(|e| {
-            ControllerError::InitializationFailed(format!("滚动处理器Enigo初始化失败: {}", e))
-        })?;
-        Ok(Self { enigo })
+    fn new(mode: ScrollMode, dry_run: bool) -> ControllerResult<Self> {
+        let output = create_output_sink(OutputBackend::Enigo, dry_run)?;
+        Ok(Self::with_output(output, mode))
+    }
+
+    fn with_output(output: Box<dyn OutputSink>, mode: ScrollMode) -> Self {
+        Self {
+            output,
+            mode,
+            line_remainder: (0.0, 0.0),
+        }
+    }
+
+    /// 发送滚动事件
+    ///
+    /// 平滑模式按像素平滑滚动；即时模式将滚动量换算为行数直接滚动，
+    /// 不足一行的部分累积到下一次。
+    fn scroll(&mut self, delta: i32, axis: Axis) -> InputResult<()> {
+        if self.mode == ScrollMode::Smooth {
+            return self.output.smooth_scroll(delta, axis);
+        }
+
+        let remainder = match axis {
+            Axis::Vertical => &mut self.line_remainder.0,
+            Axis::Horizontal => &mut self.line_remainder.1,
+        };
+        let lines = delta as f64 / PIXELS_PER_LINE + *remainder;
+        let whole = lines.trunc();
+        *remainder = lines - whole;
+        if whole == 0.0 {
+            return Ok(());
+        }
+        self.output.scroll(whole as i32, axis)
+    }
+
+    /// 切换平滑/按行滚动，切换时丢弃不足一行的累积量
+    fn set_mode(&mut self, mode: ScrollMode) {
+        if mode != self.mode {
+            self.mode = mode;
+            self.line_remainder = (0.0, 0.0);
+        }
+    }
+
+    /// 滚动停止时丢弃该轴不足一行的累积量，下次滚动从零开始，不会多滚或反向滚动一行
+    fn stop(&mut self, axis: Axis) {
+        match axis {
+            Axis::Vertical => self.line_remainder.0 = 0.0,
+            Axis::Horizontal => self.line_remainder.1 = 0.0,
+        }
     }
 }
 
-/// "步调器"线程用于发送平滑滚动事件
-fn run_pacer_loop(scroll_power: Arc<Mutex<f64>>, config: ControllerConfig) {
-    let mut scroll_handler = match ScrollHandler::new() {
+/// 让实际滚动力度向目标力度靠拢
+///
+/// 力度增大时按加速系数、减小或反向时按减速系数靠近目标，足够接近时直接取目标值，
+/// 因此目标归零后经过几次循环即可完全停止。
+fn ramp_scroll_power(current: f64, target: f64, config: &ControllerConfig) -> f64 {
+    let speeding_up = target.abs() > current.abs() && target * current >= 0.0;
+    let factor = if speeding_up {
+        config.scroll_ramp_up
+    } else {
+        config.scroll_ramp_down
+    };
+    let next = current + (target - current) * factor;
+    if (target - next).abs() < 0.01 {
+        target
+    } else {
+        next
+    }
+}
+
+/// "步调器"线程用于发送平滑滚动事件，`running` 变为 false 后停止滚动并退出
+///
+/// 热重载的配置通过 `config_updates` 送达，下一次循环即按新配置滚动。
+fn run_pacer_loop(
+    scroll_state: Arc<Mutex<ScrollState>>,
+    mut config: ControllerConfig,
+    config_updates: Receiver<ControllerConfig>,
+    running: Arc<AtomicBool>,
+) {
+    let mut scroll_handler = match ScrollHandler::new(config.scroll_mode, config.dry_run) {
         Ok(handler) => handler,
         Err(e) => {
-            eprintln!("在步调器线程中初始化滚动处理器时出错: {}", e);
+            error!("在步调器线程中初始化滚动处理器时出错: {}", e);
             return;
         }
     };
 
-    let loop_interval = time::Duration::from_secs_f64(1.0 / config.pacer_loop_hz as f64);
+    // 实际施加的滚动力度，每次循环向共享的目标力度靠拢
+    let mut power = 0.0;
+    let mut horizontal = 0.0;
+    let mut zoom = false;
 
-    loop {
-        let power = match scroll_power.lock() {
+    while running.load(Ordering::Relaxed) {
+        if let Some(new_config) = config_updates.try_iter().last() {
+            scroll_handler.set_mode(new_config.scroll_mode);
+            config = new_config;
+        }
+        let loop_interval = time::Duration::from_secs_f64(1.0 / config.pacer_loop_hz as f64);
+        let zoom_modifier = output::primary_modifier(config.platform);
+
+        let target = match scroll_state.lock() {
             Ok(guard) => *guard,
             Err(_) => {
-                eprintln!("无法获取滚动力度锁");
+                error!("无法获取滚动力度锁");
                 continue;
             }
         };
+        power = ramp_scroll_power(power, target.power, &config);
+        horizontal = ramp_scroll_power(horizontal, target.horizontal, &config);
+        // 减速期间沿用松开前的缩放状态，避免最后几次滚动变成普通滚动
+        if target.power != 0.0 {
+            zoom = target.zoom;
+        }
 
         if power.abs() > 0.01 {
             let scroll_delta = power.round() as i32;
@@ -60,144 +185,482 @@ fn run_pacer_loop(scroll_power: Arc<Mutex<f64>>, config: ControllerConfig) {
                     -scroll_delta
                 };
                 
+                // 缩放滚动：滚动期间按住主修饰键（macOS 为 Cmd，其他系统为 Ctrl）
+                if zoom {
+                    if let Err(e) = scroll_handler.output.key(zoom_modifier, Press) {
+                        error!("按下缩放修饰键时出错: {}", e);
+                    }
+                }
+
                 // 正值向下滚动，负值向上滚动
-                if let Err(e) = scroll_handler
-                    .enigo
-                    .smooth_scroll(safe_scroll_delta, Axis::Vertical)
-                {
-                    eprintln!("滚动时出错: {}", e);
+                if let Err(e) = scroll_handler.scroll(safe_scroll_delta, Axis::Vertical) {
+                    error!("滚动时出错: {}", e);
+                }
+
+                if zoom {
+                    if let Err(e) = scroll_handler.output.key(zoom_modifier, Release) {
+                        error!("释放缩放修饰键时出错: {}", e);
+                    }
                 }
             }
+        } else {
+            scroll_handler.stop(Axis::Vertical);
+        }
+
+        // 水平滚动（陀螺仪映射），正值向右
+        let horizontal_delta = horizontal.round() as i32;
+        if horizontal_delta != 0 {
+            if let Err(e) = scroll_handler.scroll(horizontal_delta, Axis::Horizontal) {
+                error!("水平滚动时出错: {}", e);
+            }
+        } else if horizontal.abs() <= 0.01 {
+            scroll_handler.stop(Axis::Horizontal);
         }
         thread::sleep(loop_interval);
     }
+
+    scroll_handler.stop(Axis::Vertical);
+    scroll_handler.stop(Axis::Horizontal);
 }
 
 /// 打印操作说明
 fn print_instructions(config: &ControllerConfig) {
     println!("设备已连接！控制器现在可以控制鼠标了。");
-    println!(" - 左摇杆：移动光标");
+    if config.cursor_source.uses_left_stick() {
+        println!(" - 左摇杆：移动光标");
+    }
     println!(" - 右摇杆上/下：滚动页面（平滑且松开时停止）");
-    println!(" - 右摇杆左/右：导航前进/后退（在浏览器等应用中）");
-    println!(" - 按住LT + 移动控制器：陀螺仪瞄准");
+    match config.right_stick_horizontal_mode {
+        RightStickHorizontalMode::Navigate => {
+            println!(" - 右摇杆左/右：导航前进/后退（在浏览器等应用中）")
+        }
+        RightStickHorizontalMode::HorizontalScroll => println!(" - 右摇杆左/右：水平滚动"),
+    }
+    if config.cursor_source.uses_gyro() {
+        println!(" - 按住LT + 移动控制器：陀螺仪瞄准");
+    }
     println!();
     println!("按键绑定：");
 
+    let modifier = primary_modifier_label(config);
+
     for (combo, action) in &config.button_mappings {
-        println!(" - {}: {}", combo, format_button_action(action));
+        let disabled = if config.is_binding_enabled(combo) {
+            ""
+        } else {
+            "（已停用）"
+        };
+        println!(
+            " - {}: {}{}",
+            combo,
+            format_button_action(action, &modifier),
+            disabled
+        );
+    }
+    if let Some(action) = &config.default_action {
+        println!(" - 其他按键: {}", format_button_action(action, &modifier));
+    }
+    if !config.combo_mappings.is_empty() {
+        println!();
+        println!("组合键绑定：");
+        for (combo, action) in &config.combo_mappings {
+            let disabled = if config.is_binding_enabled(combo) {
+                ""
+            } else {
+                "（已停用）"
+            };
+            println!(
+                " - {}: {}{}",
+                combo,
+                format_button_action(action, &modifier),
+                disabled
+            );
+        }
+    }
+
+    if config.arming.require_arm {
+        println!();
+        println!(
+            "手柄当前处于锁定状态，同时按下 {} 解锁后开始控制。",
+            config.arming.arm_chord.join("+")
+        );
     }
 
     println!("按 Ctrl+C 退出程序。");
     println!("{}", "-".repeat(40));
 }
 
-/// 格式化按钮动作描述
-fn format_button_action(action: &config::ButtonAction) -> String {
+/// 操作说明中主修饰键的名称：优先显示 `primary_modifier` 配置，否则按平台显示 Cmd 或 Ctrl
+fn primary_modifier_label(config: &ControllerConfig) -> String {
+    match &config.primary_modifier {
+        Some(name) => name.clone(),
+        None => match output::primary_modifier(config.platform) {
+            enigo::Key::Meta => "Cmd".to_string(),
+            _ => "Ctrl".to_string(),
+        },
+    }
+}
+
+/// 格式化按钮动作描述，`modifier` 为主修饰键的显示名称
+fn format_button_action(action: &config::ButtonAction, modifier: &str) -> String {
     match action {
         config::ButtonAction::LeftClick => "左鼠标点击".to_string(),
         config::ButtonAction::RightClick => "右鼠标点击".to_string(),
-        config::ButtonAction::CloseWindow => "关闭窗口 (Cmd+W)".to_string(),
+        config::ButtonAction::LeftTap => "左鼠标单击（按下即完成）".to_string(),
+        config::ButtonAction::RightTap => "右鼠标单击（按下即完成）".to_string(),
+        config::ButtonAction::CloseWindow => format!("关闭窗口 ({}+W)", modifier),
         config::ButtonAction::MissionControl => "调度中心".to_string(),
         config::ButtonAction::PrevTab => "上一个标签页".to_string(),
         config::ButtonAction::NextTab => "下一个标签页".to_string(),
-        config::ButtonAction::QuitApp => "退出应用程序 (Cmd+Q)".to_string(),
-        config::ButtonAction::NewTab => "新建标签页 (Cmd+T)".to_string(),
-        config::ButtonAction::Refresh => "刷新页面 (Cmd+R)".to_string(),
+        config::ButtonAction::QuitApp => format!("退出应用程序 ({}+Q)", modifier),
+        config::ButtonAction::QuitController => "退出本程序".to_string(),
+        config::ButtonAction::NewTab => format!("新建标签页 ({}+T)", modifier),
+        config::ButtonAction::Refresh => format!("刷新页面 ({}+R)", modifier),
+        config::ButtonAction::ScrollToTop => "滚动到顶部".to_string(),
+        config::ButtonAction::ScrollToBottom => "滚动到底部".to_string(),
+        config::ButtonAction::LockScreen => "锁定屏幕".to_string(),
+        config::ButtonAction::Paste => "粘贴".to_string(),
+        config::ButtonAction::PastePlain => "粘贴为纯文本".to_string(),
+        config::ButtonAction::ToggleFullscreen => "切换全屏".to_string(),
+        config::ButtonAction::MinimizeWindow => "最小化窗口".to_string(),
+        config::ButtonAction::HideApp => "隐藏应用".to_string(),
+        config::ButtonAction::StartDictation => "启动听写".to_string(),
+        config::ButtonAction::VolumeUp => "调高音量".to_string(),
+        config::ButtonAction::VolumeDown => "调低音量".to_string(),
+        config::ButtonAction::Mute => "静音".to_string(),
+        config::ButtonAction::BrightnessUp => "调高亮度".to_string(),
+        config::ButtonAction::BrightnessDown => "调低亮度".to_string(),
+        config::ButtonAction::SwitchTab { index } => format!("切换到第 {} 个标签页", index),
+        config::ButtonAction::RepeatLast => "重复上一个动作".to_string(),
+        config::ButtonAction::HoldKey(key) => format!("按住按键: {}", key),
+        config::ButtonAction::DoubleClick(button) => match button {
+            config::MouseButton::Left => "双击左鼠标键".to_string(),
+            config::MouseButton::Right => "双击右鼠标键".to_string(),
+            config::MouseButton::Middle => "双击中鼠标键".to_string(),
+        },
+        config::ButtonAction::ToggleHold(button) => match button {
+            config::MouseButton::Left => "切换按住左鼠标键".to_string(),
+            config::MouseButton::Right => "切换按住右鼠标键".to_string(),
+            config::MouseButton::Middle => "切换按住中鼠标键".to_string(),
+        },
+        config::ButtonAction::MoveCursorTo(anchor) => match anchor {
+            config::ScreenAnchor::TopLeft => "光标移到左上角".to_string(),
+            config::ScreenAnchor::TopRight => "光标移到右上角".to_string(),
+            config::ScreenAnchor::BottomLeft => "光标移到左下角".to_string(),
+            config::ScreenAnchor::BottomRight => "光标移到右下角".to_string(),
+            config::ScreenAnchor::Center => "光标移到屏幕中央".to_string(),
+        },
+        config::ButtonAction::Swipe(direction) => match direction {
+            config::SwipeDirection::Back => "滑动手势: 后退".to_string(),
+            config::SwipeDirection::Forward => "滑动手势: 前进".to_string(),
+        },
         config::ButtonAction::CustomShortcut { modifiers, key } => {
             format!("自定义快捷键: {}+{}", modifiers.join("+"), key)
         }
+        config::ButtonAction::TypeText(text) => format!("输入文本: {}", text_preview(text)),
+        config::ButtonAction::LaunchApp(app) => format!("启动应用: {}", app),
+        config::ButtonAction::Macro(steps) => format!("宏 ({} 步)", steps.len()),
         config::ButtonAction::None => "无操作".to_string(),
     }
 }
 
+/// 操作说明中显示的文本预览，过长时截断（按字符截断，不会切开中文等多字节字符）
+fn text_preview(text: &str) -> String {
+    const MAX_CHARS: usize = 20;
+    let mut preview: String = text.chars().take(MAX_CHARS).collect();
+    if text.chars().count() > MAX_CHARS {
+        preview.push('…');
+    }
+    format!("{:?}", preview)
+}
+
+/// 是否打印完整的操作说明：首次连接时打印，重连时按配置决定
+fn should_print_instructions(first_connect: bool, config: &ControllerConfig) -> bool {
+    first_connect || config.reconnection.reprint_instructions
+}
+
+/// 手柄连接成功后的处理：检查摇杆漂移、打印说明并执行连接动作序列
+fn on_controller_connected(
+    devices: &[ConnectedDevice],
+    input_groups: &mut InputGroups,
+    config: &ControllerConfig,
+    first_connect: bool,
+) {
+    for device in devices {
+        check_stick_drift(&device.controller, config);
+    }
+    if devices.len() > 1 {
+        info!("已连接 {} 个手柄", devices.len());
+    }
+    if should_print_instructions(first_connect, config) {
+        print_instructions(config);
+    } else {
+        info!("手柄已重新连接。");
+    }
+
+    let input_handler = input_groups.primary();
+    if config.recenter_cursor_on_connect
+        && let Err(e) = input_handler.recenter_cursor()
+    {
+        handle_error_with_recovery(e);
+    }
+    if let Err(e) = input_handler.run_action_sequence(&config.on_connect_actions) {
+        handle_error_with_recovery(e);
+    }
+}
+
+/// 采样摇杆静止位置，偏移明显超出死区时提示可能存在摇杆漂移
+fn check_stick_drift(controller: &HidController, config: &ControllerConfig) {
+    if config.drift_check_ms == 0 {
+        return;
+    }
+
+    let duration = time::Duration::from_millis(config.drift_check_ms);
+    let offsets = match controller.sample_resting_sticks(config.analog_trigger_threshold, duration)
+    {
+        Ok(Some(offsets)) => offsets,
+        Ok(None) => return, // 采样期间没有收到输入报告
+        Err(e) => {
+            warn!("摇杆漂移检查失败: {}", e);
+            return;
+        }
+    };
+
+    let sticks = [
+        (
+            "左摇杆",
+            offsets.left,
+            config.joystick_deadzone,
+            "joystick_deadzone",
+        ),
+        (
+            "右摇杆",
+            offsets.right,
+            config.right_joystick_deadzone,
+            "right_joystick_deadzone",
+        ),
+    ];
+    for (name, (x, y), deadzone, key) in sticks {
+        if x.saturating_abs() > deadzone || y.saturating_abs() > deadzone {
+            warn!(
+                "{}静止时偏移 ({}, {}) 超出死区 {}，可能存在摇杆漂移。",
+                name, x, y, deadzone
+            );
+            warn!(
+                "建议校准手柄，或将 {} 调大到 {} 以上（连接时正在推动摇杆可忽略此提示）",
+                key,
+                x.saturating_abs().max(y.saturating_abs())
+            );
+        }
+    }
+}
+
+/// 断线或退出时释放所有按住的输出并停止滚动
+fn release_held_outputs(input_groups: &mut InputGroups, scroll_state: &Arc<Mutex<ScrollState>>) {
+    input_groups.stop_scroll(scroll_state);
+    if let Err(e) = input_groups.release_all() {
+        error!("释放按住的输出时出错: {}", e);
+    }
+}
+
+/// 从 panic 负载中提取可读的错误信息
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "未知panic".to_string()
+    }
+}
+
 /// 处理错误并根据恢复策略执行相应操作
 fn handle_error_with_recovery(error: ControllerError) -> bool {
     let recovery_strategy = ErrorContext::suggest_recovery_strategy(&error);
     let context = ErrorContext::new(error, recovery_strategy);
 
-    eprintln!("错误: {}", context.error);
-    println!("建议: {}", context.user_message);
+    error!("{}", context.error);
+    info!("建议: {}", context.user_message);
 
     match &context.recovery_strategy {
         RecoveryStrategy::Retry {
             max_attempts,
             delay_ms,
         } => {
-            println!("将在 {}ms 后重试，最多重试 {} 次", delay_ms, max_attempts);
+            info!("将在 {}ms 后重试，最多重试 {} 次", delay_ms, max_attempts);
             thread::sleep(time::Duration::from_millis(*delay_ms));
             false // 继续运行
         }
         RecoveryStrategy::Reconnect => {
-            println!("正在尝试重新连接设备...");
+            info!("正在尝试重新连接设备...");
             thread::sleep(time::Duration::from_millis(1000));
             false // 继续运行
         }
         RecoveryStrategy::Skip => {
-            println!("跳过当前操作，继续运行...");
+            info!("跳过当前操作，继续运行...");
             false // 继续运行
         }
         RecoveryStrategy::Exit => {
-            println!("程序将退出。");
+            info!("程序将退出。");
             true // 退出程序
         }
     }
 }
 
+/// 配置文件路径：命令行指定的路径优先，否则使用默认路径
+fn resolve_config_path(args: &cli::Args) -> ControllerResult<PathBuf> {
+    match &args.config_path {
+        Some(path) => Ok(path.clone()),
+        None => ControllerConfig::default_config_path().map_err(ControllerError::Config),
+    }
+}
+
 /// 加载配置文件
-fn load_configuration() -> ControllerResult<ControllerConfig> {
-    let config_path =
-        ControllerConfig::default_config_path().map_err(|e| ControllerError::Config(e))?;
+fn load_configuration(args: &cli::Args) -> ControllerResult<ControllerConfig> {
+    let config_path = resolve_config_path(args)?;
 
     let config = ControllerConfig::load_or_create_default(&config_path)
         .map_err(|e| ControllerError::Config(e))?;
 
-    config.validate().map_err(|e| ControllerError::Config(e))?;
-
+    // 校验并应用选择的配置档案（命令行 --profile 优先）
+    let config = config
+        .activate(args.profile.as_deref())
+        .map_err(ControllerError::Config)?;
+    if let Some(name) = &config.profile {
+        info!("使用配置档案: {}", name);
+    }
     Ok(config)
 }
 
+/// 主循环每帧的最短间隔，`main_loop_max_hz` 为 0 时不限制
+fn main_loop_interval(config: &ControllerConfig) -> time::Duration {
+    if config.main_loop_max_hz == 0 {
+        time::Duration::ZERO
+    } else {
+        time::Duration::from_secs_f64(1.0 / config.main_loop_max_hz as f64)
+    }
+}
+
+/// 初始连接失败时按策略输出的提示，静默策略不输出
+fn initial_failure_message(policy: InitialFailurePolicy) -> Option<&'static str> {
+    match policy {
+        InitialFailurePolicy::Wait => Some("初始连接失败，开始等待设备连接..."),
+        InitialFailurePolicy::Silent => None,
+        InitialFailurePolicy::Beep => Some("\x07初始连接失败，开始等待设备连接..."),
+    }
+}
+
+/// 从未连接成功且等待时间超过 `initial_connect_timeout_ms` 时返回 true（超时为0表示一直等待）
+fn initial_connect_timed_out(
+    ever_connected: bool,
+    timeout: time::Duration,
+    waited: time::Duration,
+) -> bool {
+    !ever_connected && !timeout.is_zero() && waited >= timeout
+}
+
 /// 主控制循环（支持自动重连）
 fn run_control_loop_with_reconnect(
     mut connection_manager: ConnectionManager,
-    mut input_handler: InputHandler,
-    scroll_power: Arc<Mutex<f64>>,
+    input_groups: &mut InputGroups,
+    scroll_state: &Arc<Mutex<ScrollState>>,
     config: &ControllerConfig,
+    config_updates: &Receiver<Result<ControllerConfig, String>>,
+    pacer_config: &Sender<ControllerConfig>,
 ) -> ControllerResult<()> {
-    let mut current_controller: Option<HidController> = None;
-    let mut retry_count = 0;
+    let mut analog_trigger_threshold = config.analog_trigger_threshold;
+    let mut frame_interval = main_loop_interval(config);
+    let mut devices: Vec<ConnectedDevice> = Vec::new();
     const MAX_RETRIES: u32 = 5;
+    // 断线时刻，宽限期内保持按住的输出，宽限期结束后释放
+    let disconnect_grace = time::Duration::from_millis(config.reconnection.disconnect_grace_ms);
+    let mut disconnected_at: Option<time::Instant> = None;
+
+    // 从未连接成功时的等待期限
+    let started_at = time::Instant::now();
+    let initial_timeout =
+        time::Duration::from_millis(config.reconnection.initial_connect_timeout_ms);
+    let mut ever_connected = false;
+    let mut battery_checked_at = time::Instant::now();
 
     // 尝试初始连接
     match connection_manager.initial_connect() {
-        Ok(controller) => {
-            current_controller = Some(controller);
-            print_instructions(config);
+        Ok(controllers) => {
+            devices = input_groups.attach(controllers);
+            on_controller_connected(&devices, input_groups, config, true);
+            ever_connected = true;
         }
         Err(e) => {
             if !connection_manager.should_continue() {
                 return Err(e);
             }
-            println!("初始连接失败，开始等待设备连接...");
+            if let Some(message) = initial_failure_message(config.reconnection.on_initial_failure) {
+                warn!("{}", message);
+            }
         }
     }
 
-    loop {
+    'control: loop {
         // 检查是否应该继续运行
         if !connection_manager.should_continue() {
             break;
         }
 
+        // 应用热重载的配置，校验失败时保留原配置
+        if let Ok(update) = config_updates.try_recv() {
+            match update {
+                Ok(new_config) => {
+                    analog_trigger_threshold = new_config.analog_trigger_threshold;
+                    frame_interval = main_loop_interval(&new_config);
+                    // 绑定可能已改变，先按旧配置释放按住的输出，避免新绑定无法释放旧的按键
+                    release_held_outputs(input_groups, scroll_state);
+                    input_groups.update_config(&new_config);
+                    if pacer_config.send(new_config).is_err() {
+                        warn!("步调器线程已退出，滚动配置未更新");
+                    }
+                    info!("配置已更新");
+                }
+                Err(e) => warn!("重新加载配置失败，继续使用原配置: {}", e),
+            }
+        }
+
         // 如果没有控制器，尝试重连
-        if current_controller.is_none() {
+        if devices.is_empty() {
+            if initial_connect_timed_out(ever_connected, initial_timeout, started_at.elapsed()) {
+                error!(
+                    "等待 {}ms 仍未连接到手柄，程序退出。",
+                    initial_timeout.as_millis()
+                );
+                return Err(ControllerError::DeviceNotFound);
+            }
+
             if let Some(reconnect_result) = connection_manager.try_reconnect() {
                 match reconnect_result {
-                    Ok(controller) => {
-                        current_controller = Some(controller);
-                        retry_count = 0;
-                        print_instructions(config);
+                    Ok(controllers) => {
+                        devices = input_groups.attach(controllers);
+                        // 宽限期内重连视为无缝恢复，不重复显示说明
+                        if disconnected_at.take().is_none() {
+                            on_controller_connected(
+                                &devices,
+                                input_groups,
+                                config,
+                                !ever_connected,
+                            );
+                        }
+                        ever_connected = true;
                         continue;
                     }
                     Err(_) => {
+                        if let Some(at) = disconnected_at {
+                            let remaining = disconnect_grace.saturating_sub(at.elapsed());
+                            if !remaining.is_zero() {
+                                // 宽限期内只等待剩余时间，以便在释放前再尝试一次重连
+                                thread::sleep(remaining);
+                                continue;
+                            }
+                            release_held_outputs(input_groups, scroll_state);
+                            disconnected_at = None;
+                        }
                         connection_manager.wait_reconnect_interval();
                         continue;
                     }
@@ -208,46 +671,253 @@ fn run_control_loop_with_reconnect(
             }
         }
 
-        // 有控制器时，尝试读取状态
-        if let Some(controller) = &current_controller {
-            match controller.read_state(config.analog_trigger_threshold) {
+        // 有控制器时，逐个读取状态
+        let frame_started = time::Instant::now();
+        let check_battery = battery_checked_at.elapsed() >= BATTERY_CHECK_INTERVAL;
+        if check_battery {
+            battery_checked_at = time::Instant::now();
+        }
+        let device_count = devices.len();
+        let mut lost_devices = Vec::new();
+        for index in 0..device_count {
+            let controller = &devices[index].controller;
+            controller.update_rumble();
+            if check_battery
+                && let Ok(Some(status)) = controller.read_battery_level()
+                && status.is_low()
+            {
+                if device_count > 1 {
+                    warn!(
+                        "第 {} 个手柄电量低 ({}%)，请及时充电",
+                        index + 1,
+                        status.level
+                    );
+                } else {
+                    warn!("手柄电量低 ({}%)，请及时充电", status.level);
+                }
+            }
+
+            let group = devices[index].group;
+            match controller.read_latest_state(analog_trigger_threshold) {
                 Ok(Some(state)) => {
-                    retry_count = 0;
-
-                    // 处理输入
-                    if let Err(e) = input_handler.handle_input(&state, &scroll_power) {
-                        if handle_error_with_recovery(e) {
-                            return Err(ControllerError::InitializationFailed(
-                                "用户选择退出".to_string(),
-                            ));
+                    devices[index].retry_count = 0;
+                    devices[index].last_state = Some(state);
+                    // 同组的多个手柄合并后一起处理
+                    let Some(state) = merged_state(&devices, group) else {
+                        continue;
+                    };
+                    let input_handler = input_groups.handler(group);
+
+                    // 处理输入（捕获 panic，避免单帧异常导致整个程序退出）
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                        input_handler.handle_input(&state, scroll_state)
+                    }));
+
+                    match result {
+                        Ok(Ok(())) => {
+                            if input_handler.take_rumble_request() {
+                                devices[index].controller.rumble_feedback();
+                            }
+                            if input_handler.take_quit_request() {
+                                info!("收到退出指令，正在退出...");
+                                break 'control;
+                            }
+                        }
+                        Ok(Err(e)) => {
+                            if handle_error_with_recovery(e) {
+                                return Err(ControllerError::InitializationFailed(
+                                    "用户选择退出".to_string(),
+                                ));
+                            }
+                        }
+                        Err(payload) => {
+                            error!("输入处理发生panic: {}", panic_message(payload.as_ref()));
+                            // panic 时按键可能停在按下状态，先释放再决定是否继续
+                            release_held_outputs(input_groups, scroll_state);
+                            if config.input_panic_policy == InputPanicPolicy::Exit {
+                                return Err(ControllerError::InitializationFailed(
+                                    "输入处理发生panic".to_string(),
+                                ));
+                            }
                         }
                     }
                 }
-                Ok(None) => continue, // 没有新数据，继续下一次循环
+                Ok(None) => {
+                    // 没有新数据，但等待组合的按键和按住重复的动作仍需按时执行
+                    let input_handler = input_groups.handler(group);
+                    if let Err(e) = input_handler.handle_timers(time::Instant::now()) {
+                        error!("执行定时动作失败: {}", e);
+                    }
+                }
                 Err(_) => {
-                    retry_count += 1;
+                    devices[index].retry_count += 1;
+                    if devices[index].retry_count >= MAX_RETRIES {
+                        lost_devices.push(index);
+                    }
+                }
+            }
+        }
 
-                    if retry_count >= MAX_RETRIES {
-                        // 设备断开
-                        connection_manager.handle_disconnect();
-                        current_controller = None;
-                        retry_count = 0;
+        // 限制主循环频率：本帧提前完成时休眠剩余时间，降低空闲时的 CPU 占用
+        let remaining = frame_interval.saturating_sub(frame_started.elapsed());
+        if !remaining.is_zero() {
+            thread::sleep(remaining);
+        }
+
+        if lost_devices.is_empty() {
+            continue;
+        }
+        for &index in lost_devices.iter().rev() {
+            let lost = devices.remove(index);
+            // 仍有其他手柄连接时，只停止已没有手柄的输入组的滚动并释放其输出
+            if !devices.is_empty() {
+                warn!("第 {} 个手柄已断开连接", index + 1);
+                if !devices.iter().any(|device| device.group == lost.group) {
+                    let handler = input_groups.handler(lost.group);
+                    handler.stop_scroll(scroll_state);
+                    if let Err(e) = handler.release_all() {
+                        error!("释放按住的输出时出错: {}", e);
                     }
                 }
             }
         }
+
+        if devices.is_empty() {
+            // 设备全部断开
+            connection_manager.handle_disconnect();
+
+            // 滚动立即停止；按住的输出在宽限期结束后释放
+            input_groups.stop_scroll(scroll_state);
+            if disconnect_grace.is_zero() {
+                release_held_outputs(input_groups, scroll_state);
+            } else {
+                disconnected_at = Some(time::Instant::now());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 录制模式：将手柄的原始 HID 报告逐份写入文件，直到 Ctrl+C 或出错
+fn run_capture(path: &Path) -> ControllerResult<()> {
+    println!("正在搜索 {}...", HidController::get_device_info());
+    let controller = HidController::new()?;
+    let mut writer = CaptureWriter::create(path, controller.vendor_id(), controller.product_id())?;
+    println!("开始录制原始报告到 {}，按 Ctrl+C 结束。", path.display());
+
+    let mut count: u64 = 0;
+    loop {
+        if let Some(report) = controller.read_raw_report()? {
+            writer.record(&report)?;
+            count += 1;
+            if count.is_multiple_of(500) {
+                println!("已录制 {} 份报告", count);
+            }
+        }
     }
+}
 
+/// 解析模式：读取录制文件并打印每份报告的字段值
+fn run_analyze(path: &Path) -> ControllerResult<()> {
+    let capture = Capture::load(path).map_err(ControllerError::Serialization)?;
+    capture.print_analysis(ControllerConfig::default().analog_trigger_threshold);
+    Ok(())
+}
+
+/// 校准模式：测量摇杆静止时的最大漂移，确认后将建议的死区写入配置文件
+fn run_calibrate(args: &cli::Args) -> ControllerResult<()> {
+    let config_path = resolve_config_path(args)?;
+    let mut config =
+        ControllerConfig::load_or_create_default(&config_path).map_err(ControllerError::Config)?;
+
+    println!("正在搜索 {}...", HidController::get_device_info());
+    let mut controller = HidController::new()?;
+    controller.set_report_size(config.hid_report_size);
+    println!(
+        "请放开两个摇杆，不要触碰手柄，{} 秒后完成测量...",
+        CALIBRATION_DURATION.as_secs()
+    );
+    let samples =
+        controller.sample_states(config.analog_trigger_threshold, CALIBRATION_DURATION)?;
+    let Some(peak) = StickOffsets::peak(&samples) else {
+        return Err(ControllerError::HidDevice(
+            "测量期间没有收到手柄输入报告".to_string(),
+        ));
+    };
+
+    let suggest = |(x, y): (i16, i16)| {
+        let drift = x.max(y) as f64;
+        (drift * CALIBRATION_MARGIN).ceil().min(i16::MAX as f64) as i16
+    };
+    let left = suggest(peak.left);
+    // 右摇杆死区同时是滚动和导航的触发阈值，只在漂移超出时调大
+    let right = suggest(peak.right).max(config.right_joystick_deadzone);
+
+    println!("共采样 {} 份报告，静止时的最大偏移:", samples.len());
+    println!(
+        " - 左摇杆: X={} Y={}，建议 joystick_deadzone = {}（当前 {}）",
+        peak.left.0, peak.left.1, left, config.joystick_deadzone
+    );
+    println!(
+        " - 右摇杆: X={} Y={}，建议 right_joystick_deadzone = {}（当前 {}）",
+        peak.right.0, peak.right.1, right, config.right_joystick_deadzone
+    );
+    println!("是否将建议值写入 {}？[y/N]", config_path.display());
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    if !answer.trim().eq_ignore_ascii_case("y") {
+        println!("未修改配置文件。");
+        return Ok(());
+    }
+
+    config.joystick_deadzone = left;
+    config.right_joystick_deadzone = right;
+    config
+        .save_to_file(&config_path)
+        .map_err(ControllerError::Config)?;
+    println!("已保存到 {}", config_path.display());
     Ok(())
 }
 
 fn main() {
-    println!("正在启动Xbox手柄控制器应用程序...");
+    // 默认输出 info 及以上级别的日志，可通过 RUST_LOG 调整（如 RUST_LOG=warn 或 debug）
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let args = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("{}", cli::USAGE);
+            process::exit(2);
+        }
+    };
+
+    // 录制/解析等工具模式执行完即退出
+    let result = match &args.mode {
+        Mode::Run => None,
+        Mode::Help => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Mode::Capture(path) => Some(run_capture(path)),
+        Mode::Analyze(path) => Some(run_analyze(path)),
+        Mode::Calibrate => Some(run_calibrate(&args)),
+    };
+    if let Some(result) = result {
+        if let Err(e) = result {
+            handle_error_with_recovery(e);
+        }
+        return;
+    }
+
+    info!("正在启动Xbox手柄控制器应用程序...");
 
     // 1. 加载配置
-    let config = match load_configuration() {
+    let config = match load_configuration(&args) {
         Ok(config) => {
-            println!("配置加载成功");
+            info!("配置加载成功");
             config
         }
         Err(e) => {
@@ -255,19 +925,23 @@ fn main() {
                 return;
             }
             // 使用默认配置继续
-            println!("使用默认配置继续运行");
+            warn!("使用默认配置继续运行");
             ControllerConfig::default()
         }
     };
 
-    println!("正在搜索 {}...", HidController::get_device_info());
+    if config.dry_run {
+        info!("试运行模式：只打印将要执行的操作，不会移动光标或发送按键");
+    }
+
+    info!("正在搜索 {}...", HidController::get_device_info());
 
     // 2. 初始化连接管理器
-    let connection_manager = ConnectionManager::new(&config);
+    let connection_manager = ConnectionManager::new(&config, None);
 
-    // 3. 初始化输入处理器
-    let input_handler = match InputHandler::new(config.clone()) {
-        Ok(handler) => handler,
+    // 3. 初始化输入处理器（多手柄时按配置档案分组）
+    let mut input_groups = match InputGroups::new(&config) {
+        Ok(groups) => groups,
         Err(e) => {
             handle_error_with_recovery(e);
             return;
@@ -277,20 +951,170 @@ fn main() {
     println!("{}", "-".repeat(40));
 
     // 4. 启动滚动步调器线程
-    let scroll_power = Arc::new(Mutex::new(0.0));
-    let pacer_power = Arc::clone(&scroll_power);
-    let pacer_config = config.clone();
-    thread::spawn(move || run_pacer_loop(pacer_power, pacer_config));
+    let scroll_state = Arc::new(Mutex::new(ScrollState::default()));
+    let pacer_state = Arc::clone(&scroll_state);
+    let initial_pacer_config = config.clone();
+    let (pacer_config, pacer_updates) = mpsc::channel();
+    let pacer_running = Arc::new(AtomicBool::new(true));
+    let pacer_flag = Arc::clone(&pacer_running);
+    let pacer = thread::spawn(move || {
+        run_pacer_loop(pacer_state, initial_pacer_config, pacer_updates, pacer_flag)
+    });
 
-    // 5. 运行主控制循环（支持自动重连）
+    // 5. 监视配置文件，修改后热重载
+    let (config_tx, config_rx) = mpsc::channel();
+    if let Ok(config_path) = resolve_config_path(&args) {
+        // 调参命令与热重载共用通道，调整后的配置同样由主循环应用
+        if args.tune {
+            match ControllerConfig::load_from_file(&config_path) {
+                Ok(file_config) => {
+                    tuning::spawn_stdin_tuner(
+                        config_path.clone(),
+                        file_config,
+                        args.profile.clone(),
+                        config_tx.clone(),
+                    );
+                }
+                Err(e) => warn!("无法读取配置文件，调参模式未启用: {}", e),
+            }
+        }
+        ControllerConfig::watch_for_changes(config_path, args.profile.clone(), config_tx);
+    }
+
+    // 6. 运行主控制循环（支持自动重连）
     if let Err(e) = run_control_loop_with_reconnect(
         connection_manager,
-        input_handler,
-        scroll_power,
+        &mut input_groups,
+        &scroll_state,
         &config,
+        &config_rx,
+        &pacer_config,
     ) {
         handle_error_with_recovery(e);
     }
 
-    println!("应用程序已退出。");
+    // 无论以何种方式退出，都释放仍按住的鼠标键和按键，避免输出卡在按下状态
+    release_held_outputs(&mut input_groups, &scroll_state);
+
+    // 通知步调器线程停止滚动并等待其退出
+    pacer_running.store(false, Ordering::Relaxed);
+    if pacer.join().is_err() {
+        error!("步调器线程异常退出");
+    }
+
+    info!("应用程序已退出。");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panic_message_reads_str_and_string_payloads() {
+        let payload = panic::catch_unwind(|| panic!("静态消息")).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "静态消息");
+
+        let payload = panic::catch_unwind(|| panic!("第 {} 帧", 3)).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "第 3 帧");
+
+        let payload = panic::catch_unwind(|| panic::panic_any(42)).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "未知panic");
+    }
+
+    #[test]
+    fn initial_failure_policies() {
+        assert!(initial_failure_message(InitialFailurePolicy::Wait).is_some());
+        assert!(initial_failure_message(InitialFailurePolicy::Silent).is_none());
+        assert!(
+            initial_failure_message(InitialFailurePolicy::Beep)
+                .is_some_and(|message| message.starts_with('\x07'))
+        );
+    }
+
+    #[test]
+    fn initial_connect_timeout_only_applies_before_first_connection() {
+        let ms = time::Duration::from_millis;
+        assert!(initial_connect_timed_out(false, ms(1000), ms(1000)));
+        assert!(!initial_connect_timed_out(false, ms(1000), ms(999)));
+        // 0 表示一直等待
+        assert!(!initial_connect_timed_out(false, ms(0), ms(60_000)));
+        // 连接过之后的断线不受初始超时限制
+        assert!(!initial_connect_timed_out(true, ms(1000), ms(60_000)));
+    }
+
+    #[test]
+    fn smooth_scroll_handler_sends_pixel_scrolls() {
+        let sink = output::RecordingSink::new();
+        let mut handler = ScrollHandler::with_output(Box::new(sink.clone()), ScrollMode::Smooth);
+        handler.scroll(7, Axis::Vertical).unwrap();
+        handler.scroll(-3, Axis::Horizontal).unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![
+                output::OutputEvent::SmoothScroll(7, Axis::Vertical),
+                output::OutputEvent::SmoothScroll(-3, Axis::Horizontal),
+            ]
+        );
+    }
+
+    #[test]
+    fn line_scroll_handler_accumulates_partial_lines() {
+        let sink = output::RecordingSink::new();
+        let mut handler = ScrollHandler::with_output(Box::new(sink.clone()), ScrollMode::Line);
+
+        // 不足一行时不滚动，累积到一整行后按行滚动
+        handler.scroll(6, Axis::Vertical).unwrap();
+        assert!(sink.take().is_empty());
+        handler.scroll(6, Axis::Vertical).unwrap();
+        handler.scroll(25, Axis::Horizontal).unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![
+                output::OutputEvent::Scroll(1, Axis::Vertical),
+                output::OutputEvent::Scroll(2, Axis::Horizontal),
+            ]
+        );
+
+        // 停止后丢弃剩余的不足一行的量
+        handler.stop(Axis::Vertical);
+        handler.scroll(8, Axis::Vertical).unwrap();
+        assert!(sink.take().is_empty());
+    }
+
+    #[test]
+    fn switching_scroll_mode_drops_partial_lines() {
+        let sink = output::RecordingSink::new();
+        let mut handler = ScrollHandler::with_output(Box::new(sink.clone()), ScrollMode::Line);
+        handler.scroll(8, Axis::Vertical).unwrap();
+
+        handler.set_mode(ScrollMode::Smooth);
+        handler.scroll(4, Axis::Vertical).unwrap();
+        handler.set_mode(ScrollMode::Line);
+        handler.scroll(4, Axis::Vertical).unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![output::OutputEvent::SmoothScroll(4, Axis::Vertical)]
+        );
+    }
+
+    #[test]
+    fn shortcut_labels_follow_platform_modifier() {
+        let mut config = ControllerConfig {
+            platform: config::Platform::Windows,
+            ..ControllerConfig::default()
+        };
+        let label = |config: &ControllerConfig| {
+            format_button_action(
+                &config::ButtonAction::CloseWindow,
+                &primary_modifier_label(config),
+            )
+        };
+        assert_eq!(label(&config), "关闭窗口 (Ctrl+W)");
+
+        config.platform = config::Platform::MacOS;
+        assert_eq!(label(&config), "关闭窗口 (Cmd+W)");
+
+        config.primary_modifier = Some("alt".to_string());
+        assert_eq!(label(&config), "关闭窗口 (alt+W)");
+    }
 }
@@ -0,0 +1,130 @@
+use crate::config::{ControllerConfig, MultiControllerConfig};
+use crate::error::{ControllerError, ControllerResult};
+use crate::hid::{ControllerState, HidController};
+use crate::input_handler::{InputHandler, ScrollState};
+use log::{info, warn};
+use std::sync::{Arc, Mutex};
+
+/// 已连接的一个手柄及其读取状态
+pub struct ConnectedDevice {
+    pub controller: HidController,
+    /// 所属输入组的序号
+    pub group: usize,
+    /// 最近一次收到的输入状态，用于与同组其他手柄合并
+    pub last_state: Option<ControllerState>,
+    /// 连续读取失败的次数
+    pub retry_count: u32,
+}
+
+/// 合并同组所有手柄最近一次的输入状态
+pub fn merged_state(devices: &[ConnectedDevice], group: usize) -> Option<ControllerState> {
+    ControllerState::merge(
+        devices
+            .iter()
+            .filter(|device| device.group == group)
+            .filter_map(|device| device.last_state.as_ref()),
+    )
+}
+
+/// 按配置档案分组的输入处理器
+///
+/// 第0组使用当前配置，其余每组对应 `device_profiles` 中的一个档案。
+/// 同组的手柄共用一个输入处理器，状态合并后一起处理；各组共用同一个滚动状态，
+/// 步调器按各组滚动分量之和滚动。
+/// 分组在启动时确定，修改 `multi_controller` 后需重启程序才会生效。
+pub struct InputGroups {
+    groups: Vec<(Option<String>, InputHandler)>,
+    multi_controller: MultiControllerConfig,
+}
+
+impl InputGroups {
+    /// 为当前配置和每个被指定的配置档案各创建一个输入处理器
+    pub fn new(config: &ControllerConfig) -> ControllerResult<Self> {
+        let mut groups = vec![(None, InputHandler::new(config.clone())?)];
+        for name in &config.multi_controller.device_profiles {
+            if name.is_empty() || groups.iter().any(|(n, _)| n.as_ref() == Some(name)) {
+                continue;
+            }
+            let profile_config = config.with_profile(name).map_err(ControllerError::Config)?;
+            groups.push((Some(name.clone()), InputHandler::new(profile_config)?));
+        }
+
+        Ok(Self {
+            groups,
+            multi_controller: config.multi_controller.clone(),
+        })
+    }
+
+    /// 按连接顺序为手柄分配输入组
+    pub fn attach(&self, controllers: Vec<HidController>) -> Vec<ConnectedDevice> {
+        controllers
+            .into_iter()
+            .enumerate()
+            .map(|(index, controller)| {
+                let group = self.group_for(index);
+                if let Some(name) = &self.groups[group].0 {
+                    info!("第 {} 个手柄使用配置档案: {}", index + 1, name);
+                }
+                ConnectedDevice {
+                    controller,
+                    group,
+                    last_state: None,
+                    retry_count: 0,
+                }
+            })
+            .collect()
+    }
+
+    /// 第 `index` 个连接的手柄（从0开始）所属的输入组
+    fn group_for(&self, index: usize) -> usize {
+        self.multi_controller
+            .profile_for(index)
+            .and_then(|name| {
+                self.groups
+                    .iter()
+                    .position(|(n, _)| n.as_deref() == Some(name))
+            })
+            .unwrap_or(0)
+    }
+
+    /// 指定组的输入处理器
+    pub fn handler(&mut self, group: usize) -> &mut InputHandler {
+        &mut self.groups[group].1
+    }
+
+    /// 使用当前配置的输入处理器（执行连接动作等）
+    pub fn primary(&mut self) -> &mut InputHandler {
+        self.handler(0)
+    }
+
+    /// 热重载：各组在新配置上重新应用自己的配置档案
+    pub fn update_config(&mut self, config: &ControllerConfig) {
+        for (profile, handler) in &mut self.groups {
+            match profile {
+                None => handler.update_config(config.clone()),
+                Some(name) => match config.with_profile(name) {
+                    Ok(profile_config) => handler.update_config(profile_config),
+                    Err(e) => warn!("配置档案 {} 的手柄未更新配置: {}", name, e),
+                },
+            }
+        }
+    }
+
+    /// 停止所有组的滚动
+    pub fn stop_scroll(&mut self, scroll_state: &Arc<Mutex<ScrollState>>) {
+        for (_, handler) in &mut self.groups {
+            handler.stop_scroll(scroll_state);
+        }
+    }
+
+    /// 释放所有组仍处于按住状态的输出
+    pub fn release_all(&mut self) -> ControllerResult<()> {
+        let mut result = Ok(());
+        for (_, handler) in &mut self.groups {
+            if let Err(e) = handler.release_all() {
+                result = Err(e);
+            }
+        }
+        result
+    }
+}
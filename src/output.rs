@@ -0,0 +1,769 @@
+use crate::config::{OutputBackend, Platform, SwipeDirection};
+use crate::error::{ControllerError, ControllerResult};
+use enigo::{
+    Axis, Button, Coordinate, Direction, Enigo, InputError, InputResult, Key, Keyboard, Mouse,
+    Settings,
+};
+
+/// 输出后端：将光标移动、鼠标按键和键盘事件发送给系统
+pub trait OutputSink {
+    /// 获取当前光标位置
+    fn location(&self) -> InputResult<(i32, i32)>;
+    /// 移动光标
+    fn move_mouse(&mut self, x: i32, y: i32, coordinate: Coordinate) -> InputResult<()>;
+    /// 按下/释放/点击鼠标按键
+    fn button(&mut self, button: Button, direction: Direction) -> InputResult<()>;
+    /// 按下/释放/点击键盘按键
+    fn key(&mut self, key: Key, direction: Direction) -> InputResult<()>;
+    /// 输入一段文本
+    fn text(&mut self, text: &str) -> InputResult<()>;
+    /// 获取主屏幕尺寸
+    fn main_display(&self) -> InputResult<(i32, i32)>;
+    /// 发送翻页滑动手势
+    fn swipe(&mut self, direction: SwipeDirection) -> InputResult<()>;
+    /// 按行滚动
+    fn scroll(&mut self, length: i32, axis: Axis) -> InputResult<()>;
+    /// 按像素平滑滚动
+    fn smooth_scroll(&mut self, length: i32, axis: Axis) -> InputResult<()>;
+    /// 是否原生支持相对移动（无需先查询光标位置），支持时光标移动直接使用相对坐标
+    fn native_relative_move(&self) -> bool {
+        false
+    }
+    /// 是否能合成翻页手势，不支持时调用方改用等效的导航快捷键
+    fn native_swipe(&self) -> bool {
+        false
+    }
+}
+
+impl OutputSink for Enigo {
+    fn location(&self) -> InputResult<(i32, i32)> {
+        Mouse::location(self)
+    }
+
+    fn move_mouse(&mut self, x: i32, y: i32, coordinate: Coordinate) -> InputResult<()> {
+        Mouse::move_mouse(self, x, y, coordinate)
+    }
+
+    fn button(&mut self, button: Button, direction: Direction) -> InputResult<()> {
+        Mouse::button(self, button, direction)
+    }
+
+    fn key(&mut self, key: Key, direction: Direction) -> InputResult<()> {
+        Keyboard::key(self, key, direction)
+    }
+
+    fn text(&mut self, text: &str) -> InputResult<()> {
+        Keyboard::text(self, text)
+    }
+
+    fn main_display(&self) -> InputResult<(i32, i32)> {
+        Mouse::main_display(self)
+    }
+
+    /// Enigo 无法合成手势事件，由调用方按配置的平台和主修饰键改用导航快捷键
+    fn swipe(&mut self, _direction: SwipeDirection) -> InputResult<()> {
+        Err(InputError::Simulate("Enigo 后端不支持滑动手势"))
+    }
+
+    fn scroll(&mut self, length: i32, axis: Axis) -> InputResult<()> {
+        Mouse::scroll(self, length, axis)
+    }
+
+    fn smooth_scroll(&mut self, length: i32, axis: Axis) -> InputResult<()> {
+        Mouse::smooth_scroll(self, length, axis)
+    }
+}
+
+/// 试运行后端：只打印将要执行的操作，不向系统发送任何输入事件
+///
+/// 创建时读取一次真实的屏幕尺寸和光标位置，之后在内部模拟光标位置。
+pub struct DryRunSink {
+    cursor: (i32, i32),
+    display: (i32, i32),
+}
+
+impl DryRunSink {
+    pub fn new() -> ControllerResult<Self> {
+        let enigo = new_enigo()?;
+        let display = Mouse::main_display(&enigo).map_err(|e| {
+            ControllerError::InitializationFailed(format!("获取屏幕尺寸失败: {}", e))
+        })?;
+        let cursor = Mouse::location(&enigo).unwrap_or((display.0 / 2, display.1 / 2));
+        Ok(Self { cursor, display })
+    }
+}
+
+impl OutputSink for DryRunSink {
+    fn location(&self) -> InputResult<(i32, i32)> {
+        Ok(self.cursor)
+    }
+
+    fn move_mouse(&mut self, x: i32, y: i32, coordinate: Coordinate) -> InputResult<()> {
+        let target = match coordinate {
+            Coordinate::Abs => (x, y),
+            Coordinate::Rel => (self.cursor.0 + x, self.cursor.1 + y),
+        };
+        println!(
+            "[试运行] 移动光标 ({:+}, {:+}) -> ({}, {})",
+            target.0 - self.cursor.0,
+            target.1 - self.cursor.1,
+            target.0,
+            target.1
+        );
+        self.cursor = target;
+        Ok(())
+    }
+
+    fn button(&mut self, button: Button, direction: Direction) -> InputResult<()> {
+        println!("[试运行] 鼠标按键 {:?} {:?}", button, direction);
+        Ok(())
+    }
+
+    fn key(&mut self, key: Key, direction: Direction) -> InputResult<()> {
+        println!("[试运行] 键盘按键 {:?} {:?}", key, direction);
+        Ok(())
+    }
+
+    fn text(&mut self, text: &str) -> InputResult<()> {
+        println!("[试运行] 输入文本 {:?}", text);
+        Ok(())
+    }
+
+    fn main_display(&self) -> InputResult<(i32, i32)> {
+        Ok(self.display)
+    }
+
+    fn swipe(&mut self, direction: SwipeDirection) -> InputResult<()> {
+        println!("[试运行] 滑动手势 {:?}", direction);
+        Ok(())
+    }
+
+    fn scroll(&mut self, length: i32, axis: Axis) -> InputResult<()> {
+        println!("[试运行] 滚动 {} 行 ({:?})", length, axis);
+        Ok(())
+    }
+
+    fn smooth_scroll(&mut self, length: i32, axis: Axis) -> InputResult<()> {
+        println!("[试运行] 平滑滚动 {} ({:?})", length, axis);
+        Ok(())
+    }
+
+    fn native_swipe(&self) -> bool {
+        true
+    }
+}
+
+/// 记录仍处于按下状态的鼠标按键和键盘按键的输出后端包装
+///
+/// 按下成功时记录，释放成功时移除，点击不改变记录。`release_held` 释放所有记录的按键，
+/// 即使动作执行到一半出错（例如组合键的主键点击失败、修饰键未释放），断线或退出时也不会有按键卡住。
+pub struct TrackedOutput {
+    inner: Box<dyn OutputSink>,
+    keys: Vec<Key>,
+    buttons: Vec<Button>,
+}
+
+impl TrackedOutput {
+    pub fn new(inner: Box<dyn OutputSink>) -> Self {
+        Self {
+            inner,
+            keys: Vec::new(),
+            buttons: Vec::new(),
+        }
+    }
+
+    /// 释放所有仍按下的按键（键盘按键按按下的逆序释放），某个按键释放失败时继续释放其余按键
+    pub fn release_held(&mut self) -> InputResult<()> {
+        let mut result = Ok(());
+        for button in std::mem::take(&mut self.buttons) {
+            if let Err(e) = self.inner.button(button, Direction::Release) {
+                result = Err(e);
+            }
+        }
+        for key in std::mem::take(&mut self.keys).into_iter().rev() {
+            if let Err(e) = self.inner.key(key, Direction::Release) {
+                result = Err(e);
+            }
+        }
+        result
+    }
+
+    /// 根据发送成功的事件更新按下记录
+    fn track<T: PartialEq>(held: &mut Vec<T>, item: T, direction: Direction) {
+        match direction {
+            Direction::Press if !held.contains(&item) => held.push(item),
+            Direction::Release => held.retain(|h| *h != item),
+            _ => {}
+        }
+    }
+}
+
+impl OutputSink for TrackedOutput {
+    fn location(&self) -> InputResult<(i32, i32)> {
+        self.inner.location()
+    }
+
+    fn move_mouse(&mut self, x: i32, y: i32, coordinate: Coordinate) -> InputResult<()> {
+        self.inner.move_mouse(x, y, coordinate)
+    }
+
+    fn button(&mut self, button: Button, direction: Direction) -> InputResult<()> {
+        self.inner.button(button, direction)?;
+        Self::track(&mut self.buttons, button, direction);
+        Ok(())
+    }
+
+    fn key(&mut self, key: Key, direction: Direction) -> InputResult<()> {
+        self.inner.key(key, direction)?;
+        Self::track(&mut self.keys, key, direction);
+        Ok(())
+    }
+
+    fn text(&mut self, text: &str) -> InputResult<()> {
+        self.inner.text(text)
+    }
+
+    fn main_display(&self) -> InputResult<(i32, i32)> {
+        self.inner.main_display()
+    }
+
+    fn swipe(&mut self, direction: SwipeDirection) -> InputResult<()> {
+        self.inner.swipe(direction)
+    }
+
+    fn scroll(&mut self, length: i32, axis: Axis) -> InputResult<()> {
+        self.inner.scroll(length, axis)
+    }
+
+    fn smooth_scroll(&mut self, length: i32, axis: Axis) -> InputResult<()> {
+        self.inner.smooth_scroll(length, axis)
+    }
+
+    fn native_relative_move(&self) -> bool {
+        self.inner.native_relative_move()
+    }
+
+    fn native_swipe(&self) -> bool {
+        self.inner.native_swipe()
+    }
+}
+
+/// 根据配置创建输出后端，试运行时总是使用只打印操作的后端
+pub fn create_output_sink(
+    backend: OutputBackend,
+    dry_run: bool,
+) -> ControllerResult<Box<dyn OutputSink>> {
+    if dry_run {
+        return Ok(Box::new(DryRunSink::new()?));
+    }
+    match backend {
+        OutputBackend::Enigo => Ok(Box::new(new_enigo()?)),
+        #[cfg(target_os = "macos")]
+        OutputBackend::CoreGraphics => Ok(Box::new(macos::CoreGraphicsSink::new()?)),
+        #[cfg(not(target_os = "macos"))]
+        OutputBackend::CoreGraphics => Err(ControllerError::InitializationFailed(
+            "CoreGraphics 输出后端仅支持 macOS".to_string(),
+        )),
+    }
+}
+
+/// 系统的主修饰键：macOS 为 Cmd，其他系统为 Ctrl
+pub fn primary_modifier(platform: Platform) -> Key {
+    match platform {
+        Platform::MacOS => Key::Meta,
+        Platform::Linux | Platform::Windows => Key::Control,
+    }
+}
+
+/// 页面后退/前进的快捷键：macOS 为主修饰键+[ / ]，其他系统为 Alt+左/右箭头
+pub fn navigation_shortcut(
+    platform: Platform,
+    primary: Key,
+    direction: SwipeDirection,
+) -> (Key, Key) {
+    match (platform, direction) {
+        (Platform::MacOS, SwipeDirection::Back) => (primary, Key::Unicode('[')),
+        (Platform::MacOS, SwipeDirection::Forward) => (primary, Key::Unicode(']')),
+        (_, SwipeDirection::Back) => (Key::Alt, Key::LeftArrow),
+        (_, SwipeDirection::Forward) => (Key::Alt, Key::RightArrow),
+    }
+}
+
+fn new_enigo() -> ControllerResult<Enigo> {
+    Enigo::new(&Settings::default())
+        .map_err(|e| ControllerError::InitializationFailed(format!("Enigo初始化失败: {}", e)))
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use super::{OutputSink, new_enigo};
+    use crate::config::SwipeDirection;
+    use crate::error::{ControllerError, ControllerResult};
+    use core_graphics::display::CGDisplay;
+    use core_graphics::event::{
+        CGEvent, CGEventField, CGEventTapLocation, CGEventType, CGMouseButton, EventField,
+        ScrollEventUnit,
+    };
+    use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+    use core_graphics::geometry::CGPoint;
+    use enigo::{
+        Axis, Button, Coordinate, Direction, Enigo, InputError, InputResult, Key, Keyboard, Mouse,
+    };
+    use log::warn;
+    use std::sync::mpsc::{self, Sender};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    // 滚轮事件的手势阶段字段（kCGScrollWheelEventScrollPhase）及取值
+    const SCROLL_PHASE_FIELD: CGEventField = 99;
+    const SCROLL_PHASE_BEGAN: i64 = 1;
+    const SCROLL_PHASE_CHANGED: i64 = 2;
+    const SCROLL_PHASE_ENDED: i64 = 4;
+    // 手势事件之间的间隔，接近触控板的事件频率
+    const SWIPE_FRAME_INTERVAL: Duration = Duration::from_millis(8);
+    // 同一按键两次按下间隔不超过该值时计为连击（macOS 默认的双击速度）
+    const MULTI_CLICK_WINDOW: Duration = Duration::from_millis(500);
+
+    /// 滑动手势每帧的水平像素位移
+    ///
+    /// 正值表示内容向右移动（双指向右滑动，即后退）。位移先加速后减速，
+    /// 总量足以越过应用的翻页阈值。
+    fn swipe_deltas(direction: SwipeDirection) -> Vec<i32> {
+        const PROFILE: [i32; 8] = [6, 14, 24, 32, 32, 24, 14, 6];
+        let sign = match direction {
+            SwipeDirection::Back => 1,
+            SwipeDirection::Forward => -1,
+        };
+        PROFILE.iter().map(|delta| delta * sign).collect()
+    }
+
+    /// 发送一帧带手势阶段的连续水平滚动事件
+    fn post_swipe_event(source: &CGEventSource, delta_x: i32, phase: i64) -> InputResult<()> {
+        let event =
+            CGEvent::new_scroll_event(source.clone(), ScrollEventUnit::PIXEL, 2, 0, delta_x, 0)
+                .map_err(|_| InputError::Simulate("无法创建滑动手势事件"))?;
+        event.set_integer_value_field(EventField::SCROLL_WHEEL_EVENT_IS_CONTINUOUS, 1);
+        event.set_integer_value_field(SCROLL_PHASE_FIELD, phase);
+        event.post(CGEventTapLocation::HID);
+        Ok(())
+    }
+
+    /// 逐帧发送一次完整的翻页手势
+    fn post_swipe(source: &CGEventSource, direction: SwipeDirection) -> InputResult<()> {
+        post_swipe_event(source, 0, SCROLL_PHASE_BEGAN)?;
+        for delta in swipe_deltas(direction) {
+            thread::sleep(SWIPE_FRAME_INTERVAL);
+            post_swipe_event(source, delta, SCROLL_PHASE_CHANGED)?;
+        }
+        thread::sleep(SWIPE_FRAME_INTERVAL);
+        post_swipe_event(source, 0, SCROLL_PHASE_ENDED)
+    }
+
+    /// 启动发送翻页手势的后台线程
+    ///
+    /// 手势各帧之间需要间隔，放在后台线程发送以免阻塞输入线程；多个手势按请求顺序依次发送。
+    fn spawn_swipe_worker() -> ControllerResult<Sender<SwipeDirection>> {
+        let (sender, receiver) = mpsc::channel::<SwipeDirection>();
+        thread::Builder::new()
+            .name("swipe".to_string())
+            .spawn(move || {
+                // 事件源不能跨线程传递，线程内单独创建
+                let Ok(source) = CGEventSource::new(CGEventSourceStateID::HIDSystemState) else {
+                    warn!("创建滑动手势事件源失败");
+                    return;
+                };
+                for direction in receiver {
+                    if let Err(e) = post_swipe(&source, direction) {
+                        warn!("发送滑动手势失败: {}", e);
+                    }
+                }
+            })
+            .map_err(|e| {
+                ControllerError::InitializationFailed(format!("启动滑动手势线程失败: {}", e))
+            })?;
+        Ok(sender)
+    }
+
+    /// 直接通过 CoreGraphics 发送鼠标事件和翻页手势的后端
+    ///
+    /// 相对移动从事件源创建一个事件（自带当前光标位置），改为移动事件并写入位移后直接发送，
+    /// 不再单独查询位置再发送绝对坐标；按住鼠标键时发送拖拽事件，保证拖拽手势正常工作。
+    /// 键盘和滚动事件仍交给 Enigo 处理。
+    pub struct CoreGraphicsSink {
+        source: CGEventSource,
+        keyboard: Enigo,
+        left_down: bool,
+        right_down: bool,
+        last_click: Option<(Button, Instant)>, // 最近一次按下的鼠标键及时刻
+        click_count: i64,                      // 当前连击次数，写入事件供系统识别双击
+        swipes: Sender<SwipeDirection>,        // 交给后台线程发送的翻页手势
+    }
+
+    impl CoreGraphicsSink {
+        pub fn new() -> ControllerResult<Self> {
+            let source =
+                CGEventSource::new(CGEventSourceStateID::HIDSystemState).map_err(|_| {
+                    ControllerError::InitializationFailed(
+                        "创建 CoreGraphics 事件源失败".to_string(),
+                    )
+                })?;
+
+            Ok(Self {
+                source,
+                keyboard: new_enigo()?,
+                left_down: false,
+                right_down: false,
+                last_click: None,
+                click_count: 0,
+                swipes: spawn_swipe_worker()?,
+            })
+        }
+
+        fn cursor_position(&self) -> InputResult<CGPoint> {
+            CGEvent::new(self.source.clone())
+                .map(|event| event.location())
+                .map_err(|_| InputError::Simulate("无法读取光标位置"))
+        }
+
+        fn post_mouse_event(
+            &self,
+            event_type: CGEventType,
+            position: CGPoint,
+            button: CGMouseButton,
+        ) -> InputResult<()> {
+            let event = CGEvent::new_mouse_event(self.source.clone(), event_type, position, button)
+                .map_err(|_| InputError::Simulate("无法创建鼠标事件"))?;
+            event.post(CGEventTapLocation::HID);
+            Ok(())
+        }
+
+        /// 发送相对移动事件：事件位置为当前位置加位移（限制在主屏幕内），并写入实际位移
+        fn post_relative_move(
+            &self,
+            delta_x: i32,
+            delta_y: i32,
+            event_type: CGEventType,
+            button: CGMouseButton,
+        ) -> InputResult<()> {
+            let event = CGEvent::new(self.source.clone())
+                .map_err(|_| InputError::Simulate("无法创建鼠标事件"))?;
+            let current = event.location();
+            let bounds = CGDisplay::main().bounds();
+            let target = CGPoint::new(
+                (current.x + delta_x as f64)
+                    .clamp(bounds.origin.x, bounds.origin.x + bounds.size.width - 1.0),
+                (current.y + delta_y as f64)
+                    .clamp(bounds.origin.y, bounds.origin.y + bounds.size.height - 1.0),
+            );
+
+            event.set_type(event_type);
+            event.set_location(target);
+            event.set_integer_value_field(
+                EventField::MOUSE_EVENT_DELTA_X,
+                (target.x - current.x).round() as i64,
+            );
+            event.set_integer_value_field(
+                EventField::MOUSE_EVENT_DELTA_Y,
+                (target.y - current.y).round() as i64,
+            );
+            event.set_integer_value_field(EventField::MOUSE_EVENT_BUTTON_NUMBER, button as i64);
+            event.post(CGEventTapLocation::HID);
+            Ok(())
+        }
+
+        /// 发送带连击次数的鼠标按下/松开事件，连击次数为2时系统识别为双击
+        fn post_click_event(
+            &self,
+            event_type: CGEventType,
+            position: CGPoint,
+            button: CGMouseButton,
+        ) -> InputResult<()> {
+            let event = CGEvent::new_mouse_event(self.source.clone(), event_type, position, button)
+                .map_err(|_| InputError::Simulate("无法创建鼠标事件"))?;
+            event.set_integer_value_field(EventField::MOUSE_EVENT_CLICK_STATE, self.click_count);
+            event.post(CGEventTapLocation::HID);
+            Ok(())
+        }
+    }
+
+    impl OutputSink for CoreGraphicsSink {
+        fn location(&self) -> InputResult<(i32, i32)> {
+            let point = self.cursor_position()?;
+            Ok((point.x.round() as i32, point.y.round() as i32))
+        }
+
+        fn move_mouse(&mut self, x: i32, y: i32, coordinate: Coordinate) -> InputResult<()> {
+            // 按住鼠标键时移动需发送拖拽事件
+            let (event_type, button) = if self.left_down {
+                (CGEventType::LeftMouseDragged, CGMouseButton::Left)
+            } else if self.right_down {
+                (CGEventType::RightMouseDragged, CGMouseButton::Right)
+            } else {
+                (CGEventType::MouseMoved, CGMouseButton::Left)
+            };
+
+            match coordinate {
+                Coordinate::Abs => {
+                    self.post_mouse_event(event_type, CGPoint::new(x as f64, y as f64), button)
+                }
+                Coordinate::Rel => self.post_relative_move(x, y, event_type, button),
+            }
+        }
+
+        fn button(&mut self, button: Button, direction: Direction) -> InputResult<()> {
+            let (down, up, cg_button) = match button {
+                Button::Left => (
+                    CGEventType::LeftMouseDown,
+                    CGEventType::LeftMouseUp,
+                    CGMouseButton::Left,
+                ),
+                Button::Right => (
+                    CGEventType::RightMouseDown,
+                    CGEventType::RightMouseUp,
+                    CGMouseButton::Right,
+                ),
+                _ => return Err(InputError::InvalidInput("不支持的鼠标按键")),
+            };
+            let position = self.cursor_position()?;
+
+            if matches!(direction, Direction::Press | Direction::Click) {
+                let now = Instant::now();
+                self.click_count = match self.last_click {
+                    Some((last, at)) if last == button && now - at <= MULTI_CLICK_WINDOW => {
+                        self.click_count + 1
+                    }
+                    _ => 1,
+                };
+                self.last_click = Some((button, now));
+                self.post_click_event(down, position, cg_button)?;
+            }
+            if matches!(direction, Direction::Release | Direction::Click) {
+                self.post_click_event(up, position, cg_button)?;
+            }
+
+            let held = matches!(direction, Direction::Press);
+            match button {
+                Button::Left => self.left_down = held,
+                _ => self.right_down = held,
+            }
+            Ok(())
+        }
+
+        fn key(&mut self, key: Key, direction: Direction) -> InputResult<()> {
+            self.keyboard.key(key, direction)
+        }
+
+        fn text(&mut self, text: &str) -> InputResult<()> {
+            self.keyboard.text(text)
+        }
+
+        fn scroll(&mut self, length: i32, axis: Axis) -> InputResult<()> {
+            self.keyboard.scroll(length, axis)
+        }
+
+        fn smooth_scroll(&mut self, length: i32, axis: Axis) -> InputResult<()> {
+            self.keyboard.smooth_scroll(length, axis)
+        }
+
+        fn main_display(&self) -> InputResult<(i32, i32)> {
+            let bounds = CGDisplay::main().bounds();
+            Ok((bounds.size.width as i32, bounds.size.height as i32))
+        }
+
+        fn native_relative_move(&self) -> bool {
+            true
+        }
+
+        fn native_swipe(&self) -> bool {
+            true
+        }
+
+        fn swipe(&mut self, direction: SwipeDirection) -> InputResult<()> {
+            self.swipes
+                .send(direction)
+                .map_err(|_| InputError::Simulate("滑动手势线程已退出"))
+        }
+    }
+}
+
+/// 测试用输出事件
+#[cfg(test)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutputEvent {
+    Move(i32, i32, Coordinate),
+    Button(Button, Direction),
+    Key(Key, Direction),
+    Text(String),
+    Swipe(SwipeDirection),
+    Scroll(i32, Axis),
+    SmoothScroll(i32, Axis),
+}
+
+/// 测试用输出后端：记录所有输出事件，屏幕尺寸固定为 1920x1080
+///
+/// 克隆出的后端共享同一份记录，交给 `InputHandler` 后仍可通过保留的克隆检查输出。
+#[cfg(test)]
+#[derive(Clone)]
+pub struct RecordingSink {
+    events: std::rc::Rc<std::cell::RefCell<Vec<OutputEvent>>>,
+    cursor: std::rc::Rc<std::cell::Cell<(i32, i32)>>,
+    failing: std::rc::Rc<std::cell::Cell<bool>>,
+    locations: std::rc::Rc<std::cell::Cell<usize>>,
+    native: bool,
+}
+
+#[cfg(test)]
+impl RecordingSink {
+    pub const DISPLAY: (i32, i32) = (1920, 1080);
+
+    pub fn new() -> Self {
+        Self {
+            events: Default::default(),
+            cursor: std::rc::Rc::new(std::cell::Cell::new((
+                Self::DISPLAY.0 / 2,
+                Self::DISPLAY.1 / 2,
+            ))),
+            failing: Default::default(),
+            locations: Default::default(),
+            native: false,
+        }
+    }
+
+    /// 与 CoreGraphics 后端一样原生支持相对移动和翻页手势的记录后端
+    pub fn relative() -> Self {
+        Self {
+            native: true,
+            ..Self::new()
+        }
+    }
+
+    /// 取出目前记录的事件并清空记录
+    pub fn take(&self) -> Vec<OutputEvent> {
+        std::mem::take(&mut *self.events.borrow_mut())
+    }
+
+    /// 目前为止查询光标位置的次数
+    pub fn location_calls(&self) -> usize {
+        self.locations.get()
+    }
+
+    /// 设置之后的输出是否全部失败（不记录事件），用于测试输出出错时的处理
+    pub fn set_failing(&self, failing: bool) {
+        self.failing.set(failing);
+    }
+
+    fn record(&self, event: OutputEvent) -> InputResult<()> {
+        if self.failing.get() {
+            return Err(enigo::InputError::Simulate("测试设置的输出失败"));
+        }
+        self.events.borrow_mut().push(event);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl OutputSink for RecordingSink {
+    fn location(&self) -> InputResult<(i32, i32)> {
+        self.locations.set(self.locations.get() + 1);
+        Ok(self.cursor.get())
+    }
+
+    fn move_mouse(&mut self, x: i32, y: i32, coordinate: Coordinate) -> InputResult<()> {
+        let (cx, cy) = self.cursor.get();
+        self.cursor.set(match coordinate {
+            Coordinate::Abs => (x, y),
+            Coordinate::Rel => (cx + x, cy + y),
+        });
+        self.record(OutputEvent::Move(x, y, coordinate))
+    }
+
+    fn button(&mut self, button: Button, direction: Direction) -> InputResult<()> {
+        self.record(OutputEvent::Button(button, direction))
+    }
+
+    fn key(&mut self, key: Key, direction: Direction) -> InputResult<()> {
+        self.record(OutputEvent::Key(key, direction))
+    }
+
+    fn text(&mut self, text: &str) -> InputResult<()> {
+        self.record(OutputEvent::Text(text.to_string()))
+    }
+
+    fn main_display(&self) -> InputResult<(i32, i32)> {
+        Ok(Self::DISPLAY)
+    }
+
+    fn swipe(&mut self, direction: SwipeDirection) -> InputResult<()> {
+        self.record(OutputEvent::Swipe(direction))
+    }
+
+    fn scroll(&mut self, length: i32, axis: Axis) -> InputResult<()> {
+        self.record(OutputEvent::Scroll(length, axis))
+    }
+
+    fn smooth_scroll(&mut self, length: i32, axis: Axis) -> InputResult<()> {
+        self.record(OutputEvent::SmoothScroll(length, axis))
+    }
+
+    fn native_relative_move(&self) -> bool {
+        self.native
+    }
+
+    fn native_swipe(&self) -> bool {
+        self.native
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracked_output_releases_held_keys_in_reverse_order() {
+        let sink = RecordingSink::new();
+        let mut output = TrackedOutput::new(Box::new(sink.clone()));
+        output.key(Key::Control, Direction::Press).unwrap();
+        output.key(Key::Shift, Direction::Press).unwrap();
+        output.key(Key::Unicode('a'), Direction::Click).unwrap();
+        output.button(Button::Left, Direction::Press).unwrap();
+        sink.take();
+
+        output.release_held().unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![
+                OutputEvent::Button(Button::Left, Direction::Release),
+                OutputEvent::Key(Key::Shift, Direction::Release),
+                OutputEvent::Key(Key::Control, Direction::Release),
+            ]
+        );
+
+        // 已释放的按键不会再次释放
+        output.release_held().unwrap();
+        assert!(sink.take().is_empty());
+    }
+
+    #[test]
+    fn tracked_output_forgets_released_keys() {
+        let sink = RecordingSink::new();
+        let mut output = TrackedOutput::new(Box::new(sink.clone()));
+        output.key(Key::Alt, Direction::Press).unwrap();
+        output.key(Key::Alt, Direction::Release).unwrap();
+        sink.take();
+
+        output.release_held().unwrap();
+        assert!(sink.take().is_empty());
+    }
+
+    #[test]
+    fn navigation_shortcut_uses_given_primary_modifier_on_macos() {
+        assert_eq!(
+            navigation_shortcut(Platform::MacOS, Key::Control, SwipeDirection::Back),
+            (Key::Control, Key::Unicode('['))
+        );
+        assert_eq!(
+            navigation_shortcut(Platform::Windows, Key::Control, SwipeDirection::Forward),
+            (Key::Alt, Key::RightArrow)
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use crate::config::ControllerConfig;
+use std::io::{self, BufRead};
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use std::thread;
+
+/// 调参命令说明
+pub const TUNING_HELP: &str = "调参命令:
+  <参数> +<数值>   增大参数，如 sens +1
+  <参数> -<数值>   减小参数，如 deadzone -100
+  <参数> <数值>    直接设置参数，如 gyro 0.5
+  show             显示当前参数
+  save             将当前配置写回配置文件
+  help             显示此帮助信息
+参数: sens (摇杆灵敏度)、gyro (陀螺仪灵敏度)、scroll (滚动灵敏度)、
+      deadzone (左摇杆死区)、rdeadzone (右摇杆死区)、gyrodz (陀螺仪死区)";
+
+/// 可在运行时调整的参数
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TunableField {
+    JoystickSensitivity,
+    GyroSensitivity,
+    ScrollSensitivity,
+    JoystickDeadzone,
+    RightJoystickDeadzone,
+    GyroDeadzone,
+}
+
+/// 命令中的参数简称及对应的参数，同时决定 `show` 的显示顺序
+const FIELDS: &[(&str, TunableField)] = &[
+    ("sens", TunableField::JoystickSensitivity),
+    ("gyro", TunableField::GyroSensitivity),
+    ("scroll", TunableField::ScrollSensitivity),
+    ("deadzone", TunableField::JoystickDeadzone),
+    ("rdeadzone", TunableField::RightJoystickDeadzone),
+    ("gyrodz", TunableField::GyroDeadzone),
+];
+
+impl TunableField {
+    fn parse(name: &str) -> Option<Self> {
+        FIELDS
+            .iter()
+            .find(|(short, _)| short.eq_ignore_ascii_case(name))
+            .map(|&(_, field)| field)
+    }
+
+    /// 配置文件中的字段名
+    fn key(self) -> &'static str {
+        match self {
+            TunableField::JoystickSensitivity => "joystick_sensitivity",
+            TunableField::GyroSensitivity => "gyro_sensitivity",
+            TunableField::ScrollSensitivity => "direct_scroll_sensitivity",
+            TunableField::JoystickDeadzone => "joystick_deadzone",
+            TunableField::RightJoystickDeadzone => "right_joystick_deadzone",
+            TunableField::GyroDeadzone => "gyro_deadzone",
+        }
+    }
+
+    fn get(self, config: &ControllerConfig) -> f64 {
+        match self {
+            TunableField::JoystickSensitivity => config.joystick_sensitivity,
+            TunableField::GyroSensitivity => config.gyro_sensitivity,
+            TunableField::ScrollSensitivity => config.direct_scroll_sensitivity,
+            TunableField::JoystickDeadzone => config.joystick_deadzone as f64,
+            TunableField::RightJoystickDeadzone => config.right_joystick_deadzone as f64,
+            TunableField::GyroDeadzone => config.gyro_deadzone as f64,
+        }
+    }
+
+    /// 设置参数，死区四舍五入为整数（超出 i16 范围时取边界值，由配置校验拒绝负数）
+    fn set(self, config: &mut ControllerConfig, value: f64) {
+        let deadzone = value.round().clamp(i16::MIN as f64, i16::MAX as f64) as i16;
+        match self {
+            TunableField::JoystickSensitivity => config.set_joystick_sensitivity(value),
+            TunableField::GyroSensitivity => config.gyro_sensitivity = value,
+            TunableField::ScrollSensitivity => config.direct_scroll_sensitivity = value,
+            TunableField::JoystickDeadzone => config.joystick_deadzone = deadzone,
+            TunableField::RightJoystickDeadzone => config.right_joystick_deadzone = deadzone,
+            TunableField::GyroDeadzone => config.gyro_deadzone = deadzone,
+        }
+    }
+}
+
+/// 一条调参命令
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TuningCommand {
+    /// 在当前值上增减
+    Adjust {
+        field: TunableField,
+        delta: f64,
+    },
+    /// 直接设置为指定值
+    Set {
+        field: TunableField,
+        value: f64,
+    },
+    Show,
+    Save,
+    Help,
+}
+
+impl TuningCommand {
+    fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let command = match name.to_lowercase().as_str() {
+            "show" => TuningCommand::Show,
+            "save" => TuningCommand::Save,
+            "help" => TuningCommand::Help,
+            _ => {
+                let field =
+                    TunableField::parse(name).ok_or_else(|| format!("未知的命令: {}", name))?;
+                let amount = words
+                    .next()
+                    .ok_or_else(|| format!("{} 需要指定数值", name))?;
+                let number: f64 = amount
+                    .parse()
+                    .ok()
+                    .filter(|n: &f64| n.is_finite())
+                    .ok_or_else(|| format!("无效的数值: {}", amount))?;
+                if amount.starts_with(['+', '-']) {
+                    TuningCommand::Adjust {
+                        field,
+                        delta: number,
+                    }
+                } else {
+                    TuningCommand::Set {
+                        field,
+                        value: number,
+                    }
+                }
+            }
+        };
+        if let Some(extra) = words.next() {
+            return Err(format!("多余的参数: {}", extra));
+        }
+        Ok(command)
+    }
+}
+
+/// 在后台线程中从标准输入读取调参命令
+///
+/// `config` 为配置文件中的原始配置（未应用配置档案）。每次调整后重新校验并应用配置档案，
+/// 通过与配置热重载相同的通道发送给主循环；校验失败时保留原值。调整的是基础配置，
+/// 配置档案中覆盖了同一参数时以档案为准。`save` 会整体重写配置文件，手写的注释不会保留。
+/// 标准输入关闭或接收方断开后线程退出。
+pub fn spawn_stdin_tuner(
+    config_path: PathBuf,
+    mut config: ControllerConfig,
+    profile: Option<String>,
+    tx: Sender<Result<ControllerConfig, String>>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        println!("调参模式已启用，输入 help 查看可用命令");
+        for line in io::stdin().lock().lines().map_while(Result::ok) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let command = match TuningCommand::parse(&line) {
+                Ok(command) => command,
+                Err(e) => {
+                    println!("{}，输入 help 查看可用命令", e);
+                    continue;
+                }
+            };
+
+            let (field, value) = match command {
+                TuningCommand::Adjust { field, delta } => (field, field.get(&config) + delta),
+                TuningCommand::Set { field, value } => (field, value),
+                TuningCommand::Show => {
+                    for &(short, field) in FIELDS {
+                        println!(" - {} ({}) = {}", short, field.key(), field.get(&config));
+                    }
+                    continue;
+                }
+                TuningCommand::Save => {
+                    match config.save_to_file(&config_path) {
+                        Ok(()) => println!("已保存到 {}", config_path.display()),
+                        Err(e) => println!("保存失败: {}", e),
+                    }
+                    continue;
+                }
+                TuningCommand::Help => {
+                    println!("{}", TUNING_HELP);
+                    continue;
+                }
+            };
+
+            let mut tuned = config.clone();
+            field.set(&mut tuned, value);
+            match tuned.clone().activate(profile.as_deref()) {
+                Ok(active) => {
+                    println!("{} = {}", field.key(), field.get(&tuned));
+                    config = tuned;
+                    if tx.send(Ok(active)).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => println!("未应用: {}", e),
+            }
+        }
+    })
+}